@@ -1,6 +1,6 @@
 use proc_macro::{self, TokenStream};
-use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, GenericArgument, PathArguments, Type};
 
 #[proc_macro_derive(BotState)]
 pub fn mobot_derive_botstate(input: TokenStream) -> TokenStream {
@@ -19,3 +19,105 @@ pub fn mobot_derive_request(input: TokenStream) -> TokenStream {
     };
     output.into()
 }
+
+/// Generates a `with_<field>(mut self, value: impl Into<T>) -> Self` setter for every
+/// `Option<T>` field of a struct. The field's doc comment is copied to the setter.
+///
+/// Fields marked `#[builder(skip)]` are left alone, so they can have hand-written setters.
+/// Non-`Option` fields (e.g., `#[serde(flatten)]` base structs like `EditMessageBase`) are
+/// ignored -- derive `BotRequestBuilder` on the base struct instead.
+#[proc_macro_derive(BotRequestBuilder, attributes(builder))]
+pub fn mobot_derive_request_builder(input: TokenStream) -> TokenStream {
+    let DeriveInput {
+        ident,
+        generics,
+        data,
+        ..
+    } = parse_macro_input!(input);
+
+    let fields = match data {
+        Data::Struct(s) => match s.fields {
+            Fields::Named(fields) => fields.named,
+            _ => {
+                return syn::Error::new_spanned(
+                    ident,
+                    "BotRequestBuilder requires a struct with named fields",
+                )
+                .to_compile_error()
+                .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(
+                ident,
+                "BotRequestBuilder can only be derived for structs",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+
+    let mut setters = vec![];
+    for field in fields {
+        let mut skip = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("builder")) {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported builder attribute, expected `skip`"))
+                }
+            });
+
+            if let Err(err) = result {
+                return err.to_compile_error().into();
+            }
+        }
+
+        let inner = match option_inner(&field.ty) {
+            Some(inner) if !skip => inner,
+            _ => continue,
+        };
+
+        let name = field.ident.unwrap();
+        let setter = format_ident!("with_{}", name);
+        let docs = field.attrs.iter().filter(|a| a.path().is_ident("doc"));
+
+        setters.push(quote! {
+            #(#docs)*
+            pub fn #setter(mut self, #name: impl Into<#inner>) -> Self {
+                self.#name = Some(#name.into());
+                self
+            }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let output = quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#setters)*
+        }
+    };
+    output.into()
+}
+
+/// Returns `T` if `ty` is `Option<T>`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -7,14 +7,10 @@ use std::env;
 async fn main() {
     let client = Client::new(env::var("TELEGRAM_TOKEN").unwrap());
 
-    // Create a router with a custom error handler
-    let mut router = Router::new(client).with_error_handler(|api, chat_id, _, err| async move {
-        api.send_message(&api::SendMessageRequest::new(
-            chat_id,
-            format!("Failed: {}", err),
-        ))
-        .await
-        .unwrap();
+    // Create a router with a custom error handler. Return ErrorAction::Silent instead
+    // to log the error without replying.
+    let mut router = Router::new(client).with_error_handler(|_, _, _, err| async move {
+        ErrorAction::Reply(format!("Failed: {}", err))
     });
 
     // Return an error from the handler
@@ -1,25 +1,10 @@
 /// This is a simple bot that download all sent photos in directory beside bot executable
-use mobot::{
-    api::{DownloadRequest, GetFileRequest},
-    *,
-};
+use mobot::*;
 use std::env;
 
 async fn get_user_photo(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
-    let telegram_file = e
-        .api
-        .get_file(&GetFileRequest::new(
-            e.update.photo().unwrap().last().unwrap().clone().file_id,
-        ))
-        .await?;
-    let mut file = std::fs::File::create(telegram_file.file_id)?;
-    let mut content = std::io::Cursor::new(
-        e.api
-            .download_file(&DownloadRequest::new(telegram_file.file_path.unwrap()))
-            .await
-            .unwrap(),
-    );
-    std::io::copy(&mut content, &mut file)?;
+    let file_id = &e.update.largest_photo()?.file_id;
+    std::fs::write(file_id, e.download_largest_photo().await?)?;
     Ok(Action::ReplyText("Photo saved".into()))
 }
 
@@ -1,6 +1,5 @@
-/// This is a simple ping bot that responds to every message with a sticker and
-/// a text message.
-
+/// This is a simple inline bot that answers every inline query with the uptime of the
+/// host.
 #[macro_use]
 extern crate log;
 
@@ -24,19 +23,22 @@ async fn get_uptime() -> anyhow::Result<String> {
     Ok(String::from_utf8(output.stdout)?)
 }
 
-/// The handler for the chat. This is a simple function that takes a `ChatEvent`
-/// and returns a `ChatAction`.
+/// The handler for inline queries. Inline queries aren't sent in a chat, so this answers
+/// the query with a single article containing the uptime.
 async fn handle_query_event(e: Event, state: State<QueryState>) -> Result<Action, anyhow::Error> {
     let mut state = state.get().write().await;
     state.counter += 1;
 
-    e.send_message(format!(
+    let uptime = format!(
         "uptime({}): {}",
         state.counter,
         get_uptime()
             .await
             .or(Err(anyhow!("Failed to get uptime")))?
-    ))
+    );
+    e.answer(vec![api::InlineQueryResultArticle::new(
+        "uptime", "Uptime", uptime,
+    )])
     .await?;
 
     Ok(Action::Done)
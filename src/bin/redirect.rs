@@ -1,6 +1,5 @@
-use anyhow::anyhow;
-/// This is a simple bot that replies with "Hello world!" to every message.
-use mobot::{api::BotCommand, *};
+/// This is a simple bot that replies with the same message on multiple bot accounts.
+use mobot::*;
 use std::env;
 
 #[derive(Clone, Default, BotState)]
@@ -11,63 +10,39 @@ struct App {
 #[tokio::main]
 async fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
+    if args.len() < 3 {
         println!("Usage: {} <message> <token> <token> ...", args[0]);
         return;
     }
 
     println!("{:?}", args);
 
-    let mut handles = vec![];
     let app = App {
         message: args[1].clone(),
     };
 
-    for token in args.iter().skip(2) {
-        let token = token.clone();
-        let app = app.clone();
-
-        let handle = tokio::spawn(async move {
-            let client = Client::new(token);
-            let mut router = Router::new(client).with_state(app);
-
-            let commands = vec![
-                BotCommand {
-                    command: "start".into(),
-                    description: "Start the bot".into(),
-                },
-                BotCommand {
-                    command: "help".into(),
-                    description: "Show help".into(),
-                },
-            ];
-
-            router
-                .api
-                .set_my_commands(&api::SetMyCommandsRequest {
-                    commands,
-                    ..Default::default()
-                })
-                .await
-                .unwrap();
-
-            router.add_route(Route::Default, |e: Event, s: State<App>| async move {
-                let message = e.update.get_message_or_post()?;
+    // All the accounts share the same handlers.
+    let mut router = Router::new(Client::new(args[2].clone())).with_state(app);
+    for token in args.iter().skip(3) {
+        router = router.with_account(Client::new(token.clone()));
+    }
 
-                if message.text.as_ref().ok_or(anyhow!("bad text"))? == "/help" {
-                    return Ok(Action::ReplyText(
-                        "This bot does nothing much really :-/".into(),
-                    ));
-                }
+    commands!(router,
+        "start": "Start the bot" => |_: Event, s: State<App>| async move {
+            let message = s.get().read().await.message.clone();
+            Ok(Action::ReplyText(message))
+        },
+        "help": "Show help" => |_: Event, _: State<App>| async move {
+            Ok(Action::ReplyText("This bot does nothing much really :-/".into()))
+        },
+    );
 
-                let message = s.get().read().await.message.clone();
-                Ok(Action::ReplyText(message))
-            });
-            router.start().await;
-        });
+    router.set_my_commands().await.unwrap();
 
-        handles.push(handle);
-    }
+    router.add_route(Route::Default, |_: Event, s: State<App>| async move {
+        let message = s.get().read().await.message.clone();
+        Ok(Action::ReplyText(message))
+    });
 
-    _ = futures::future::join_all(handles).await;
+    router.start().await;
 }
@@ -1,20 +1,99 @@
+use crate::{
+    api::{ChatId, SendMessageRequest},
+    Text,
+};
+
 /// `Action` represents an action to take after handling a chat event.
 #[derive(Debug, Clone)]
 pub enum Action {
     /// Continue to the next handler.
     Next,
 
+    /// Continue to the next handler, passing it a value, e.g., the result of parsing or
+    /// validating the message. The next handler reads it with
+    /// [`Event::prev_value`](crate::Event::prev_value). This lets you build pipelines of
+    /// handlers (validate, then format, then send) without stashing intermediate results
+    /// in the chat state: the value only lives while the current update is dispatched,
+    /// whereas the state persists across updates.
+    NextWith(serde_json::Value),
+
+    /// Answer the callback query that triggered this event, with an optional notification
+    /// text, and continue to the next handler. Telegram shows a loading indicator on the
+    /// button until the query is answered, so use this in handlers that only record a
+    /// button press. See also [`Event::acknowledge_callback`](crate::Event::acknowledge_callback).
+    AckCallback(Option<String>),
+
     /// Stop handling events.
     Done,
 
     /// Reply to the message with the given text and stop handling events. This
-    /// is equivalent to `e.send_text(...)` followed by `Ok(Action::Done)`.
+    /// is equivalent to `e.send_message(...)` followed by `Ok(Action::Done)`.
+    ///
+    /// This is fire-and-forget: the sent message is discarded by the router. If you
+    /// need the sent [`Message`](crate::api::Message) (e.g., to edit it later), use
+    /// [`Event::reply`](crate::Event::reply) or [`Event::send_message`](crate::Event::send_message)
+    /// instead.
     ReplyText(String),
 
+    /// Reply to the message with the given text, and continue to the next handler like
+    /// `Action::Next`. The ID of the sent message is available to the next handlers via
+    /// [`Event::last_sent_message_id`](crate::Event::last_sent_message_id), so they can
+    /// refine the reply, e.g., with `editMessageText`.
+    ReplyAndContinue(String),
+
     /// Same as ReplyText, but with MarkdownV2 formatting. Make
     /// sure to escape any user input!
     ReplyMarkdown(String),
 
     /// Reply to the message with the given sticker and stop running handlers.
     ReplySticker(String),
+
+    /// Reply to the message with the given [`Text`] and stop handling events. The parse
+    /// mode is derived from the `Text` variant, so this covers `ReplyText` and
+    /// `ReplyMarkdown` (as well as HTML).
+    Reply(Text),
+
+    /// Send the given message and stop handling events. Use this for replies that need
+    /// more than text, e.g., a reply keyboard or reply parameters. The `chat_id` is filled
+    /// in by the router, so it can be left as the default:
+    ///
+    /// ```
+    /// # use mobot::*;
+    /// let action = Action::ReplyWith(
+    ///     api::SendMessageRequest::new(0, "*Pick one*")
+    ///         .with_parse_mode(api::ParseMode::MarkdownV2)
+    ///         .with_reply_to_message_id(42)
+    ///         .into(),
+    /// );
+    /// ```
+    ReplyWith(Box<SendMessageRequest>),
+
+    /// Forward the message that triggered this event to the chat `to_chat_id` (a chat ID,
+    /// or the username of a channel), and stop handling events.
+    Forward { to_chat_id: ChatId },
+
+    /// Delete the message that triggered this event, and stop handling events. The bot
+    /// needs the `can_delete_messages` admin right to delete other users' messages in groups.
+    Delete,
+}
+
+/// `ErrorAction` is returned by the router's error handler (see
+/// [`Router::with_error_handler`](crate::Router::with_error_handler)) to tell the router what
+/// to do with a handler error.
+#[derive(Debug, Clone)]
+pub enum ErrorAction {
+    /// Send the given text to the chat that caused the error.
+    Reply(String),
+
+    /// Don't send anything to the chat. The error handler is expected to have logged
+    /// (or otherwise dealt with) the error.
+    Silent,
+}
+
+/// Error handlers that return `()` take care of replying themselves, so the router
+/// stays silent.
+impl From<()> for ErrorAction {
+    fn from(_: ()) -> Self {
+        ErrorAction::Silent
+    }
 }
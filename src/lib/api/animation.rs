@@ -0,0 +1,110 @@
+use mobot_derive::{BotRequest, BotRequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    chat::ChatId, message::Message, InputFile, ParseMode, PhotoSize, ReplyParameters, API,
+};
+
+/// An animation file (GIF or H.264/MPEG-4 AVC video without sound).
+/// <https://core.telegram.org/bots/api#animation>
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Animation {
+    /// Identifier for this file, which can be used to download or reuse the file
+    pub file_id: String,
+
+    /// Video width as defined by sender
+    pub width: i64,
+
+    /// Video height as defined by sender
+    pub height: i64,
+
+    /// Duration of the video in seconds as defined by sender
+    pub duration: i64,
+
+    /// Animation thumbnail as defined by sender
+    pub thumbnail: Option<PhotoSize>,
+
+    /// Original animation filename as defined by sender
+    pub file_name: Option<String>,
+
+    /// MIME type of the file as defined by sender
+    pub mime_type: Option<String>,
+
+    /// File size
+    pub file_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest, BotRequestBuilder)]
+pub struct SendAnimationRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Animation to send. Pass a file_id to send an animation that exists on the
+    /// Telegram servers, an HTTP URL for Telegram to get the animation from the
+    /// Internet, or upload a new animation with [`InputFile::upload`].
+    pub animation: InputFile,
+
+    /// Duration of sent animation in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+
+    /// Animation width
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i64>,
+
+    /// Animation height
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i64>,
+
+    /// Animation caption, 0-1024 characters after entities parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+
+    /// Mode for parsing entities in the animation caption
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+
+    /// If the message is a reply, ID of the original message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+impl SendAnimationRequest {
+    pub fn new(chat_id: impl Into<ChatId>, animation: impl Into<InputFile>) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            animation: animation.into(),
+            duration: None,
+            width: None,
+            height: None,
+            caption: None,
+            parse_mode: None,
+            reply_parameters: None,
+        }
+    }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+}
+
+impl API {
+    /// Send an animation (GIF or H.264/MPEG-4 AVC video without sound). New files are
+    /// uploaded as multipart/form-data.
+    pub async fn send_animation(&self, req: &SendAnimationRequest) -> anyhow::Result<Message> {
+        match req.animation {
+            InputFile::Upload {
+                ref file_name,
+                ref data,
+            } => {
+                self.client
+                    .post_multipart("sendAnimation", req, "animation", file_name, data.clone())
+                    .await
+            }
+            InputFile::Remote(_) => self.client.post("sendAnimation", req).await,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -9,18 +11,52 @@ use crate::Client;
 pub struct API {
     /// The underlying HTTP client.
     pub client: Client,
+
+    /// Cached `file_path`s by `file_id`, along with when they were fetched. See
+    /// [`API::download_by_file_id`].
+    pub(crate) file_paths: Mutex<HashMap<String, (String, Instant)>>,
 }
 
 impl API {
     /// Returns a new Telegram API client.
     pub fn new(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            file_paths: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Call an arbitrary Bot API method with raw JSON parameters, and return the raw JSON
+    /// result. Use this for methods that don't have typed wrappers yet.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # async fn example(api: &API) -> anyhow::Result<()> {
+    /// let result = api
+    ///     .call_raw("setMessageReaction", serde_json::json!({
+    ///         "chat_id": 1234,
+    ///         "message_id": 42,
+    ///         "reaction": [{"type": "emoji", "emoji": "👍"}],
+    ///     }))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn call_raw(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value> {
+        self.client.post(method, &params).await
     }
 }
 
 /// Request is a trait that all Telegram API requests must implement.
 pub trait Request: Serialize + Send + Sync {}
 
+/// Raw JSON parameters, see [`API::call_raw`].
+impl Request for serde_json::Value {}
+
 /// APIError wraps error messages returned by the Telegram API.
 #[derive(Error, Debug)]
 pub enum ApiError {
@@ -32,8 +68,44 @@ pub enum ApiError {
 
     #[error("No result")]
     NoResult,
+
+    /// The server returned a response that isn't a Telegram API response, e.g., an HTML
+    /// error page from a proxy.
+    #[error("HTTP error {status}: {body_snippet}")]
+    Http { status: u16, body_snippet: String },
+
+    /// The Telegram API returned an error with a non-2xx HTTP status code, e.g., 429 when
+    /// rate limited, or 401 for a bad token.
+    #[error("Telegram error ({status}): {description}")]
+    Status { status: u16, description: String },
+
+    /// Telegram rejected the request with 409 (Conflict). For `getUpdates`, this means
+    /// another instance of the bot is polling with the same token, or a webhook is set.
+    #[error("Telegram conflict (409): {0}")]
+    Conflict(String),
+
+    /// The Telegram API returned a successful response, but its result couldn't be parsed,
+    /// e.g., an update with a field of an unexpected type.
+    #[error("Malformed result: {0}")]
+    Malformed(String),
 }
 
+impl ApiError {
+    /// Returns the HTTP status code of the response that caused this error, if known. Use
+    /// this to classify errors, e.g., 429 (Too Many Requests) vs 401 (Unauthorized).
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Http { status, .. } | Self::Status { status, .. } => Some(*status),
+            Self::Conflict(_) => Some(409),
+            _ => None,
+        }
+    }
+}
+
+/// The maximum number of characters of a non-JSON response body to include in
+/// [`ApiError::Http`].
+const BODY_SNIPPET_LEN: usize = 200;
+
 /// This is a wrapper around the Telegram API response. If `ok` is `true`, then
 /// `result` is guaranteed to be `Some`. If `ok` is `false`, then `description`
 /// is guaranteed to be `Some`, with a description of the error.
@@ -55,6 +127,45 @@ impl<'de, T: Deserialize<'de>> ApiResponse<T> {
         let response: ApiResponse<T> = serde_json::from_str(data)?;
         Ok(response)
     }
+
+    /// Parse an HTTP response body with the given status code. If the body isn't a valid
+    /// API response, this returns an [`ApiError::Http`] with the status code and the start
+    /// of the body. If the API returned an error with a non-2xx status, this returns an
+    /// [`ApiError::Status`]. If it's a valid API response, but the result isn't a `T`, this
+    /// returns an [`ApiError::Malformed`].
+    pub fn from_http(status: u16, body: &'de str) -> Result<Self> {
+        let response: ApiResponse<T> = serde_json::from_str(body).map_err(|err| {
+            if serde_json::from_str::<ApiResponse<serde_json::Value>>(body).is_ok() {
+                ApiError::Malformed(err.to_string())
+            } else {
+                ApiError::Http {
+                    status,
+                    body_snippet: body.trim().chars().take(BODY_SNIPPET_LEN).collect(),
+                }
+            }
+        })?;
+
+        if !response.ok && status == 409 {
+            return Err(ApiError::Conflict(
+                response
+                    .description
+                    .unwrap_or("No error description".to_string()),
+            )
+            .into());
+        }
+
+        if !response.ok && !(200..300).contains(&status) {
+            return Err(ApiError::Status {
+                status,
+                description: response
+                    .description
+                    .unwrap_or("No error description".to_string()),
+            }
+            .into());
+        }
+
+        Ok(response)
+    }
 }
 
 impl<T> ApiResponse<T> {
@@ -84,15 +195,18 @@ impl<T> ApiResponse<T> {
     }
 
     /// Returns the result of the request, if `ok` is `true`. Otherwise, returns
-    /// an error.
+    /// an error. Conflicts (descriptions starting with "Conflict:") are returned as
+    /// [`ApiError::Conflict`], even without an HTTP status.
     pub fn result(&self) -> Result<&T> {
         if !self.ok {
-            return Err(ApiError::AppError(
-                self.description
-                    .clone()
-                    .unwrap_or("No error description".to_string()),
-            )
-            .into());
+            let description = self
+                .description
+                .clone()
+                .unwrap_or("No error description".to_string());
+            if description.starts_with("Conflict:") {
+                return Err(ApiError::Conflict(description).into());
+            }
+            return Err(ApiError::AppError(description).into());
         }
 
         if self.result.is_none() {
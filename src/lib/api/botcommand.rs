@@ -1,9 +1,9 @@
 use mobot_derive::BotRequest;
 use serde::{Deserialize, Serialize};
 
-use super::API;
+use super::{ChatId, API};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct BotCommand {
     /// Text of the command, 1-32 characters. Can contain only lowercase English
     /// letters, digits and underscores.
@@ -13,7 +13,7 @@ pub struct BotCommand {
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Clone, BotRequest)]
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest)]
 /// This strcut represents the scope type for BotCommandScope.
 pub enum BotCommnandScopeType {
     #[serde(rename = "default")]
@@ -32,19 +32,59 @@ pub enum BotCommnandScopeType {
     ChatMember,
 }
 
-#[derive(Debug, Serialize, Clone, BotRequest)]
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest)]
 pub struct BotCommandScope {
     #[serde(rename = "type")]
     pub type_: BotCommnandScopeType,
 
+    /// Chat ID or username of the target chat, for the `Chat`, `ChatAdministrators` and
+    /// `ChatMember` scopes
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub chat_id: Option<i64>,
+    pub chat_id: Option<ChatId>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_id: Option<i64>,
 }
 
-#[derive(Default, Debug, Serialize, Clone, BotRequest)]
+impl BotCommandScope {
+    /// Returns a scope of the given type. Use [`BotCommandScope::chat`],
+    /// [`BotCommandScope::chat_administrators`] and [`BotCommandScope::chat_member`] for
+    /// scopes that refer to a chat.
+    pub fn new(type_: BotCommnandScopeType) -> Self {
+        Self {
+            type_,
+            chat_id: None,
+            user_id: None,
+        }
+    }
+
+    /// All members of the given chat.
+    pub fn chat(chat_id: impl Into<ChatId>) -> Self {
+        Self {
+            chat_id: Some(chat_id.into()),
+            ..Self::new(BotCommnandScopeType::Chat)
+        }
+    }
+
+    /// All administrators of the given group or supergroup.
+    pub fn chat_administrators(chat_id: impl Into<ChatId>) -> Self {
+        Self {
+            chat_id: Some(chat_id.into()),
+            ..Self::new(BotCommnandScopeType::ChatAdministrators)
+        }
+    }
+
+    /// A single member of the given group or supergroup.
+    pub fn chat_member(chat_id: impl Into<ChatId>, user_id: i64) -> Self {
+        Self {
+            chat_id: Some(chat_id.into()),
+            user_id: Some(user_id),
+            ..Self::new(BotCommnandScopeType::ChatMember)
+        }
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
 pub struct SetMyCommandsRequest {
     /// At most 100 commands can be specified.
     pub commands: Vec<BotCommand>,
@@ -62,7 +102,7 @@ pub struct SetMyCommandsRequest {
     pub language_code: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone, BotRequest)]
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest)]
 pub struct DeleteMyCommandsRequest {
     /// A JSON-serialized object, describing scope of users for which the commands are
     /// relevant. Defaults to BotCommandScopeDefault.
@@ -74,7 +114,16 @@ pub struct DeleteMyCommandsRequest {
     pub language_code: Option<String>,
 }
 
-type GetMyCommandsRequest = DeleteMyCommandsRequest;
+impl DeleteMyCommandsRequest {
+    pub fn new(scope: Option<BotCommandScope>, language_code: Option<String>) -> Self {
+        Self {
+            scope,
+            language_code,
+        }
+    }
+}
+
+pub type GetMyCommandsRequest = DeleteMyCommandsRequest;
 
 impl API {
     pub async fn get_my_commands(
@@ -91,4 +140,59 @@ impl API {
     pub async fn delete_my_commands(&self, req: &DeleteMyCommandsRequest) -> anyhow::Result<bool> {
         self.client.post("deleteMyCommands", req).await
     }
+
+    /// Get the commands for the given scope and language. This is a shortcut for
+    /// [`API::get_my_commands`].
+    pub async fn get_commands(
+        &self,
+        scope: BotCommandScope,
+        language_code: Option<&str>,
+    ) -> anyhow::Result<Vec<BotCommand>> {
+        self.get_my_commands(&GetMyCommandsRequest::new(
+            Some(scope),
+            language_code.map(String::from),
+        ))
+        .await
+    }
+
+    /// Set the commands for several scopes and languages at once, e.g., to publish
+    /// translated command menus. The `setMyCommands` calls are issued concurrently, and
+    /// if any of them fail, the returned error lists every failed scope and language.
+    pub async fn set_commands_multi(
+        &self,
+        commands: Vec<(BotCommandScope, Option<String>, Vec<BotCommand>)>,
+    ) -> anyhow::Result<()> {
+        let results = futures::future::join_all(commands.into_iter().map(
+            |(scope, language_code, commands)| {
+                let req = SetMyCommandsRequest {
+                    commands,
+                    scope: Some(scope.clone()),
+                    language_code: language_code.clone(),
+                };
+                async move {
+                    let result = self.set_my_commands(&req).await;
+                    (scope, language_code, result)
+                }
+            },
+        ))
+        .await;
+
+        let errors: Vec<String> = results
+            .into_iter()
+            .filter_map(|(scope, language_code, result)| {
+                let err = result.err()?;
+                Some(format!(
+                    "{:?} in {}: {}",
+                    scope,
+                    language_code.as_deref().unwrap_or("all languages"),
+                    err
+                ))
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            anyhow::bail!("setMyCommands failed for {}", errors.join("; "));
+        }
+        Ok(())
+    }
 }
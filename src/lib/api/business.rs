@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+use super::user::User;
+
+/// Describes the connection of the bot with a business account. Bots receive a
+/// `business_connection` update when they're connected to or disconnected from a
+/// business account, or the connection is edited.
+/// <https://core.telegram.org/bots/api#businessconnection>
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct BusinessConnection {
+    /// Unique identifier of the business connection
+    pub id: String,
+
+    /// Business account user that created the business connection
+    pub user: User,
+
+    /// Identifier of a private chat with the user who created the business connection
+    pub user_chat_id: i64,
+
+    /// Date the connection was established in Unix time
+    pub date: i64,
+
+    /// True, if the bot can act on behalf of the business account in chats that were
+    /// active in the last 24 hours
+    pub can_reply: bool,
+
+    /// True, if the connection is active
+    pub is_enabled: bool,
+}
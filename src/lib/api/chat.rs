@@ -1,9 +1,10 @@
 use mobot_derive::BotRequest;
 use serde::{Deserialize, Serialize};
 
-use super::API;
+use super::{User, API};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Chat {
     /// Unique identifier for this chat. This number may be greater than 32 bits and some programming languages may have difficulty/silent defects in interpreting it. But it is smaller than 52 bits, so a signed 64 bit integer or double-precision float type are safe for storing this identifier.
     pub id: i64,
@@ -32,6 +33,59 @@ pub struct Chat {
     pub is_forum: Option<bool>,
 }
 
+/// `ChatId` identifies the target of a request: either a numeric chat ID, or the
+/// username of a channel or supergroup (in the format `@channelusername`). It serializes
+/// as an integer or a string respectively.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatId {
+    Id(i64),
+    Username(String),
+}
+
+impl ChatId {
+    /// Returns the numeric chat ID, or `None` if this is a username.
+    pub fn id(&self) -> Option<i64> {
+        match self {
+            ChatId::Id(id) => Some(*id),
+            ChatId::Username(_) => None,
+        }
+    }
+}
+
+impl Default for ChatId {
+    fn default() -> Self {
+        ChatId::Id(0)
+    }
+}
+
+impl std::fmt::Display for ChatId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatId::Id(id) => write!(f, "{}", id),
+            ChatId::Username(username) => write!(f, "{}", username),
+        }
+    }
+}
+
+impl From<i64> for ChatId {
+    fn from(id: i64) -> Self {
+        ChatId::Id(id)
+    }
+}
+
+impl From<&str> for ChatId {
+    fn from(username: &str) -> Self {
+        ChatId::Username(username.into())
+    }
+}
+
+impl From<String> for ChatId {
+    fn from(username: String) -> Self {
+        ChatId::Username(username)
+    }
+}
+
 impl<T: Into<String>> From<T> for Chat {
     fn from(s: T) -> Self {
         let from = s.into();
@@ -44,7 +98,7 @@ impl<T: Into<String>> From<T> for Chat {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChatAction {
     #[serde(rename = "typing")]
     Typing,
@@ -71,7 +125,7 @@ pub enum ChatAction {
 #[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
 pub struct SendChatActionRequest {
     /// Unique identifier for the target chat or username of the target channel (in the format @channelusername)
-    pub chat_id: i64,
+    pub chat_id: ChatId,
 
     /// Unique identifier for the target message thread.
     pub message_thread_id: Option<i64>,
@@ -81,19 +135,333 @@ pub struct SendChatActionRequest {
 }
 
 impl SendChatActionRequest {
-    pub fn new(chat_id: i64, action: ChatAction) -> Self {
+    pub fn new(chat_id: impl Into<ChatId>, action: ChatAction) -> Self {
         Self {
-            chat_id,
+            chat_id: chat_id.into(),
             action,
             message_thread_id: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
+pub struct LeaveChatRequest {
+    /// Unique identifier for the target chat or username of the target supergroup or channel
+    pub chat_id: ChatId,
+}
+
+impl LeaveChatRequest {
+    pub fn new(chat_id: impl Into<ChatId>) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
+pub struct GetChatMemberCountRequest {
+    /// Unique identifier for the target chat or username of the target supergroup or channel
+    pub chat_id: ChatId,
+}
+
+impl GetChatMemberCountRequest {
+    pub fn new(chat_id: impl Into<ChatId>) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
+pub struct GetChatMemberRequest {
+    /// Unique identifier for the target chat or username of the target supergroup or channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier of the target user
+    pub user_id: i64,
+}
+
+impl GetChatMemberRequest {
+    pub fn new(chat_id: impl Into<ChatId>, user_id: i64) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            user_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
+pub struct PinChatMessageRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Identifier of a message to pin
+    pub message_id: i64,
+
+    /// Pass True if it is not necessary to send a notification to all chat members
+    /// about the new pinned message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+}
+
+impl PinChatMessageRequest {
+    pub fn new(chat_id: impl Into<ChatId>, message_id: i64) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            message_id,
+            disable_notification: None,
+        }
+    }
+
+    pub fn with_disable_notification(mut self, disable_notification: bool) -> Self {
+        self.disable_notification = Some(disable_notification);
+        self
+    }
+}
+
+/// `ChatInviteLink` represents an invite link for a chat.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatInviteLink {
+    /// The invite link. If the link was created by another chat administrator, then the
+    /// second part of the link will be replaced with “…”.
+    pub invite_link: String,
+
+    /// Creator of the link
+    pub creator: User,
+
+    /// True, if users joining the chat via the link need to be approved by chat
+    /// administrators
+    pub creates_join_request: bool,
+
+    /// True, if the link is primary
+    pub is_primary: bool,
+
+    /// True, if the link is revoked
+    pub is_revoked: bool,
+
+    /// Invite link name
+    pub name: Option<String>,
+
+    /// Point in time (Unix timestamp) when the link will expire or has been expired
+    pub expire_date: Option<i64>,
+}
+
+/// `ChatJoinRequest` represents a request to join a chat, sent to the bot if it's an
+/// administrator with the `can_invite_users` right. See [`Route::ChatJoinRequest`](crate::Route::ChatJoinRequest).
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatJoinRequest {
+    /// Chat to which the request was sent
+    pub chat: Chat,
+
+    /// User that sent the join request
+    pub from: User,
+
+    /// Identifier of a private chat with the user who sent the join request. The bot can
+    /// use this identifier for 5 minutes to send messages until the join request is
+    /// processed, assuming no other administrator contacted the user.
+    pub user_chat_id: i64,
+
+    /// Date the request was sent in Unix time
+    pub date: i64,
+
+    /// Bio of the user
+    pub bio: Option<String>,
+
+    /// Chat invite link that was used by the user to send the join request
+    pub invite_link: Option<ChatInviteLink>,
+}
+
+/// `ChatMember` contains information about one member of a chat.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatMember {
+    /// The member's status in the chat: “creator”, “administrator”, “member”,
+    /// “restricted”, “left” or “kicked”
+    pub status: String,
+
+    /// Information about the user
+    pub user: User,
+
+    /// True, if the user is a member of the chat at the moment of the request. Only set
+    /// for restricted users.
+    pub is_member: Option<bool>,
+
+    /// True, if the user's presence in the chat is hidden. Only set for owners and
+    /// administrators.
+    pub is_anonymous: Option<bool>,
+
+    /// Custom title for this user. Only set for owners and administrators.
+    pub custom_title: Option<String>,
+
+    /// Date when restrictions or the ban will be lifted for this user; Unix time. If 0,
+    /// then the user is restricted or banned forever.
+    pub until_date: Option<i64>,
+
+    /// True, if the administrator can access the chat event log, get boost list, see
+    /// hidden supergroup and channel members, report spam messages and ignore slow mode.
+    /// Only set for administrators.
+    pub can_manage_chat: Option<bool>,
+
+    /// True, if the administrator can delete messages of other users. Only set for
+    /// administrators.
+    pub can_delete_messages: Option<bool>,
+
+    /// True, if the administrator can restrict, ban or unban chat members. Only set for
+    /// administrators.
+    pub can_restrict_members: Option<bool>,
+
+    /// True, if the administrator can add new administrators. Only set for administrators.
+    pub can_promote_members: Option<bool>,
+
+    /// True, if the user is allowed to change the chat title, photo and other settings.
+    /// Only set for administrators and restricted users.
+    pub can_change_info: Option<bool>,
+
+    /// True, if the user is allowed to invite new users to the chat. Only set for
+    /// administrators and restricted users.
+    pub can_invite_users: Option<bool>,
+
+    /// True, if the user is allowed to pin messages. Only set for administrators and
+    /// restricted users.
+    pub can_pin_messages: Option<bool>,
+
+    /// True, if the user is allowed to send text messages. Only set for restricted users.
+    pub can_send_messages: Option<bool>,
+}
+
+impl ChatMember {
+    /// Returns true if the user is in the chat (including restricted users who haven't
+    /// left), as opposed to having left or been banned.
+    pub fn is_member(&self) -> bool {
+        match self.status.as_str() {
+            "creator" | "administrator" | "member" => true,
+            "restricted" => self.is_member.unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the user is the owner or an administrator of the chat.
+    pub fn is_admin(&self) -> bool {
+        matches!(self.status.as_str(), "creator" | "administrator")
+    }
+
+    /// Returns true if the user can delete other users' messages. Owners can always
+    /// delete messages, administrators need the `can_delete_messages` right.
+    pub fn can_delete_messages(&self) -> bool {
+        self.status == "creator" || self.can_delete_messages == Some(true)
+    }
+}
+
+/// `ChatMemberUpdated` represents changes in the status of a chat member. See
+/// [`Route::MyChatMember`](crate::Route::MyChatMember) and
+/// [`Route::ChatMember`](crate::Route::ChatMember).
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatMemberUpdated {
+    /// Chat the user belongs to
+    pub chat: Chat,
+
+    /// Performer of the action, which resulted in the change
+    pub from: User,
+
+    /// Date the change was done in Unix time
+    pub date: i64,
+
+    /// Previous information about the chat member
+    pub old_chat_member: ChatMember,
+
+    /// New information about the chat member
+    pub new_chat_member: ChatMember,
+
+    /// Chat invite link, which was used by the user to join the chat; for joining by
+    /// invite link events only.
+    pub invite_link: Option<ChatInviteLink>,
+
+    /// True, if the user joined the chat after sending a direct join request without
+    /// using an invite link and being approved by an administrator
+    pub via_join_request: Option<bool>,
+}
+
+impl ChatMemberUpdated {
+    /// Returns true if the user joined (or was added to) the chat.
+    pub fn joined(&self) -> bool {
+        !self.old_chat_member.is_member() && self.new_chat_member.is_member()
+    }
+
+    /// Returns true if the user left (or was removed from) the chat.
+    pub fn left(&self) -> bool {
+        self.old_chat_member.is_member() && !self.new_chat_member.is_member()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, BotRequest)]
+pub struct ApproveChatJoinRequestRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier of the target user
+    pub user_id: i64,
+}
+
+impl ApproveChatJoinRequestRequest {
+    pub fn new(chat_id: impl Into<ChatId>, user_id: i64) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            user_id,
+        }
+    }
+}
+
+pub type DeclineChatJoinRequestRequest = ApproveChatJoinRequestRequest;
+
 /// API methods for sending, editing, and deleting messages.
 impl API {
     /// Send a message.
     pub async fn send_chat_action(&self, req: &SendChatActionRequest) -> anyhow::Result<bool> {
         self.client.post("sendChatAction", req).await
     }
+
+    /// Leave a group, supergroup or channel.
+    pub async fn leave_chat(&self, req: &LeaveChatRequest) -> anyhow::Result<bool> {
+        self.client.post("leaveChat", req).await
+    }
+
+    /// Get information about a member of a chat, e.g., to check the bot's own rights. See
+    /// also [`Event::my_permissions`](crate::Event::my_permissions).
+    pub async fn get_chat_member(&self, req: &GetChatMemberRequest) -> anyhow::Result<ChatMember> {
+        self.client.post("getChatMember", req).await
+    }
+
+    /// Get the number of members in a chat.
+    pub async fn get_chat_member_count(
+        &self,
+        req: &GetChatMemberCountRequest,
+    ) -> anyhow::Result<i64> {
+        self.client.post("getChatMemberCount", req).await
+    }
+
+    /// Pin a message in a chat. The bot must be an administrator in groups and channels.
+    pub async fn pin_chat_message(&self, req: &PinChatMessageRequest) -> anyhow::Result<bool> {
+        self.client.post("pinChatMessage", req).await
+    }
+
+    /// Approve a chat join request. The bot must be an administrator in the chat, with the
+    /// `can_invite_users` right.
+    pub async fn approve_chat_join_request(
+        &self,
+        req: &ApproveChatJoinRequestRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("approveChatJoinRequest", req).await
+    }
+
+    /// Decline a chat join request. The bot must be an administrator in the chat, with the
+    /// `can_invite_users` right.
+    pub async fn decline_chat_join_request(
+        &self,
+        req: &DeclineChatJoinRequestRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("declineChatJoinRequest", req).await
+    }
 }
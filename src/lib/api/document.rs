@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use super::PhotoSize;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Document {
     /// Identifier for this file, which can be used to download or reuse the file
     pub file_id: String,
@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use bytes;
 use mobot_derive::BotRequest;
 use serde::{Deserialize, Serialize};
@@ -12,10 +14,70 @@ pub struct File {
     /// File size
     pub file_size: Option<i64>,
 
-    /// File path. You can use it with api.download_file to download file
+    /// File path. You can use it with api.download_file to download file. The
+    /// path is only guaranteed to be valid for an hour, so don't store it: store
+    /// the `file_id` instead, and call `get_file` again when needed.
     pub file_path: Option<String>,
 }
 
+/// How long [`API::download_by_file_id`] caches `file_path`s for. Telegram guarantees
+/// links are valid for at least an hour.
+pub const FILE_PATH_TTL: Duration = Duration::from_secs(50 * 60);
+
+/// `InputFile` is a file to send with a request: either a file that Telegram can already
+/// get to (by `file_id` or HTTP URL), or a new file to upload.
+#[derive(Debug, Clone)]
+pub enum InputFile {
+    /// The `file_id` of a file on the Telegram servers, or an HTTP URL for Telegram to
+    /// fetch the file from.
+    Remote(String),
+
+    /// Upload a new file with the given name and contents.
+    Upload {
+        file_name: String,
+        data: bytes::Bytes,
+    },
+}
+
+impl InputFile {
+    /// Upload a new file with the given name and contents.
+    pub fn upload(file_name: impl Into<String>, data: impl Into<bytes::Bytes>) -> Self {
+        InputFile::Upload {
+            file_name: file_name.into(),
+            data: data.into(),
+        }
+    }
+}
+
+impl From<&str> for InputFile {
+    fn from(file: &str) -> Self {
+        InputFile::Remote(file.into())
+    }
+}
+
+impl From<String> for InputFile {
+    fn from(file: String) -> Self {
+        InputFile::Remote(file)
+    }
+}
+
+/// Remote files serialize as their `file_id` or URL. Uploads are sent as multipart
+/// form data, so they serialize as just their file name.
+impl Serialize for InputFile {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            InputFile::Remote(file) => serializer.serialize_str(file),
+            InputFile::Upload { file_name, .. } => serializer.serialize_str(file_name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InputFile {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(InputFile::Remote)
+    }
+}
+
 #[derive(Debug, Serialize, Clone, BotRequest)]
 pub struct GetFileRequest {
     /// Unique identifier for target file
@@ -48,4 +110,39 @@ impl API {
     pub async fn download_file(&self, req: &DownloadRequest) -> anyhow::Result<bytes::Bytes> {
         self.client.download_file(&req.file_path).await
     }
+
+    /// Download a file by its `file_id`. This calls `get_file` to look up the file's
+    /// `file_path`, then downloads it. The `file_path` is cached for [`FILE_PATH_TTL`], so
+    /// downloading the same file again skips the `get_file` call.
+    pub async fn download_by_file_id(
+        &self,
+        file_id: impl Into<String>,
+    ) -> anyhow::Result<bytes::Bytes> {
+        let file_id = file_id.into();
+        let file_path = match self.cached_file_path(&file_id) {
+            Some(file_path) => file_path,
+            None => {
+                let file = self.get_file(&GetFileRequest::new(file_id.clone())).await?;
+                let file_path = file
+                    .file_path
+                    .ok_or_else(|| anyhow::anyhow!("No file_path for file {}", file_id))?;
+
+                let mut file_paths = self.file_paths.lock().unwrap();
+                file_paths.retain(|_, (_, fetched)| fetched.elapsed() < FILE_PATH_TTL);
+                file_paths.insert(file_id, (file_path.clone(), Instant::now()));
+                file_path
+            }
+        };
+
+        self.download_file(&DownloadRequest::new(file_path)).await
+    }
+
+    fn cached_file_path(&self, file_id: &str) -> Option<String> {
+        self.file_paths
+            .lock()
+            .unwrap()
+            .get(file_id)
+            .filter(|(_, fetched)| fetched.elapsed() < FILE_PATH_TTL)
+            .map(|(file_path, _)| file_path.clone())
+    }
 }
@@ -1,6 +1,7 @@
 pub fn escape_md(text: &str) -> String {
     let escapes = [
         '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+        '\\',
     ];
 
     let mut result = String::new();
@@ -29,3 +30,19 @@ pub fn escape_code(text: &str) -> String {
 
     result
 }
+
+pub fn escape_html(text: &str) -> String {
+    let mut result = String::new();
+
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
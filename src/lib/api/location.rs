@@ -0,0 +1,280 @@
+use mobot_derive::{BotRequest, BotRequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{chat::ChatId, message::Message, EditMessageBase, ReplyParameters, API};
+
+/// A point on the map.
+/// <https://core.telegram.org/bots/api#location>
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Location {
+    /// Latitude as defined by sender
+    pub latitude: f64,
+
+    /// Longitude as defined by sender
+    pub longitude: f64,
+
+    /// The radius of uncertainty for the location, measured in meters; 0-1500
+    pub horizontal_accuracy: Option<f64>,
+
+    /// Time relative to the message sending date, during which the location can be
+    /// updated; in seconds. For active live locations only.
+    pub live_period: Option<i64>,
+
+    /// The direction in which user is moving, in degrees; 1-360. For active live
+    /// locations only.
+    pub heading: Option<i64>,
+
+    /// The maximum distance for proximity alerts about approaching another chat member,
+    /// in meters. For sent live locations only.
+    pub proximity_alert_radius: Option<i64>,
+}
+
+/// A venue: a location with a title and address.
+/// <https://core.telegram.org/bots/api#venue>
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Venue {
+    /// Venue location. Can't be a live location
+    pub location: Location,
+
+    /// Name of the venue
+    pub title: String,
+
+    /// Address of the venue
+    pub address: String,
+
+    /// Foursquare identifier of the venue
+    pub foursquare_id: Option<String>,
+
+    /// Google Places identifier of the venue
+    pub google_place_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest, BotRequestBuilder)]
+pub struct SendLocationRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Latitude of the location
+    pub latitude: f64,
+
+    /// Longitude of the location
+    pub longitude: f64,
+
+    /// The radius of uncertainty for the location, measured in meters; 0-1500
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal_accuracy: Option<f64>,
+
+    /// Period in seconds during which the location will be updated (see
+    /// [`API::edit_message_live_location`]), should be between 60 and 86400, or 0x7FFFFFFF
+    /// for live locations that can be edited indefinitely.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_period: Option<i64>,
+
+    /// For live locations, a direction in which the user is moving, in degrees; 1-360
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<i64>,
+
+    /// For live locations, a maximum distance for proximity alerts about approaching
+    /// another chat member, in meters; 1-100000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proximity_alert_radius: Option<i64>,
+
+    /// Sends the message silently. Users will receive a notification with no sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+
+    /// If the message is a reply, ID of the original message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+impl SendLocationRequest {
+    pub fn new(chat_id: impl Into<ChatId>, latitude: f64, longitude: f64) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            latitude,
+            longitude,
+            horizontal_accuracy: None,
+            live_period: None,
+            heading: None,
+            proximity_alert_radius: None,
+            disable_notification: None,
+            reply_parameters: None,
+        }
+    }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+}
+
+/// Edit a live location message, until its `live_period` expires or it's stopped with
+/// [`API::stop_message_live_location`].
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest, BotRequestBuilder)]
+pub struct EditMessageLiveLocationRequest {
+    /// Base fields for edit requests
+    #[serde(flatten)]
+    pub base: EditMessageBase,
+
+    /// Latitude of new location
+    pub latitude: f64,
+
+    /// Longitude of new location
+    pub longitude: f64,
+
+    /// The radius of uncertainty for the location, measured in meters; 0-1500
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal_accuracy: Option<f64>,
+
+    /// Direction in which the user is moving, in degrees; 1-360
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub heading: Option<i64>,
+
+    /// The maximum distance for proximity alerts about approaching another chat member,
+    /// in meters; 1-100000
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proximity_alert_radius: Option<i64>,
+}
+
+impl EditMessageLiveLocationRequest {
+    /// Move the live location message identified by `base` (e.g.,
+    /// `EditMessageBase::new().with_chat_id(chat_id).with_message_id(message_id)`) to the
+    /// given coordinates.
+    pub fn new(base: EditMessageBase, latitude: f64, longitude: f64) -> Self {
+        Self {
+            base,
+            latitude,
+            longitude,
+            ..Default::default()
+        }
+    }
+}
+
+/// Stop updating a live location message before its `live_period` expires.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
+pub struct StopMessageLiveLocationRequest {
+    /// Base fields for edit requests
+    #[serde(flatten)]
+    pub base: EditMessageBase,
+}
+
+impl StopMessageLiveLocationRequest {
+    /// Stop the live location message identified by `base`.
+    pub fn new(base: EditMessageBase) -> Self {
+        Self { base }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, BotRequest, BotRequestBuilder)]
+pub struct SendVenueRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Latitude of the venue
+    pub latitude: f64,
+
+    /// Longitude of the venue
+    pub longitude: f64,
+
+    /// Name of the venue
+    pub title: String,
+
+    /// Address of the venue
+    pub address: String,
+
+    /// Foursquare identifier of the venue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foursquare_id: Option<String>,
+
+    /// Google Places identifier of the venue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub google_place_id: Option<String>,
+
+    /// Sends the message silently. Users will receive a notification with no sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+
+    /// If the message is a reply, ID of the original message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+impl SendVenueRequest {
+    pub fn new(
+        chat_id: impl Into<ChatId>,
+        latitude: f64,
+        longitude: f64,
+        title: impl Into<String>,
+        address: impl Into<String>,
+    ) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            latitude,
+            longitude,
+            title: title.into(),
+            address: address.into(),
+            foursquare_id: None,
+            google_place_id: None,
+            disable_notification: None,
+            reply_parameters: None,
+        }
+    }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+}
+
+impl API {
+    /// Send a point on the map. Set `live_period` to send a live location, which can then
+    /// be updated with [`API::edit_message_live_location`]. Returns the sent message.
+    pub async fn send_location(&self, req: &SendLocationRequest) -> anyhow::Result<Message> {
+        self.client.post("sendLocation", req).await
+    }
+
+    /// Update a live location message. Returns the edited message. Updates from users
+    /// sharing their live location arrive as edited messages (see
+    /// [`Matcher::Location`](crate::Matcher::Location)).
+    pub async fn edit_message_live_location(
+        &self,
+        req: &EditMessageLiveLocationRequest,
+    ) -> anyhow::Result<Message> {
+        self.client.post("editMessageLiveLocation", req).await
+    }
+
+    /// Update a live location message sent via the bot in inline mode. Telegram returns
+    /// `true` instead of the edited message for inline messages.
+    pub async fn edit_inline_message_live_location(
+        &self,
+        req: &EditMessageLiveLocationRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("editMessageLiveLocation", req).await
+    }
+
+    /// Stop updating a live location message. Returns the edited message.
+    pub async fn stop_message_live_location(
+        &self,
+        req: &StopMessageLiveLocationRequest,
+    ) -> anyhow::Result<Message> {
+        self.client.post("stopMessageLiveLocation", req).await
+    }
+
+    /// Stop updating a live location message sent via the bot in inline mode.
+    pub async fn stop_inline_message_live_location(
+        &self,
+        req: &StopMessageLiveLocationRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("stopMessageLiveLocation", req).await
+    }
+
+    /// Send information about a venue. Returns the sent message.
+    pub async fn send_venue(&self, req: &SendVenueRequest) -> anyhow::Result<Message> {
+        self.client.post("sendVenue", req).await
+    }
+}
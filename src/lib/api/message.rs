@@ -1,27 +1,69 @@
 use chrono::Utc;
-use mobot_derive::BotRequest;
+use mobot_derive::{BotRequest, BotRequestBuilder};
 use serde::{Deserialize, Serialize};
 
-use super::{chat::Chat, sticker::Sticker, user::User, Document, PhotoSize, ReplyMarkup, API};
+use super::{
+    animation::Animation, chat::Chat, chat::ChatId, sticker::Sticker, user::User, ChatShared,
+    Document, Invoice, Location, MessageEntity, MessageEntityType, PhotoSize, RefundedPayment,
+    ReplyMarkup, SuccessfulPayment, UsersShared, Venue, API,
+};
 
 /// `Message` represents a message sent in a chat. It can be a text message, a sticker, a photo, etc.
 /// <https://core.telegram.org/bots/api#message>
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Message {
     /// Unique message identifier inside this chat
     pub message_id: i64,
 
+    /// Unique identifier of a message thread to which the message belongs; for
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+
+    /// True, if the message is sent to a forum topic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_topic_message: Option<bool>,
+
     /// Sender, empty for messages sent to channels
     #[serde(skip_serializing_if = "Option::is_none")]
     pub from: Option<User>,
 
+    /// Sender of the message when sent on behalf of a chat. For example, the channel
+    /// itself for channel posts, the supergroup itself for messages from anonymous group
+    /// administrators, or the linked channel for messages automatically forwarded to
+    /// the discussion group
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sender_chat: Option<Chat>,
+
+    /// Unique identifier of the business connection from which the message was received.
+    /// If non-empty, the message belongs to a chat of the corresponding business account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
+
     /// Date the message was sent in Unix time
     pub date: i64,
 
-    /// Message text
+    /// Message text. Custom emoji appear in the text as their placeholder (a regular
+    /// emoji), with a `custom_emoji` entity in `entities`; see
+    /// [`Message::has_only_custom_emoji`].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
 
+    /// For text messages, special entities like usernames, URLs, bot commands, custom
+    /// emoji, etc. that appear in the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<MessageEntity>>,
+
+    /// Caption for the animation, audio, document, photo, video or voice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+
+    /// For messages with a caption, special entities like usernames, URLs, bot commands,
+    /// etc. that appear in the caption
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+
     /// Message is a photo, available sizes of the photo
     #[serde(skip_serializing_if = "Option::is_none")]
     pub photo: Option<Vec<PhotoSize>>,
@@ -30,12 +72,23 @@ pub struct Message {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub document: Option<Document>,
 
+    /// Message is an animation, information about the animation. For backward
+    /// compatibility, when this field is set, the `document` field will also be set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub animation: Option<Animation>,
+
     /// Conversation the message belongs to
     /// - For sent messages, the first available identifier of the chat
     /// - For messages forwarded to the chat, the identifier of the original chat
     /// - For messages in channels, the identifier of the channel is contained in the `chat_id` field
     pub chat: Chat,
 
+    /// For forwarded messages, information about the original message. Older Bot API
+    /// versions set the `forward_*` fields below instead; use [`Message::origin`] to handle
+    /// both.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_origin: Option<MessageOrigin>,
+
     /// For forwarded messages, sender of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub forward_from: Option<User>,
@@ -60,19 +113,127 @@ pub struct Message {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub forward_date: Option<i64>,
 
+    /// True, if the message is a channel post that was automatically forwarded to the
+    /// connected discussion group
+    pub is_automatic_forward: bool,
+
+    /// True, if the message can't be forwarded
+    pub has_protected_content: bool,
+
     /// For replies, the original message. Note that the Message object in this field will not contain further `reply_to_message` fields even if it itself is a reply.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub reply_to_message: Option<serde_json::value::Value>,
+    pub reply_to_message: Option<Box<Message>>,
 
     /// Sticker for messages with a sticker
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sticker: Option<Sticker>,
 
+    /// Message is a shared location, information about the location. Also set for venues.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+
+    /// Message is a venue, information about the venue
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub venue: Option<Venue>,
+
+    /// Service message: users were shared with the bot via a `request_users` keyboard button
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users_shared: Option<UsersShared>,
+
+    /// Service message: a chat was shared with the bot via a `request_chat` keyboard button
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_shared: Option<ChatShared>,
+
+    /// Message is an invoice for a payment, information about the invoice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<Invoice>,
+
+    /// Service message: a payment was successfully received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub successful_payment: Option<SuccessfulPayment>,
+
+    /// Service message: a payment was refunded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refunded_payment: Option<RefundedPayment>,
+
     /// Inline keyboard attached to the message.
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+/// `MessageOrigin` describes where a forwarded message originally came from, see
+/// [`Message::origin`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MessageOrigin {
+    /// The message was originally sent by a known user.
+    User {
+        /// Date the message was sent originally in Unix time
+        date: i64,
+
+        /// User that sent the message originally
+        sender_user: User,
+    },
+
+    /// The message was originally sent by an unknown user, who disallows linking to
+    /// their account in forwarded messages.
+    HiddenUser {
+        /// Date the message was sent originally in Unix time
+        date: i64,
+
+        /// Name of the user that sent the message originally
+        sender_user_name: String,
+    },
+
+    /// The message was originally sent on behalf of a chat to a group chat.
+    Chat {
+        /// Date the message was sent originally in Unix time
+        date: i64,
+
+        /// Chat that sent the message originally
+        sender_chat: Chat,
+
+        /// For messages originally sent by an anonymous chat administrator, original
+        /// message author signature
+        #[serde(skip_serializing_if = "Option::is_none")]
+        author_signature: Option<String>,
+    },
+
+    /// The message was originally sent to a channel chat.
+    Channel {
+        /// Date the message was sent originally in Unix time
+        date: i64,
+
+        /// Channel chat to which the message was originally sent
+        chat: Chat,
+
+        /// Unique message identifier inside the chat
+        message_id: i64,
+
+        /// Signature of the original post author
+        #[serde(skip_serializing_if = "Option::is_none")]
+        author_signature: Option<String>,
+    },
+
+    /// Origin types that mobot doesn't know about yet.
+    #[serde(other)]
+    Unknown,
+}
+
+impl MessageOrigin {
+    /// Returns the date the message was sent originally in Unix time, or `None` for
+    /// unknown origin types.
+    pub fn date(&self) -> Option<i64> {
+        match self {
+            Self::User { date, .. }
+            | Self::HiddenUser { date, .. }
+            | Self::Chat { date, .. }
+            | Self::Channel { date, .. } => Some(*date),
+            Self::Unknown => None,
+        }
+    }
+}
+
 impl Message {
     /// Creates a new `Message` with the given `text` and `from` fields.
     pub fn new(from: impl Into<String>, text: impl Into<String>) -> Self {
@@ -81,6 +242,94 @@ impl Message {
         message
     }
 
+    /// Returns the text of the message, or, for media messages, the caption.
+    pub fn text_or_caption(&self) -> Option<&str> {
+        self.text.as_deref().or(self.caption.as_deref())
+    }
+
+    /// Returns true if the message text consists only of custom emoji (and whitespace).
+    /// Telegram sends such messages with the placeholder emoji as `text`, so text
+    /// matchers see the placeholders rather than the custom emoji, e.g., a message with a
+    /// single custom emoji might have the text "👍".
+    pub fn has_only_custom_emoji(&self) -> bool {
+        let Some(ref text) = self.text else {
+            return false;
+        };
+        let emoji: Vec<_> = self
+            .entities
+            .iter()
+            .flatten()
+            .filter(|e| e.entity_type == MessageEntityType::CustomEmoji)
+            .map(|e| e.offset..e.offset + e.length)
+            .collect();
+
+        // Entity offsets are in UTF-16 code units.
+        let mut offset = 0;
+        let mut found = false;
+        for c in text.chars() {
+            if emoji.iter().any(|range| range.contains(&offset)) {
+                found = true;
+            } else if !c.is_whitespace() {
+                return false;
+            }
+            offset += c.len_utf16() as i64;
+        }
+        found
+    }
+
+    /// Returns the origin of a forwarded message, or `None` if the message wasn't forwarded.
+    /// This is `forward_origin` if set, otherwise it's derived from the legacy `forward_*`
+    /// fields.
+    pub fn origin(&self) -> Option<MessageOrigin> {
+        if let Some(ref origin) = self.forward_origin {
+            return Some(origin.clone());
+        }
+
+        let date = self.forward_date.unwrap_or_default();
+        let author_signature = self.forward_signature.clone();
+        if let Some(ref chat) = self.forward_from_chat {
+            return Some(match self.forward_from_message_id {
+                Some(message_id) if chat.chat_type == "channel" => MessageOrigin::Channel {
+                    date,
+                    chat: chat.clone(),
+                    message_id,
+                    author_signature,
+                },
+                _ => MessageOrigin::Chat {
+                    date,
+                    sender_chat: chat.clone(),
+                    author_signature,
+                },
+            });
+        }
+
+        if let Some(ref user) = self.forward_from {
+            return Some(MessageOrigin::User {
+                date,
+                sender_user: user.clone(),
+            });
+        }
+
+        self.forward_sender_name
+            .as_ref()
+            .map(|name| MessageOrigin::HiddenUser {
+                date,
+                sender_user_name: name.clone(),
+            })
+    }
+
+    /// Returns true if the message is a reply to another message.
+    pub fn is_reply(&self) -> bool {
+        self.reply_to_message.is_some()
+    }
+
+    /// Returns the text (or caption) of the message this message replies to, if any.
+    pub fn replied_text(&self) -> Option<&str> {
+        self.reply_to_message
+            .as_deref()
+            .and_then(|message| message.text_or_caption())
+    }
+
     pub fn fake(from: impl AsRef<str>) -> Self {
         Message {
             message_id: rand::random(),
@@ -104,38 +353,88 @@ pub enum ParseMode {
     Text,
 }
 
+/// Describes the message being replied to. Used by all `send*` requests.
+/// <https://core.telegram.org/bots/api#replyparameters>
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct ReplyParameters {
-    // Identifier of the original message
+    /// Identifier of the original message
     pub message_id: i64,
 
-    // Unique identifier for the target chat or username of the target channel. This
-    // field is an i64 OR a String, so we use serde_json::value::Value.
+    /// Unique identifier for the target chat or username of the target channel, if the
+    /// message to be replied to is from a different chat.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub chat_id: Option<serde_json::value::Value>,
+    pub chat_id: Option<ChatId>,
 
-    // Allow sending the message without a reply
+    /// Pass true if the message should be sent even if the message to be replied to isn't
+    /// found (e.g., because it was deleted). Otherwise, sending fails.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
 
-    // Quoted part of the message to be replied to.
+    /// Quoted part of the message to be replied to.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quote: Option<String>,
 
+    /// Mode for parsing entities in the quote
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_parse_mode: Option<String>,
 
+    /// Special entities that appear in the quote. Can be specified instead of
+    /// `quote_parse_mode`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub quote_entities: Option<serde_json::value::Value>,
+    pub quote_entities: Option<Vec<MessageEntity>>,
 
+    /// Position of the quote in the original message in UTF-16 code units
     #[serde(skip_serializing_if = "Option::is_none")]
     pub quote_position: Option<i64>,
 }
 
+impl ReplyParameters {
+    /// Reply to the message with the given ID in the current chat.
+    pub fn new(message_id: i64) -> Self {
+        Self {
+            message_id,
+            ..Default::default()
+        }
+    }
+
+    /// Reply to a message in a different chat.
+    pub fn with_chat_id(mut self, chat_id: impl Into<ChatId>) -> Self {
+        self.chat_id = Some(chat_id.into());
+        self
+    }
+
+    /// Send the message even if the message to be replied to was deleted, instead of
+    /// failing. Useful in busy chats, where messages may be deleted by admins or other
+    /// bots before the reply is sent.
+    pub fn with_allow_sending_without_reply(mut self, allow: bool) -> Self {
+        self.allow_sending_without_reply = Some(allow);
+        self
+    }
+
+    /// Quote `quote` (an exact substring of the original message) in the reply, starting
+    /// at `position` UTF-16 code units into the message.
+    pub fn with_quote(mut self, quote: impl Into<String>, position: i64) -> Self {
+        self.quote = Some(quote.into());
+        self.quote_position = Some(position);
+        self
+    }
+
+    /// Set the entities of the quote, e.g., to keep the formatting of the quoted text.
+    pub fn with_quote_entities(mut self, entities: Vec<MessageEntity>) -> Self {
+        self.quote_entities = Some(entities);
+        self
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
 pub struct SendMessageRequest {
     /// Unique identifier for the target chat or username of the target
-    pub chat_id: i64,
+    pub chat_id: ChatId,
+
+    /// Unique identifier for the target message thread (topic) of the forum; for forum
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
 
     /// Text of the message to be sent
     pub text: String,
@@ -148,20 +447,41 @@ pub struct SendMessageRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parse_mode: Option<ParseMode>,
 
+    /// Special entities that appear in the message text, which can be specified
+    /// instead of `parse_mode`. No escaping is required when using entities.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entities: Option<Vec<MessageEntity>>,
+
     /// Reply markup for the message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
+
+    /// Unique identifier of the message effect to be added to the message; for private
+    /// chats only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_effect_id: Option<String>,
+
+    /// Unique identifier of the business connection on behalf of which the message will
+    /// be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
 }
 
 impl SendMessageRequest {
-    pub fn new(chat_id: i64, text: impl Into<String>) -> Self {
+    pub fn new(chat_id: impl Into<ChatId>, text: impl Into<String>) -> Self {
         Self {
-            chat_id,
+            chat_id: chat_id.into(),
             text: text.into(),
             ..Default::default()
         }
     }
 
+    /// Send the message to the given forum topic.
+    pub fn with_message_thread_id(mut self, message_thread_id: i64) -> Self {
+        self.message_thread_id = Some(message_thread_id);
+        self
+    }
+
     pub fn with_reply_markup(mut self, reply_markup: ReplyMarkup) -> Self {
         self.reply_markup = Some(reply_markup);
         self
@@ -171,21 +491,51 @@ impl SendMessageRequest {
         self.parse_mode = Some(parse_mode);
         self
     }
+
+    pub fn with_entities(mut self, entities: Vec<MessageEntity>) -> Self {
+        self.entities = Some(entities);
+        self
+    }
+
+    pub fn with_reply_parameters(mut self, reply_parameters: ReplyParameters) -> Self {
+        self.reply_parameters = Some(reply_parameters);
+        self
+    }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+
+    pub fn with_message_effect_id(mut self, message_effect_id: impl Into<String>) -> Self {
+        self.message_effect_id = Some(message_effect_id.into());
+        self
+    }
+
+    pub fn with_business_connection_id(
+        mut self,
+        business_connection_id: impl Into<String>,
+    ) -> Self {
+        self.business_connection_id = Some(business_connection_id.into());
+        self
+    }
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequestBuilder)]
 pub struct EditMessageBase {
     /// Required if `inline_message_id` is not specified. Unique identifier for the
     /// target chat or username of the target channel (in the format @channelusername)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub chat_id: Option<i64>,
+    pub chat_id: Option<ChatId>,
 
     /// Required if `inline_message_id` is not specified. Identifier of the message
     /// to edit
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message_id: Option<i64>,
 
-    /// Inline message identifier
+    /// Inline message identifier. Use this to edit a message sent via the bot in inline
+    /// mode, instead of `chat_id` and `message_id`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inline_message_id: Option<String>,
 
@@ -196,6 +546,7 @@ pub struct EditMessageBase {
 
     /// Reply markup for the message
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
     pub reply_markup: Option<String>,
 }
 
@@ -204,21 +555,6 @@ impl EditMessageBase {
         Self::default()
     }
 
-    pub fn with_chat_id(mut self, chat_id: i64) -> Self {
-        self.chat_id = Some(chat_id);
-        self
-    }
-
-    pub fn with_message_id(mut self, message_id: i64) -> Self {
-        self.message_id = Some(message_id);
-        self
-    }
-
-    pub fn with_parse_mode(mut self, parse_mode: ParseMode) -> Self {
-        self.parse_mode = Some(parse_mode);
-        self
-    }
-
     pub fn with_reply_markup(mut self, reply_markup: ReplyMarkup) -> Self {
         self.reply_markup = Some(serde_json::to_string(&reply_markup).unwrap());
         self
@@ -244,8 +580,8 @@ impl EditMessageTextRequest {
         }
     }
 
-    pub fn with_chat_id(mut self, chat_id: i64) -> Self {
-        self.base.chat_id = Some(chat_id);
+    pub fn with_chat_id(mut self, chat_id: impl Into<ChatId>) -> Self {
+        self.base.chat_id = Some(chat_id.into());
         self
     }
 
@@ -253,6 +589,11 @@ impl EditMessageTextRequest {
         self.base.message_id = Some(message_id);
         self
     }
+
+    pub fn with_inline_message_id(mut self, inline_message_id: impl Into<String>) -> Self {
+        self.base.inline_message_id = Some(inline_message_id.into());
+        self
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
@@ -274,8 +615,13 @@ impl EditMessageCaptionRequest {
         }
     }
 
-    pub fn with_chat_id(mut self, chat_id: i64) -> Self {
-        self.base.chat_id = Some(chat_id);
+    pub fn with_chat_id(mut self, chat_id: impl Into<ChatId>) -> Self {
+        self.base.chat_id = Some(chat_id.into());
+        self
+    }
+
+    pub fn with_inline_message_id(mut self, inline_message_id: impl Into<String>) -> Self {
+        self.base.inline_message_id = Some(inline_message_id.into());
         self
     }
 }
@@ -294,8 +640,8 @@ impl EditMessageReplyMarkupRequest {
         }
     }
 
-    pub fn with_chat_id(mut self, chat_id: i64) -> Self {
-        self.base.chat_id = Some(chat_id);
+    pub fn with_chat_id(mut self, chat_id: impl Into<ChatId>) -> Self {
+        self.base.chat_id = Some(chat_id.into());
         self
     }
 
@@ -303,25 +649,215 @@ impl EditMessageReplyMarkupRequest {
         self.base.message_id = Some(message_id);
         self
     }
+
+    pub fn with_inline_message_id(mut self, inline_message_id: impl Into<String>) -> Self {
+        self.base.inline_message_id = Some(inline_message_id.into());
+        self
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
 pub struct DeleteMessageRequest {
     /// Unique identifier for the target chat or username of the target channel
     /// (in the format @channelusername)
-    pub chat_id: i64,
+    pub chat_id: ChatId,
 
     /// Identifier of the message to delete
     pub message_id: i64,
 }
 
 impl DeleteMessageRequest {
-    pub fn new(chat_id: i64, message_id: i64) -> Self {
+    pub fn new(chat_id: impl Into<ChatId>, message_id: i64) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            message_id,
+        }
+    }
+}
+
+/// `MessageId` is a lightweight response type containing just a message identifier. It's
+/// returned by methods that copy or forward messages.
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MessageId {
+    /// Unique message identifier
+    pub message_id: i64,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
+pub struct ForwardMessageRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier for the target message thread (topic) of the forum; for forum
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+
+    /// Unique identifier for the chat where the original message was sent
+    pub from_chat_id: ChatId,
+
+    /// Message identifier in the chat specified in `from_chat_id`
+    pub message_id: i64,
+
+    /// Sends the message silently. Users will receive a notification with no sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+
+    /// Protects the contents of the forwarded message from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+}
+
+impl ForwardMessageRequest {
+    pub fn new(
+        chat_id: impl Into<ChatId>,
+        from_chat_id: impl Into<ChatId>,
+        message_id: i64,
+    ) -> Self {
         Self {
-            chat_id,
+            chat_id: chat_id.into(),
+            from_chat_id: from_chat_id.into(),
             message_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_message_thread_id(mut self, message_thread_id: i64) -> Self {
+        self.message_thread_id = Some(message_thread_id);
+        self
+    }
+
+    pub fn with_disable_notification(mut self, disable_notification: bool) -> Self {
+        self.disable_notification = Some(disable_notification);
+        self
+    }
+
+    pub fn with_protect_content(mut self, protect_content: bool) -> Self {
+        self.protect_content = Some(protect_content);
+        self
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
+pub struct ForwardMessagesRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier for the target message thread (topic) of the forum; for forum
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+
+    /// Unique identifier for the chat where the original messages were sent
+    pub from_chat_id: ChatId,
+
+    /// Identifiers of 1-100 messages in the chat `from_chat_id` to forward. The
+    /// identifiers must be specified in a strictly increasing order.
+    pub message_ids: Vec<i64>,
+
+    /// Sends the messages silently. Users will receive a notification with no sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+
+    /// Protects the contents of the forwarded messages from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+}
+
+impl ForwardMessagesRequest {
+    pub fn new(
+        chat_id: impl Into<ChatId>,
+        from_chat_id: impl Into<ChatId>,
+        message_ids: Vec<i64>,
+    ) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            from_chat_id: from_chat_id.into(),
+            message_ids,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_message_thread_id(mut self, message_thread_id: i64) -> Self {
+        self.message_thread_id = Some(message_thread_id);
+        self
+    }
+
+    pub fn with_disable_notification(mut self, disable_notification: bool) -> Self {
+        self.disable_notification = Some(disable_notification);
+        self
+    }
+
+    pub fn with_protect_content(mut self, protect_content: bool) -> Self {
+        self.protect_content = Some(protect_content);
+        self
+    }
+}
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequest)]
+pub struct CopyMessagesRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier for the target message thread (topic) of the forum; for forum
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+
+    /// Unique identifier for the chat where the original messages were sent
+    pub from_chat_id: ChatId,
+
+    /// Identifiers of 1-100 messages in the chat `from_chat_id` to copy. The
+    /// identifiers must be specified in a strictly increasing order.
+    pub message_ids: Vec<i64>,
+
+    /// Sends the messages silently. Users will receive a notification with no sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+
+    /// Protects the contents of the sent messages from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+
+    /// Pass true to copy the messages without their captions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remove_caption: Option<bool>,
+}
+
+impl CopyMessagesRequest {
+    pub fn new(
+        chat_id: impl Into<ChatId>,
+        from_chat_id: impl Into<ChatId>,
+        message_ids: Vec<i64>,
+    ) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            from_chat_id: from_chat_id.into(),
+            message_ids,
+            ..Default::default()
         }
     }
+
+    pub fn with_message_thread_id(mut self, message_thread_id: i64) -> Self {
+        self.message_thread_id = Some(message_thread_id);
+        self
+    }
+
+    pub fn with_disable_notification(mut self, disable_notification: bool) -> Self {
+        self.disable_notification = Some(disable_notification);
+        self
+    }
+
+    pub fn with_protect_content(mut self, protect_content: bool) -> Self {
+        self.protect_content = Some(protect_content);
+        self
+    }
+
+    pub fn with_remove_caption(mut self, remove_caption: bool) -> Self {
+        self.remove_caption = Some(remove_caption);
+        self
+    }
 }
 
 /// API methods for sending, editing, and deleting messages.
@@ -381,14 +917,60 @@ impl API {
         self.client.post("editMessageReplyMarkup", req).await
     }
 
+    /// Edit the text of a message sent via the bot in inline mode (the request must have
+    /// `inline_message_id` set). Telegram returns `true` instead of the edited message for
+    /// inline messages.
+    pub async fn edit_inline_message_text(
+        &self,
+        req: &EditMessageTextRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("editMessageText", req).await
+    }
+
+    /// Edit the caption of a message sent via the bot in inline mode.
+    pub async fn edit_inline_message_caption(
+        &self,
+        req: &EditMessageCaptionRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("editMessageCaption", req).await
+    }
+
+    /// Edit the reply markup of a message sent via the bot in inline mode.
+    pub async fn edit_inline_message_reply_markup(
+        &self,
+        req: &EditMessageReplyMarkupRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("editMessageReplyMarkup", req).await
+    }
+
     /// Delete a message.
     pub async fn delete_message(&self, req: &DeleteMessageRequest) -> anyhow::Result<bool> {
         self.client.post("deleteMessage", req).await
     }
 
+    /// Forward a message of any kind. Returns the sent message.
+    pub async fn forward_message(&self, req: &ForwardMessageRequest) -> anyhow::Result<Message> {
+        self.client.post("forwardMessage", req).await
+    }
+
+    /// Forward multiple messages (up to 100) of any kind. Returns the IDs of the sent
+    /// messages. Messages that can't be found or forwarded are skipped.
+    pub async fn forward_messages(
+        &self,
+        req: &ForwardMessagesRequest,
+    ) -> anyhow::Result<Vec<MessageId>> {
+        self.client.post("forwardMessages", req).await
+    }
+
+    /// Copy multiple messages (up to 100) of any kind. Unlike forwarding, the copied messages
+    /// don't have a link to the original messages. Returns the IDs of the sent messages.
+    pub async fn copy_messages(&self, req: &CopyMessagesRequest) -> anyhow::Result<Vec<MessageId>> {
+        self.client.post("copyMessages", req).await
+    }
+
     pub async fn remove_reply_keyboard(
         &self,
-        chat_id: i64,
+        chat_id: impl Into<ChatId>,
         text: String,
     ) -> anyhow::Result<Message> {
         self.send_message(
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use super::user::User;
+
+/// The type of a [`MessageEntity`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageEntityType {
+    Mention,
+    Hashtag,
+    Cashtag,
+    BotCommand,
+    Url,
+    Email,
+    PhoneNumber,
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Blockquote,
+    Code,
+    Pre,
+    TextLink,
+    TextMention,
+    CustomEmoji,
+
+    /// Entity types that mobot doesn't know about yet.
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+/// `MessageEntity` represents one special entity in a text message. For example,
+/// hashtags, usernames, URLs, etc. Entities can be sent along with a message
+/// instead of a `parse_mode`, in which case no escaping is necessary.
+/// <https://core.telegram.org/bots/api#messageentity>
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MessageEntity {
+    /// Type of the entity
+    #[serde(rename = "type")]
+    pub entity_type: MessageEntityType,
+
+    /// Offset in UTF-16 code units to the start of the entity
+    pub offset: i64,
+
+    /// Length of the entity in UTF-16 code units
+    pub length: i64,
+
+    /// For “text_link” only, URL that will be opened after user taps on the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// For “text_mention” only, the mentioned user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+
+    /// For “pre” only, the programming language of the entity text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+
+    /// For “custom_emoji” only, unique identifier of the custom emoji
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_emoji_id: Option<String>,
+}
+
+impl MessageEntity {
+    /// Create a new entity of type `entity_type`, spanning `length` UTF-16 code units
+    /// starting at `offset`.
+    pub fn new(entity_type: MessageEntityType, offset: i64, length: i64) -> Self {
+        Self {
+            entity_type,
+            offset,
+            length,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    pub fn with_user(mut self, user: User) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn with_custom_emoji_id(mut self, custom_emoji_id: impl Into<String>) -> Self {
+        self.custom_emoji_id = Some(custom_emoji_id.into());
+        self
+    }
+}
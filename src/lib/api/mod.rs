@@ -1,26 +1,38 @@
+pub mod animation;
 #[allow(clippy::module_inception)]
 pub mod api;
 pub mod botcommand;
+pub mod business;
 pub mod chat;
 pub mod document;
 pub mod file;
 pub mod format;
+pub mod location;
 pub mod message;
+pub mod message_entity;
+pub mod payments;
 pub mod photo_size;
+pub mod profile;
 pub mod query;
 pub mod reply_markup;
 pub mod sticker;
 pub mod update;
 pub mod user;
 
+pub use animation::*;
 pub use api::*;
 pub use botcommand::*;
+pub use business::*;
 pub use chat::*;
 pub use document::*;
 pub use file::*;
 pub use format::*;
+pub use location::*;
 pub use message::*;
+pub use message_entity::*;
+pub use payments::*;
 pub use photo_size::*;
+pub use profile::*;
 pub use query::*;
 pub use reply_markup::*;
 pub use sticker::*;
@@ -0,0 +1,321 @@
+use mobot_derive::{BotRequest, BotRequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{ChatId, Message, ReplyMarkup, ReplyParameters, User, API};
+
+/// `LabeledPrice` is a portion of the price for goods or services, e.g., "Subtotal" or
+/// "Delivery".
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct LabeledPrice {
+    /// Portion label
+    pub label: String,
+
+    /// Price of the product in the smallest units of the currency (integer, not
+    /// float/double). For example, for a price of US$ 1.45 pass `amount = 145`.
+    pub amount: i64,
+}
+
+impl LabeledPrice {
+    pub fn new(label: impl Into<String>, amount: i64) -> Self {
+        Self {
+            label: label.into(),
+            amount,
+        }
+    }
+}
+
+/// `Invoice` contains basic information about an invoice, see [`Message::invoice`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Invoice {
+    /// Product name
+    pub title: String,
+
+    /// Product description
+    pub description: String,
+
+    /// Unique bot deep-linking parameter that can be used to generate this invoice
+    pub start_parameter: String,
+
+    /// Three-letter ISO 4217 currency code
+    pub currency: String,
+
+    /// Total price in the smallest units of the currency
+    pub total_amount: i64,
+}
+
+/// `OrderInfo` represents information about an order, as requested with the `need_*`
+/// fields of [`SendInvoiceRequest`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OrderInfo {
+    /// User name
+    pub name: Option<String>,
+
+    /// User's phone number
+    pub phone_number: Option<String>,
+
+    /// User email
+    pub email: Option<String>,
+}
+
+/// `SuccessfulPayment` contains basic information about a successful payment, see
+/// [`Message::successful_payment`] and [`Matcher::SuccessfulPayment`](crate::Matcher::SuccessfulPayment).
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SuccessfulPayment {
+    /// Three-letter ISO 4217 currency code
+    pub currency: String,
+
+    /// Total price in the smallest units of the currency
+    pub total_amount: i64,
+
+    /// Bot specified invoice payload
+    pub invoice_payload: String,
+
+    /// Identifier of the shipping option chosen by the user
+    pub shipping_option_id: Option<String>,
+
+    /// Order information provided by the user
+    pub order_info: Option<OrderInfo>,
+
+    /// Telegram payment identifier
+    pub telegram_payment_charge_id: String,
+
+    /// Provider payment identifier
+    pub provider_payment_charge_id: String,
+}
+
+/// `RefundedPayment` contains basic information about a refunded payment, see
+/// [`Message::refunded_payment`] and [`Matcher::RefundedPayment`](crate::Matcher::RefundedPayment).
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RefundedPayment {
+    /// Three-letter ISO 4217 currency code, or "XTR" for payments in Telegram Stars
+    pub currency: String,
+
+    /// Total refunded price in the smallest units of the currency
+    pub total_amount: i64,
+
+    /// Bot specified invoice payload
+    pub invoice_payload: String,
+
+    /// Telegram payment identifier
+    pub telegram_payment_charge_id: String,
+
+    /// Provider payment identifier
+    pub provider_payment_charge_id: Option<String>,
+}
+
+/// `PreCheckoutQuery` is sent when the user confirms a payment. The bot must answer it
+/// with [`API::answer_pre_checkout_query`] within 10 seconds, otherwise the payment is
+/// cancelled. See [`Route::PreCheckoutQuery`](crate::Route::PreCheckoutQuery).
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PreCheckoutQuery {
+    /// Unique query identifier
+    pub id: String,
+
+    /// User who sent the query
+    pub from: User,
+
+    /// Three-letter ISO 4217 currency code
+    pub currency: String,
+
+    /// Total price in the smallest units of the currency
+    pub total_amount: i64,
+
+    /// Bot specified invoice payload
+    pub invoice_payload: String,
+
+    /// Identifier of the shipping option chosen by the user
+    pub shipping_option_id: Option<String>,
+
+    /// Order information provided by the user
+    pub order_info: Option<OrderInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct SendInvoiceRequest {
+    /// Unique identifier for the target chat or username of the target channel
+    pub chat_id: ChatId,
+
+    /// Unique identifier for the target message thread (topic) of the forum; for forum
+    /// supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+
+    /// Product name, 1-32 characters
+    pub title: String,
+
+    /// Product description, 1-255 characters
+    pub description: String,
+
+    /// Bot-defined invoice payload, 1-128 bytes. This will not be displayed to the user,
+    /// use it for your internal processes.
+    pub payload: String,
+
+    /// Payment provider token, obtained via @BotFather. Pass an empty string for payments
+    /// in Telegram Stars.
+    pub provider_token: String,
+
+    /// Three-letter ISO 4217 currency code, or "XTR" for payments in Telegram Stars
+    pub currency: String,
+
+    /// Price breakdown (e.g., product price, tax, discount, delivery cost). Must contain
+    /// exactly one item for payments in Telegram Stars.
+    pub prices: Vec<LabeledPrice>,
+
+    /// The maximum accepted amount for tips in the smallest units of the currency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tip_amount: Option<i64>,
+
+    /// Suggested amounts of tips in the smallest units of the currency, at most 4
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_tip_amounts: Option<Vec<i64>>,
+
+    /// Unique deep-linking parameter. If left empty, forwarded copies of the sent message
+    /// will have a Pay button; otherwise, they will have a URL button with a deep link to
+    /// the bot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_parameter: Option<String>,
+
+    /// JSON-serialized data about the invoice, which will be shared with the payment
+    /// provider
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_data: Option<String>,
+
+    /// URL of the product photo for the invoice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo_url: Option<String>,
+
+    /// Pass true if you require the user's full name to complete the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub need_name: Option<bool>,
+
+    /// Pass true if you require the user's phone number to complete the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub need_phone_number: Option<bool>,
+
+    /// Pass true if you require the user's email address to complete the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub need_email: Option<bool>,
+
+    /// Pass true if you require the user's shipping address to complete the order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub need_shipping_address: Option<bool>,
+
+    /// Pass true if the final price depends on the shipping method
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_flexible: Option<bool>,
+
+    /// Protects the contents of the sent message from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+
+    /// If the message is a reply, ID of the original message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+
+    /// Inline keyboard. If empty, one 'Pay total price' button will be shown. If not
+    /// empty, the first button must be a Pay button.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<ReplyMarkup>,
+}
+
+impl SendInvoiceRequest {
+    pub fn new(
+        chat_id: impl Into<ChatId>,
+        title: impl Into<String>,
+        description: impl Into<String>,
+        payload: impl Into<String>,
+        provider_token: impl Into<String>,
+        currency: impl Into<String>,
+        prices: Vec<LabeledPrice>,
+    ) -> Self {
+        Self {
+            chat_id: chat_id.into(),
+            title: title.into(),
+            description: description.into(),
+            payload: payload.into(),
+            provider_token: provider_token.into(),
+            currency: currency.into(),
+            prices,
+            message_thread_id: None,
+            max_tip_amount: None,
+            suggested_tip_amounts: None,
+            start_parameter: None,
+            provider_data: None,
+            photo_url: None,
+            need_name: None,
+            need_phone_number: None,
+            need_email: None,
+            need_shipping_address: None,
+            is_flexible: None,
+            protect_content: None,
+            reply_parameters: None,
+            reply_markup: None,
+        }
+    }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest)]
+pub struct AnswerPreCheckoutQueryRequest {
+    /// Unique identifier for the query to be answered
+    pub pre_checkout_query_id: String,
+
+    /// True if everything is alright (goods are available, etc.) and the bot is ready to
+    /// proceed with the order
+    pub ok: bool,
+
+    /// Required if `ok` is false. Error message in human readable form that explains the
+    /// reason for failure to proceed with the checkout, e.g., "Sorry, we're out of stock".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+impl AnswerPreCheckoutQueryRequest {
+    /// Confirm the order, so Telegram can proceed with the payment.
+    pub fn ok(pre_checkout_query_id: impl Into<String>) -> Self {
+        Self {
+            pre_checkout_query_id: pre_checkout_query_id.into(),
+            ok: true,
+            error_message: None,
+        }
+    }
+
+    /// Reject the order, showing `error_message` to the user.
+    pub fn error(
+        pre_checkout_query_id: impl Into<String>,
+        error_message: impl Into<String>,
+    ) -> Self {
+        Self {
+            pre_checkout_query_id: pre_checkout_query_id.into(),
+            ok: false,
+            error_message: Some(error_message.into()),
+        }
+    }
+}
+
+/// API methods for payments.
+impl API {
+    /// Send an invoice. Once the user pays, the bot receives a `PreCheckoutQuery`, and
+    /// after it's answered, a message with a `successful_payment`.
+    pub async fn send_invoice(&self, req: &SendInvoiceRequest) -> anyhow::Result<Message> {
+        self.client.post("sendInvoice", req).await
+    }
+
+    /// Answer a pre-checkout query. This must be done within 10 seconds of receiving it.
+    pub async fn answer_pre_checkout_query(
+        &self,
+        req: &AnswerPreCheckoutQueryRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("answerPreCheckoutQuery", req).await
+    }
+}
@@ -0,0 +1,224 @@
+use mobot_derive::{BotRequest, BotRequestBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{query::WebAppInfo, API};
+
+/// The bot's name, see [`API::get_my_name`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct BotName {
+    /// The bot's name
+    pub name: String,
+}
+
+/// The bot's description, see [`API::get_my_description`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct BotDescription {
+    /// The bot's description
+    pub description: String,
+}
+
+/// The bot's short description, see [`API::get_my_short_description`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct BotShortDescription {
+    /// The bot's short description
+    pub short_description: String,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct SetMyNameRequest {
+    /// New bot name; 0-64 characters. Pass an empty string to remove the dedicated name
+    /// for the given language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// A two-letter ISO 639-1 language code. If empty, the name will be shown to all users
+    /// for whose language there is no dedicated name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+impl SetMyNameRequest {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct SetMyDescriptionRequest {
+    /// New bot description; 0-512 characters. Shown in the chat with the bot if the chat
+    /// is empty. Pass an empty string to remove the dedicated description for the given
+    /// language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// A two-letter ISO 639-1 language code. If empty, the description will be applied to
+    /// all users for whose language there is no dedicated description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+impl SetMyDescriptionRequest {
+    pub fn new(description: impl Into<String>) -> Self {
+        Self {
+            description: Some(description.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct SetMyShortDescriptionRequest {
+    /// New short description for the bot; 0-120 characters. Shown on the bot's profile
+    /// page, and sent together with the link when users share the bot. Pass an empty
+    /// string to remove the dedicated short description for the given language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<String>,
+
+    /// A two-letter ISO 639-1 language code. If empty, the short description will be
+    /// applied to all users for whose language there is no dedicated short description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+impl SetMyShortDescriptionRequest {
+    pub fn new(short_description: impl Into<String>) -> Self {
+        Self {
+            short_description: Some(short_description.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct GetMyNameRequest {
+    /// A two-letter ISO 639-1 language code or an empty string
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_code: Option<String>,
+}
+
+impl GetMyNameRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub type GetMyDescriptionRequest = GetMyNameRequest;
+pub type GetMyShortDescriptionRequest = GetMyNameRequest;
+
+/// `MenuButton` describes the bot's menu button in a private chat.
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MenuButton {
+    /// Opens the bot's list of commands
+    Commands,
+
+    /// Launches a Web App
+    WebApp {
+        /// Text on the button
+        text: String,
+
+        /// Description of the Web App that will be launched when the user presses the
+        /// button
+        web_app: WebAppInfo,
+    },
+
+    /// No specific value for the menu button was set
+    #[default]
+    Default,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct SetChatMenuButtonRequest {
+    /// Unique identifier for the target private chat. If not specified, the default bot's
+    /// menu button will be changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<i64>,
+
+    /// The bot's new menu button. Defaults to `MenuButton::Default`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub menu_button: Option<MenuButton>,
+}
+
+impl SetChatMenuButtonRequest {
+    pub fn new(menu_button: MenuButton) -> Self {
+        Self {
+            menu_button: Some(menu_button),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct GetChatMenuButtonRequest {
+    /// Unique identifier for the target private chat. If not specified, the default bot's
+    /// menu button will be returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<i64>,
+}
+
+impl GetChatMenuButtonRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl API {
+    /// Change the bot's name.
+    pub async fn set_my_name(&self, req: &SetMyNameRequest) -> anyhow::Result<bool> {
+        self.client.post("setMyName", req).await
+    }
+
+    /// Get the bot's name for the given user language.
+    pub async fn get_my_name(&self, req: &GetMyNameRequest) -> anyhow::Result<BotName> {
+        self.client.post("getMyName", req).await
+    }
+
+    /// Change the bot's description, which is shown in the chat with the bot if the chat
+    /// is empty.
+    pub async fn set_my_description(&self, req: &SetMyDescriptionRequest) -> anyhow::Result<bool> {
+        self.client.post("setMyDescription", req).await
+    }
+
+    /// Get the bot's description for the given user language.
+    pub async fn get_my_description(
+        &self,
+        req: &GetMyDescriptionRequest,
+    ) -> anyhow::Result<BotDescription> {
+        self.client.post("getMyDescription", req).await
+    }
+
+    /// Change the bot's short description, which is shown on the bot's profile page.
+    pub async fn set_my_short_description(
+        &self,
+        req: &SetMyShortDescriptionRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("setMyShortDescription", req).await
+    }
+
+    /// Get the bot's short description for the given user language.
+    pub async fn get_my_short_description(
+        &self,
+        req: &GetMyShortDescriptionRequest,
+    ) -> anyhow::Result<BotShortDescription> {
+        self.client.post("getMyShortDescription", req).await
+    }
+
+    /// Change the bot's menu button in a private chat, or the default menu button.
+    pub async fn set_chat_menu_button(
+        &self,
+        req: &SetChatMenuButtonRequest,
+    ) -> anyhow::Result<bool> {
+        self.client.post("setChatMenuButton", req).await
+    }
+
+    /// Get the bot's menu button in a private chat, or the default menu button.
+    pub async fn get_chat_menu_button(
+        &self,
+        req: &GetChatMenuButtonRequest,
+    ) -> anyhow::Result<MenuButton> {
+        self.client.post("getChatMenuButton", req).await
+    }
+}
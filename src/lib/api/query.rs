@@ -1,9 +1,10 @@
-use mobot_derive::BotRequest;
+use mobot_derive::{BotRequest, BotRequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use super::{user::User, API};
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct CallbackQuery {
     /// Unique identifier for this query
     pub id: String,
@@ -25,7 +26,8 @@ pub struct CallbackQuery {
     pub data: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct InlineQuery {
     /// Unique identifier for this query
     pub id: String,
@@ -40,12 +42,13 @@ pub struct InlineQuery {
     pub offset: String,
 }
 
-#[derive(Debug, Serialize, Clone, Default, BotRequest)]
+#[derive(Debug, Serialize, Clone, Default, BotRequest, BotRequestBuilder)]
 pub struct AnswerInlineQuery {
     /// Unique identifier for the answered query
     pub inline_query_id: String,
 
-    /// A JSON-serialized array of results for the inline query
+    /// A JSON-serialized array of results for the inline query. This is always sent, even
+    /// if empty, since an answer without results is how "nothing found" is reported.
     pub results: Vec<InlineQueryResultArticle>,
 
     /// The maximum amount of time in seconds that the result of the inline query
@@ -53,13 +56,21 @@ pub struct AnswerInlineQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_time: Option<i64>,
 
-    /// Pass True, if results may be cached on the server side only for the user
+    /// Pass True, if results may be cached on the server side only for the user that sent
+    /// the query. By default, results may be returned to any user who sends the same query.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_personal: Option<bool>,
 
-    /// Pass the offset that a client should send in the next query with the same
+    /// Pass the offset that a client should send in the next query with the same text to
+    /// receive more results. Pass an empty string if there are no more results.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_offset: Option<String>,
+
+    /// A button to be shown above inline query results. This replaces the old
+    /// `switch_pm_text` and `switch_pm_parameter` fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(skip)]
+    pub button: Option<InlineQueryResultsButton>,
 }
 
 impl AnswerInlineQuery {
@@ -72,20 +83,64 @@ impl AnswerInlineQuery {
 
     pub fn with_article_text(self, title: impl Into<String>, text: impl Into<String>) -> Self {
         Self {
-            inline_query_id: self.inline_query_id,
-            results: vec![InlineQueryResultArticle {
-                id: "0".to_string(),
-                result_type: "article".to_string(),
-                title: title.into(),
-                input_message_content: InputMessageContent {
-                    message_text: text.into(),
-                },
-            }],
+            results: vec![InlineQueryResultArticle::new("0", title, text)],
+            ..self
+        }
+    }
+
+    /// Show a button above the results, e.g., to switch the user to a private chat with
+    /// the bot to set it up.
+    pub fn with_button(mut self, button: InlineQueryResultsButton) -> Self {
+        self.button = Some(button);
+        self
+    }
+}
+
+/// `InlineQueryResultsButton` represents a button to be shown above inline query results.
+/// Exactly one of `start_parameter` or `web_app` must be set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct InlineQueryResultsButton {
+    /// Label text on the button
+    pub text: String,
+
+    /// Description of the Web App that will be launched when the user presses the button
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_app: Option<WebAppInfo>,
+
+    /// Deep-linking parameter for the /start message sent to the bot when a user presses
+    /// the button. 1-64 characters, only A-Z, a-z, 0-9, _ and - are allowed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_parameter: Option<String>,
+}
+
+impl InlineQueryResultsButton {
+    /// A button that switches the user to a private chat with the bot, and sends it
+    /// `/start start_parameter`.
+    pub fn start(text: impl Into<String>, start_parameter: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            start_parameter: Some(start_parameter.into()),
+            ..Default::default()
+        }
+    }
+
+    /// A button that launches the Web App at `url`.
+    pub fn web_app(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            web_app: Some(WebAppInfo { url: url.into() }),
             ..Default::default()
         }
     }
 }
 
+/// `WebAppInfo` describes a Web App.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct WebAppInfo {
+    /// An HTTPS URL of a Web App to be opened
+    pub url: String,
+}
+
 #[derive(Debug, Serialize, Clone, Default)]
 pub struct InlineQueryResultArticle {
     /// Unique identifier for this result, 1-64 Bytes
@@ -102,6 +157,21 @@ pub struct InlineQueryResultArticle {
     pub input_message_content: InputMessageContent,
 }
 
+impl InlineQueryResultArticle {
+    /// Returns an article that sends `text` when picked. `id` must be unique among the
+    /// results of a query.
+    pub fn new(id: impl Into<String>, title: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            result_type: "article".to_string(),
+            title: title.into(),
+            input_message_content: InputMessageContent {
+                message_text: text.into(),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone, Default)]
 pub struct InputMessageContent {
     /// Text of the message to be sent, 1-4096 characters
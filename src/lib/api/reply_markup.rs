@@ -1,18 +1,160 @@
+use anyhow::bail;
+use mobot_derive::BotRequestBuilder;
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+/// The maximum length of [`InlineKeyboardButton::callback_data`], in bytes.
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequestBuilder)]
 pub struct KeyboardButton {
     /// Text of the button. If none of the optional fields are used, it will be sent as a message when the button is pressed
     pub text: String,
-    // Optional fields omitted
+
+    /// If specified, pressing the button opens a list of suitable users, and the
+    /// identifiers of the selected users are sent to the bot in a `users_shared` service
+    /// message. Available in private chats only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_users: Option<KeyboardButtonRequestUsers>,
+
+    /// If specified, pressing the button opens a list of suitable chats, and the identifier
+    /// of the selected chat is sent to the bot in a `chat_shared` service message.
+    /// Available in private chats only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_chat: Option<KeyboardButtonRequestChat>,
 }
 
 impl<T: Into<String>> From<T> for KeyboardButton {
     fn from(text: T) -> Self {
-        Self { text: text.into() }
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The criteria for the users shown by a [`KeyboardButton::request_users`] button.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequestBuilder)]
+pub struct KeyboardButtonRequestUsers {
+    /// Identifier of the request, which will be received back in the [`UsersShared`] object.
+    /// Must be unique within the message.
+    pub request_id: i32,
+
+    /// Pass true to request bots, false to request regular users. If not specified, no
+    /// additional restrictions are applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_is_bot: Option<bool>,
+
+    /// Pass true to request premium users, false to request non-premium users. If not
+    /// specified, no additional restrictions are applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_is_premium: Option<bool>,
+
+    /// The maximum number of users to be selected; 1-10. Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_quantity: Option<i32>,
+}
+
+impl KeyboardButtonRequestUsers {
+    pub fn new(request_id: i32) -> Self {
+        Self {
+            request_id,
+            ..Default::default()
+        }
     }
 }
 
+/// The criteria for the chats shown by a [`KeyboardButton::request_chat`] button.
+#[derive(Default, Debug, Serialize, Deserialize, Clone, BotRequestBuilder)]
+pub struct KeyboardButtonRequestChat {
+    /// Identifier of the request, which will be received back in the [`ChatShared`] object.
+    /// Must be unique within the message.
+    pub request_id: i32,
+
+    /// Pass true to request a channel chat, false to request a group or a supergroup chat.
+    pub chat_is_channel: bool,
+
+    /// Pass true to request a forum supergroup, false to request a non-forum chat.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_is_forum: Option<bool>,
+
+    /// Pass true to request a supergroup or a channel with a username, false to request a
+    /// chat without a username.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_has_username: Option<bool>,
+
+    /// Pass true to request a chat owned by the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_is_created: Option<bool>,
+
+    /// Pass true to request a chat with the bot as a member.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bot_is_member: Option<bool>,
+}
+
+impl KeyboardButtonRequestChat {
+    pub fn new(request_id: i32, chat_is_channel: bool) -> Self {
+        Self {
+            request_id,
+            chat_is_channel,
+            ..Default::default()
+        }
+    }
+}
+
+/// A user shared with the bot via a [`KeyboardButton::request_users`] button.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SharedUser {
+    /// Identifier of the shared user
+    pub user_id: i64,
+
+    /// First name of the user, if the name was requested by the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+
+    /// Last name of the user, if the name was requested by the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+
+    /// Username of the user, if the username was requested by the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// `UsersShared` is the service message sent when the user picks users with a
+/// [`KeyboardButton::request_users`] button, see [`Message::users_shared`](super::Message::users_shared).
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct UsersShared {
+    /// Identifier of the request, from [`KeyboardButtonRequestUsers::request_id`]
+    pub request_id: i32,
+
+    /// The users shared with the bot
+    pub users: Vec<SharedUser>,
+}
+
+/// `ChatShared` is the service message sent when the user picks a chat with a
+/// [`KeyboardButton::request_chat`] button, see [`Message::chat_shared`](super::Message::chat_shared).
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ChatShared {
+    /// Identifier of the request, from [`KeyboardButtonRequestChat::request_id`]
+    pub request_id: i32,
+
+    /// Identifier of the shared chat
+    pub chat_id: i64,
+
+    /// Title of the chat, if the title was requested by the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Username of the chat, if the username was requested by the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// `InlineKeyboardButton` represents one button of an inline keyboard. Exactly one of the
+/// optional fields (`url`, `callback_data`, `callback_game` or `pay`) must be set.
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct InlineKeyboardButton {
     /// Label text on the button
@@ -25,8 +167,22 @@ pub struct InlineKeyboardButton {
     /// Callback data to be sent in a callback query to the bot when button is pressed, 1-64 bytes.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub callback_data: Option<String>,
+
+    /// Launches the game registered with @BotFather when the button is pressed. This must
+    /// be the first button in the first row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callback_game: Option<CallbackGame>,
+
+    /// Send a Pay button, for invoices. This must be the first button in the first row.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pay: Option<bool>,
 }
 
+/// A placeholder for [`InlineKeyboardButton::callback_game`]; it holds no information and
+/// is sent as an empty object.
+#[derive(Default, Debug, Serialize, Deserialize, Clone)]
+pub struct CallbackGame {}
+
 impl<T: Into<String>> From<T> for InlineKeyboardButton {
     fn from(text: T) -> Self {
         Self {
@@ -37,8 +193,56 @@ impl<T: Into<String>> From<T> for InlineKeyboardButton {
 }
 
 impl InlineKeyboardButton {
+    /// Set the callback data sent to the bot when the button is pressed. Telegram rejects
+    /// data longer than [`MAX_CALLBACK_DATA_LEN`] bytes when the keyboard is sent, so this
+    /// panics on long data in debug builds. Use [`InlineKeyboardButton::try_with_callback_data`]
+    /// for data that isn't known to be short (e.g., user input).
     pub fn with_callback_data<T: Into<String>>(mut self, callback_data: T) -> Self {
-        self.callback_data = Some(callback_data.into());
+        let callback_data = callback_data.into();
+        debug_assert!(
+            callback_data.len() <= MAX_CALLBACK_DATA_LEN,
+            "callback_data is longer than {} bytes: {}",
+            MAX_CALLBACK_DATA_LEN,
+            callback_data
+        );
+        self.callback_data = Some(callback_data);
+        self
+    }
+
+    /// Same as [`InlineKeyboardButton::with_callback_data`], but returns an error if the data
+    /// is longer than [`MAX_CALLBACK_DATA_LEN`] bytes.
+    pub fn try_with_callback_data<T: Into<String>>(
+        mut self,
+        callback_data: T,
+    ) -> anyhow::Result<Self> {
+        let callback_data = callback_data.into();
+        if callback_data.len() > MAX_CALLBACK_DATA_LEN {
+            bail!(
+                "callback_data is {} bytes, the maximum is {}",
+                callback_data.len(),
+                MAX_CALLBACK_DATA_LEN
+            );
+        }
+        self.callback_data = Some(callback_data);
+        Ok(self)
+    }
+
+    /// Set the callback data to `data` serialized as JSON. Returns an error if the serialized
+    /// data is longer than [`MAX_CALLBACK_DATA_LEN`] bytes. Parse the data in the callback
+    /// handler with `serde_json::from_str`.
+    pub fn try_with_callback<T: Serialize>(self, data: &T) -> anyhow::Result<Self> {
+        self.try_with_callback_data(serde_json::to_string(data)?)
+    }
+
+    /// Make this button launch the bot's game. Don't set any other optional fields.
+    pub fn with_callback_game(mut self) -> Self {
+        self.callback_game = Some(CallbackGame {});
+        self
+    }
+
+    /// Make this a Pay button. Don't set any other optional fields.
+    pub fn with_pay(mut self) -> Self {
+        self.pay = Some(true);
         self
     }
 }
@@ -1,9 +1,10 @@
-use mobot_derive::BotRequest;
+use mobot_derive::{BotRequest, BotRequestBuilder};
 use serde::{Deserialize, Serialize};
 
-use super::{message::Message, ReplyParameters, API};
+use super::{chat::ChatId, message::Message, InputFile, ReplyParameters, API};
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Sticker {
     /// Unique identifier for this file
     pub file_id: String,
@@ -17,6 +18,9 @@ pub struct Sticker {
     /// True, if the sticker is animated
     pub is_animated: bool,
 
+    /// True, if the sticker is a video sticker
+    pub is_video: bool,
+
     /// Emoji associated with the sticker
     pub emoji: Option<String>,
 
@@ -30,7 +34,7 @@ pub struct Sticker {
 #[derive(Debug, Serialize, Clone, BotRequest)]
 pub struct SendStickerRequest {
     /// Unique identifier for the target chat or username of the target
-    pub chat_id: i64,
+    pub chat_id: ChatId,
 
     /// Sticker to send. Pass a file_id as String to send a file that
     pub sticker: String,
@@ -42,15 +46,21 @@ pub struct SendStickerRequest {
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_parameters: Option<ReplyParameters>,
+
+    /// Unique identifier of the business connection on behalf of which the message will
+    /// be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
 }
 
 impl SendStickerRequest {
-    pub fn new(chat_id: i64, sticker: String) -> Self {
+    pub fn new(chat_id: impl Into<ChatId>, sticker: String) -> Self {
         Self {
-            chat_id,
+            chat_id: chat_id.into(),
             sticker,
             disable_notification: None,
             reply_parameters: None,
+            business_connection_id: None,
         }
     }
 
@@ -58,10 +68,218 @@ impl SendStickerRequest {
         self.reply_parameters = Some(reply_parameters);
         self
     }
+
+    /// Reply to the message with the given ID in the same chat. Shorthand for
+    /// `with_reply_parameters(ReplyParameters::new(message_id))`.
+    pub fn with_reply_to_message_id(self, message_id: i64) -> Self {
+        self.with_reply_parameters(ReplyParameters::new(message_id))
+    }
+
+    pub fn with_business_connection_id(
+        mut self,
+        business_connection_id: impl Into<String>,
+    ) -> Self {
+        self.business_connection_id = Some(business_connection_id.into());
+        self
+    }
+}
+
+/// `StickerSet` represents a sticker set, see [`API::get_sticker_set`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StickerSet {
+    /// Sticker set name
+    pub name: String,
+
+    /// Sticker set title
+    pub title: String,
+
+    /// Type of stickers in the set
+    pub sticker_type: StickerType,
+
+    /// List of all set stickers
+    pub stickers: Vec<Sticker>,
+}
+
+/// The type of stickers in a sticker set.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickerType {
+    #[default]
+    Regular,
+    Mask,
+    CustomEmoji,
+}
+
+/// The format of a sticker file.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickerFormat {
+    /// A .WEBP or .PNG image, 512 pixels on one side
+    #[default]
+    Static,
+
+    /// A .TGS animation
+    Animated,
+
+    /// A .WEBM video
+    Video,
+}
+
+/// `InputSticker` describes a sticker to be added to a sticker set, see
+/// [`API::create_new_sticker_set`] and [`API::add_sticker_to_set`].
+#[derive(Debug, Clone, Deserialize, Serialize, BotRequestBuilder)]
+pub struct InputSticker {
+    /// The sticker: a `file_id` or HTTP URL, or a new file to upload with
+    /// [`InputFile::upload`]. Animated and video stickers can't be fetched via HTTP URL.
+    pub sticker: InputFile,
+
+    /// Format of the sticker
+    pub format: StickerFormat,
+
+    /// List of 1-20 emoji associated with the sticker
+    pub emoji_list: Vec<String>,
+
+    /// List of 0-20 search keywords for the sticker with total length of up to 64
+    /// characters. For “regular” and “custom_emoji” stickers only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<Vec<String>>,
+}
+
+impl InputSticker {
+    pub fn new(
+        sticker: impl Into<InputFile>,
+        format: StickerFormat,
+        emoji: impl Into<String>,
+    ) -> Self {
+        Self {
+            sticker: sticker.into(),
+            format,
+            emoji_list: vec![emoji.into()],
+            keywords: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct CreateNewStickerSetRequest {
+    /// User identifier of created sticker set owner
+    pub user_id: i64,
+
+    /// Short name of the sticker set, to be used in t.me/addstickers/ URLs. Must end in
+    /// "_by_<bot_username>".
+    pub name: String,
+
+    /// Sticker set title, 1-64 characters
+    pub title: String,
+
+    /// A list of 1-50 initial stickers to be added to the sticker set
+    pub stickers: Vec<InputSticker>,
+
+    /// Type of stickers in the set. By default, a regular sticker set is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sticker_type: Option<StickerType>,
+}
+
+impl CreateNewStickerSetRequest {
+    pub fn new(
+        user_id: i64,
+        name: impl Into<String>,
+        title: impl Into<String>,
+        stickers: Vec<InputSticker>,
+    ) -> Self {
+        Self {
+            user_id,
+            name: name.into(),
+            title: title.into(),
+            stickers,
+            sticker_type: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, BotRequest)]
+pub struct AddStickerToSetRequest {
+    /// User identifier of sticker set owner
+    pub user_id: i64,
+
+    /// Sticker set name
+    pub name: String,
+
+    /// The sticker to add to the set
+    pub sticker: InputSticker,
+}
+
+impl AddStickerToSetRequest {
+    pub fn new(user_id: i64, name: impl Into<String>, sticker: InputSticker) -> Self {
+        Self {
+            user_id,
+            name: name.into(),
+            sticker,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, BotRequest)]
+pub struct GetStickerSetRequest {
+    /// Name of the sticker set
+    pub name: String,
+}
+
+impl GetStickerSetRequest {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+/// Replace the uploads in `stickers` with `attach://` references, and return the files
+/// to upload along with the request.
+fn attach_stickers<'a>(
+    stickers: impl Iterator<Item = &'a mut InputSticker>,
+) -> Vec<(String, String, bytes::Bytes)> {
+    let mut files = vec![];
+    for (i, sticker) in stickers.enumerate() {
+        if let InputFile::Upload {
+            ref file_name,
+            ref data,
+        } = sticker.sticker
+        {
+            let field = format!("sticker{}", i);
+            files.push((field.clone(), file_name.clone(), data.clone()));
+            sticker.sticker = InputFile::Remote(format!("attach://{}", field));
+        }
+    }
+    files
 }
 
 impl API {
     pub async fn send_sticker(&self, req: &SendStickerRequest) -> anyhow::Result<Message> {
         self.client.post("sendSticker", req).await
     }
+
+    /// Get a sticker set by name.
+    pub async fn get_sticker_set(&self, req: &GetStickerSetRequest) -> anyhow::Result<StickerSet> {
+        self.client.post("getStickerSet", req).await
+    }
+
+    /// Create a new sticker set owned by a user. New sticker files are uploaded as
+    /// multipart/form-data.
+    pub async fn create_new_sticker_set(
+        &self,
+        req: &CreateNewStickerSetRequest,
+    ) -> anyhow::Result<bool> {
+        let mut req = req.clone();
+        let files = attach_stickers(req.stickers.iter_mut());
+        self.client
+            .post_files("createNewStickerSet", &req, files)
+            .await
+    }
+
+    /// Add a new sticker to a set created by the bot. New sticker files are uploaded as
+    /// multipart/form-data.
+    pub async fn add_sticker_to_set(&self, req: &AddStickerToSetRequest) -> anyhow::Result<bool> {
+        let mut req = req.clone();
+        let files = attach_stickers(std::iter::once(&mut req.sticker));
+        self.client.post_files("addStickerToSet", &req, files).await
+    }
 }
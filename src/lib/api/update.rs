@@ -1,9 +1,16 @@
+use std::collections::{HashMap, VecDeque};
+
+use futures::Stream;
 use mobot_derive::BotRequest;
 use serde::{Deserialize, Serialize};
 
-use super::{message::Message, query::InlineQuery, CallbackQuery, API};
+use super::{
+    business::BusinessConnection, message::Message, query::InlineQuery, CallbackQuery,
+    ChatJoinRequest, ChatMemberUpdated, PreCheckoutQuery, API,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Update {
     /// The update‘s unique identifier. Update identifiers start from a
     /// certain positive number and increase sequentially. This ID becomes
@@ -38,6 +45,40 @@ pub struct Update {
     /// Callbakc query
     #[serde(skip_serializing_if = "Option::is_none")]
     pub callback_query: Option<CallbackQuery>,
+
+    /// The bot was connected to or disconnected from a business account, or a user
+    /// edited an existing connection with the bot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection: Option<BusinessConnection>,
+
+    /// New message from a connected business account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_message: Option<Message>,
+
+    /// A request to join the chat has been sent. The bot must have the
+    /// `can_invite_users` administrator right in the chat to receive these updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_join_request: Option<ChatJoinRequest>,
+
+    /// The bot's chat member status was updated in a chat. For private chats, this update
+    /// is received only when the bot is blocked or unblocked by the user.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub my_chat_member: Option<ChatMemberUpdated>,
+
+    /// A chat member's status was updated in a chat. The bot must be an administrator in
+    /// the chat and must explicitly specify “chat_member” in `allowed_updates` to receive
+    /// these updates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_member: Option<ChatMemberUpdated>,
+
+    /// New incoming pre-checkout query, which contains full information about checkout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_checkout_query: Option<PreCheckoutQuery>,
+
+    /// Update types that mobot doesn't model yet, keyed by type (e.g.,
+    /// `"message_reaction"`). These are dispatched to `Route::Unknown` handlers.
+    #[serde(flatten)]
+    pub other: HashMap<String, serde_json::Value>,
 }
 
 /// Use this method to receive incoming updates using long or short
@@ -68,6 +109,61 @@ pub struct GetUpdatesRequest {
     pub allowed_updates: Option<Vec<String>>,
 }
 
+/// Removes the webhook integration, so the bot can switch back to `getUpdates`. See
+/// [`API::delete_webhook`].
+#[derive(Debug, Clone, Deserialize, Serialize, Default, BotRequest)]
+pub struct DeleteWebhookRequest {
+    /// Pass true to drop all pending updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_pending_updates: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, BotRequest)]
+pub struct GetWebhookInfoRequest {}
+
+/// The current status of the bot's webhook, see [`API::get_webhook_info`].
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct WebhookInfo {
+    /// Webhook URL, may be empty if the webhook is not set up
+    pub url: String,
+
+    /// True, if a custom certificate was provided for webhook certificate checks
+    pub has_custom_certificate: bool,
+
+    /// Number of updates awaiting delivery
+    pub pending_update_count: i64,
+
+    /// Currently used webhook IP address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_address: Option<String>,
+
+    /// Unix time for the most recent error that happened when trying to deliver an update
+    /// via webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_date: Option<i64>,
+
+    /// Error message in human-readable format for the most recent error that happened when
+    /// trying to deliver an update via webhook
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_message: Option<String>,
+
+    /// Unix time of the most recent error that happened when trying to synchronize
+    /// available updates with Telegram datacenters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_synchronization_error_date: Option<i64>,
+
+    /// The maximum allowed number of simultaneous HTTPS connections to the webhook for
+    /// update delivery
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_connections: Option<i64>,
+
+    /// A list of update types the bot is subscribed to. Defaults to all update types except
+    /// `chat_member`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_updates: Option<Vec<String>>,
+}
+
 /// Convenience methods for `GetUpdatesRequest`.
 impl GetUpdatesRequest {
     pub fn new() -> Self {
@@ -91,6 +187,13 @@ impl GetUpdatesRequest {
         self.offset = Some(offset);
         self
     }
+
+    /// Set the update types to receive. An empty list means all update types except
+    /// `chat_member`, `message_reaction`, and `message_reaction_count`.
+    pub fn with_allowed_updates(mut self, allowed_updates: Vec<String>) -> Self {
+        self.allowed_updates = Some(allowed_updates);
+        self
+    }
 }
 
 impl API {
@@ -100,4 +203,72 @@ impl API {
     pub async fn get_updates(&self, req: &GetUpdatesRequest) -> anyhow::Result<Vec<Update>> {
         self.client.post("getUpdates", req).await
     }
+
+    /// Remove the bot's webhook. Telegram rejects `getUpdates` with 409 (Conflict) while a
+    /// webhook is set, so call this before polling if the bot used webhooks before. If
+    /// `drop_pending_updates` is true, updates that haven't been delivered yet are dropped.
+    pub async fn delete_webhook(&self, drop_pending_updates: bool) -> anyhow::Result<bool> {
+        let req = DeleteWebhookRequest {
+            drop_pending_updates: Some(drop_pending_updates),
+        };
+        self.client.post("deleteWebhook", &req).await
+    }
+
+    /// Get the current status of the bot's webhook. If the bot uses `getUpdates`, the `url`
+    /// is empty.
+    pub async fn get_webhook_info(&self) -> anyhow::Result<WebhookInfo> {
+        self.client
+            .post("getWebhookInfo", &GetWebhookInfoRequest {})
+            .await
+    }
+
+    /// Returns a stream of updates, for users who want to do their own dispatching instead of
+    /// using the `Router`. The stream long-polls `getUpdates` with `req`, and keeps track of
+    /// the offset so every update is yielded exactly once.
+    ///
+    /// Polling errors are yielded as `Err` items, and the stream keeps polling if you keep
+    /// reading from it (it's up to the caller to back off or stop.)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # use futures::StreamExt;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let api = API::new(Client::new(std::env::var("TELEGRAM_TOKEN").unwrap()));
+    /// let updates = api.updates_stream(api::GetUpdatesRequest::new().with_timeout(60));
+    /// futures::pin_mut!(updates);
+    ///
+    /// while let Some(update) = updates.next().await {
+    ///     println!("{:?}", update);
+    /// }
+    /// # }
+    /// ```
+    pub fn updates_stream(
+        &self,
+        req: GetUpdatesRequest,
+    ) -> impl Stream<Item = anyhow::Result<Update>> + '_ {
+        futures::stream::unfold(
+            (req, VecDeque::new()),
+            move |(mut req, mut pending)| async move {
+                loop {
+                    if let Some(update) = pending.pop_front() {
+                        return Some((Ok(update), (req, pending)));
+                    }
+
+                    match self.get_updates(&req).await {
+                        Ok(updates) => {
+                            if let Some(last_update_id) = updates.iter().map(|u| u.update_id).max()
+                            {
+                                req.offset = Some(last_update_id + 1);
+                            }
+                            pending.extend(updates);
+                        }
+                        Err(err) => return Some((Err(err), (req, pending))),
+                    }
+                }
+            },
+        )
+    }
 }
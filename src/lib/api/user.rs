@@ -1,13 +1,18 @@
 use std::collections::hash_map::DefaultHasher;
 
-use mobot_derive::BotRequest;
+use futures::Stream;
+use mobot_derive::{BotRequest, BotRequestBuilder};
 use serde::{Deserialize, Serialize};
 
 use std::hash::{Hash, Hasher};
 
-use super::API;
+use super::{PhotoSize, API};
+
+/// The maximum number of photos returned by each `getUserProfilePhotos` call.
+const MAX_PROFILE_PHOTOS_LIMIT: i64 = 100;
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
 pub struct User {
     /// Unique identifier for this user or bot
     pub id: i64,
@@ -47,9 +52,94 @@ impl<T: Into<String>> From<T> for User {
 #[derive(Debug, Clone, Serialize, BotRequest)]
 pub struct GetMeRequest {}
 
+/// A page of a user's profile pictures, see [`API::get_user_profile_photos`].
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UserProfilePhotos {
+    /// Total number of profile pictures the target user has
+    pub total_count: i64,
+
+    /// Requested profile pictures (in up to 4 sizes each)
+    pub photos: Vec<Vec<PhotoSize>>,
+}
+
+#[derive(Default, Debug, Clone, Deserialize, Serialize, BotRequest, BotRequestBuilder)]
+pub struct GetUserProfilePhotosRequest {
+    /// Unique identifier of the target user
+    pub user_id: i64,
+
+    /// Sequential number of the first photo to be returned. By default, all photos are
+    /// returned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+
+    /// Limits the number of photos to be retrieved. Values between 1-100 are accepted.
+    /// Defaults to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
+
+impl GetUserProfilePhotosRequest {
+    pub fn new(user_id: i64) -> Self {
+        Self {
+            user_id,
+            ..Default::default()
+        }
+    }
+}
+
 impl API {
     pub async fn get_me(&self) -> anyhow::Result<User> {
         let req = GetMeRequest {};
         self.client.post("getMe", &req).await
     }
+
+    /// Get a page of a user's profile pictures. Use [`API::user_profile_photos_stream`] to
+    /// get all of them.
+    pub async fn get_user_profile_photos(
+        &self,
+        req: &GetUserProfilePhotosRequest,
+    ) -> anyhow::Result<UserProfilePhotos> {
+        self.client.post("getUserProfilePhotos", req).await
+    }
+
+    /// Returns a stream of all of a user's profile pictures (each in up to 4 sizes), newest
+    /// first. Pages are fetched with `getUserProfilePhotos` as the stream is read, so
+    /// stopping early saves requests.
+    ///
+    /// If a request fails, the error is yielded and the stream ends.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # use futures::TryStreamExt;
+    /// # async fn run(api: API) -> anyhow::Result<()> {
+    /// let photos: Vec<_> = api.user_profile_photos_stream(1234).try_collect().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn user_profile_photos_stream(
+        &self,
+        user_id: i64,
+    ) -> impl Stream<Item = anyhow::Result<Vec<PhotoSize>>> + '_ {
+        let pages = futures::stream::unfold(Some(0), move |offset| async move {
+            let offset = offset?;
+            let req = GetUserProfilePhotosRequest::new(user_id)
+                .with_offset(offset)
+                .with_limit(MAX_PROFILE_PHOTOS_LIMIT);
+
+            match self.get_user_profile_photos(&req).await {
+                Ok(page) => {
+                    let next = offset + page.photos.len() as i64;
+                    let more = !page.photos.is_empty() && next < page.total_count;
+                    let photos = page.photos.into_iter().map(Ok).collect::<Vec<_>>();
+                    Some((photos, more.then_some(next)))
+                }
+                Err(err) => Some((vec![Err(err)], None)),
+            }
+        });
+
+        futures::StreamExt::flat_map(pages, futures::stream::iter)
+    }
 }
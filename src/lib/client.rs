@@ -1,11 +1,16 @@
-use std::fmt::{self, Formatter};
+use std::{
+    fmt::{self, Formatter},
+    sync::OnceLock,
+    time::Duration,
+};
 
 use anyhow::Result;
 use bytes;
 use derive_more::*;
+use rand::{distributions::Alphanumeric, Rng};
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::api::ApiResponse;
+use crate::{api::ApiResponse, ratelimit::RateLimiter};
 
 /// This is a wrapper around the Telegram API token string. Get your token from
 /// [@BotFather](https://t.me/BotFather).
@@ -38,8 +43,36 @@ pub trait Post {
     async fn post(&self, method: String, req: String) -> Result<String>;
 }
 
+/// Connection pool settings for the underlying HTTP client, see
+/// [`Client::with_pool_max_idle_per_host`].
+#[derive(Debug, Clone, Default)]
+struct PoolOptions {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+}
+
+impl PoolOptions {
+    fn build(&self) -> reqwest::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(max_idle_per_host) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle_per_host);
+        }
+        if let Some(idle_timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(idle_timeout);
+        }
+        if let Some(tcp_keepalive) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(tcp_keepalive);
+        }
+        builder.build()
+    }
+}
+
 /// This is a thin shim around the Telegram HTTP client. Requires a valid API token.
 pub struct Client {
+    /// The API token, used to construct the URLs below.
+    token: ApiToken,
+
     /// This base URL is used for all requests and is constructed from the
     /// provided API token.
     base_url: String,
@@ -47,8 +80,20 @@ pub struct Client {
     /// This is URL is used for requests for download files
     file_url: String,
 
-    /// The underlying HTTP client.
-    client: reqwest::Client,
+    /// The underlying HTTP client, built from `pool` when the first request is sent.
+    client: OnceLock<reqwest::Client>,
+
+    /// Connection pool settings for the HTTP client.
+    pool: PoolOptions,
+
+    /// The User-Agent header sent with every request.
+    user_agent: String,
+
+    /// If true, send a random `X-Request-Id` header with every request.
+    request_ids: bool,
+
+    /// Delays outgoing messages to stay within Telegram's limits, if set.
+    rate_limiter: Option<RateLimiter>,
 
     /// A post handler that implements the Post trait. Useful for testing.
     post_handler: Option<Box<dyn Post + Send + Sync>>,
@@ -64,12 +109,122 @@ impl Client {
         Self {
             base_url: format!("https://api.telegram.org/bot{token}"),
             file_url: format!("https://api.telegram.org/file/bot{token}"),
-            client: reqwest::Client::new(),
+            token,
+            client: OnceLock::new(),
+            pool: PoolOptions::default(),
+            user_agent: format!("mobot/{}", env!("CARGO_PKG_VERSION")),
+            request_ids: false,
+            rate_limiter: None,
             post_handler: None,
             post_handler_fn: None,
         }
     }
 
+    /// Send requests to the Bot API server at `api_url` (e.g., `http://localhost:8081`)
+    /// instead of `https://api.telegram.org`, e.g., to use a self-hosted
+    /// [Bot API server](https://github.com/tdlib/telegram-bot-api).
+    pub fn with_api_url(mut self, api_url: impl AsRef<str>) -> Self {
+        let api_url = api_url.as_ref().trim_end_matches('/');
+        self.base_url = format!("{}/bot{}", api_url, self.token);
+        self.file_url = format!("{}/file/bot{}", api_url, self.token);
+        self
+    }
+
+    /// Sets the User-Agent header sent with every request. Defaults to `mobot/<version>`.
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Sets the maximum number of idle connections to keep open to the Telegram API. The
+    /// `getUpdates` long poll and outgoing requests (e.g., `sendMessage`) share the pool,
+    /// so bots that send many messages concurrently benefit from keeping a few
+    /// connections warm. Defaults to no limit.
+    pub fn with_pool_max_idle_per_host(mut self, max_idle_per_host: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max_idle_per_host);
+        self
+    }
+
+    /// Sets how long idle connections are kept open before they're closed. Set this below
+    /// any idle timeout enforced by a proxy between the bot and Telegram, so the client
+    /// doesn't reuse connections the proxy has already dropped. Defaults to 90 seconds.
+    pub fn with_pool_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.pool.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Enables TCP keep-alive probes on connections, sent at the given interval. This
+    /// keeps connections (including the long poll) from being silently dropped by NATs
+    /// and proxies. Disabled by default.
+    pub fn with_tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.pool.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// If set, send a random `X-Request-Id` header with every request, and include it in
+    /// the debug logs, so requests can be correlated with server-side logs.
+    pub fn with_request_ids(mut self, request_ids: bool) -> Self {
+        self.request_ids = request_ids;
+        self
+    }
+
+    /// Delay outgoing messages to stay within Telegram's rate limits, instead of having
+    /// them rejected with 429 (Too Many Requests). See [`RateLimiter`].
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Wait for the rate limiter (if any) to allow sending `req` to `method`.
+    async fn rate_limit(&self, method: &str, req: &impl Serialize) {
+        let Some(ref rate_limiter) = self.rate_limiter else {
+            return;
+        };
+
+        if !RateLimiter::limits(method) {
+            return;
+        }
+
+        let chat_id = serde_json::to_value(req)
+            .ok()
+            .and_then(|req| match req.get("chat_id") {
+                Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+                Some(serde_json::Value::String(id)) => Some(id.clone()),
+                _ => None,
+            });
+        rate_limiter.acquire(chat_id.as_deref()).await;
+    }
+
+    /// Returns the HTTP client, building it with the connection pool settings the first
+    /// time it's used.
+    fn http_client(&self) -> Result<&reqwest::Client> {
+        if let Some(client) = self.client.get() {
+            return Ok(client);
+        }
+        let client = self.pool.build()?;
+        Ok(self.client.get_or_init(|| client))
+    }
+
+    /// Returns a request to `url` with the User-Agent and request ID headers set.
+    fn request(&self, method: reqwest::Method, url: String) -> Result<reqwest::RequestBuilder> {
+        let request = self
+            .http_client()?
+            .request(method, url)
+            .header(reqwest::header::USER_AGENT, &self.user_agent);
+
+        if !self.request_ids {
+            return Ok(request);
+        }
+
+        let request_id: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        debug!("X-Request-Id: {}", request_id);
+        Ok(request.header("X-Request-Id", request_id))
+    }
+
     /// Sets a function that handles POST requests. This is useful for testing.
     pub fn with_post_handler_fn(mut self, post_fn: impl Into<PostFn>) -> Self {
         self.post_handler_fn = Some(post_fn.into());
@@ -87,10 +242,15 @@ impl Client {
         Req: crate::api::Request,
         Resp: Serialize + DeserializeOwned + Clone,
     {
-        let body;
+        self.rate_limit(method, req).await;
+
+        // Post handlers don't deal with HTTP, so treat their responses as 200 OK.
+        let (status, body);
         if let Some(ref post_handler) = self.post_handler_fn {
+            status = 200;
             body = (post_handler.0)(method.to_string(), serde_json::to_string(req)?).unwrap();
         } else if let Some(ref post_handler) = self.post_handler {
+            status = 200;
             body = post_handler
                 .post(method.to_string(), serde_json::to_string(req)?)
                 .await?;
@@ -100,16 +260,103 @@ impl Client {
                 method,
                 serde_json::to_string_pretty(req).unwrap()
             );
-            body = self
-                .client
-                .post(format!("{}/{}", self.base_url, method))
+            let response = self
+                .request(
+                    reqwest::Method::POST,
+                    format!("{}/{}", self.base_url, method),
+                )?
                 .json(&req)
                 .send()
-                .await?
-                .text()
                 .await?;
+            status = response.status().as_u16();
+            body = response.text().await?;
+        }
+        Self::parse_response(method, status, &body)
+    }
+
+    /// Send `method` with `req` as a multipart/form-data request, uploading `data` as the
+    /// file field `field`. The `field` key in `req` is replaced by the file. Post handlers
+    /// (used for testing) receive `req` as JSON, without the file data.
+    pub async fn post_multipart<Req, Resp>(
+        &self,
+        method: &str,
+        req: &Req,
+        field: &str,
+        file_name: &str,
+        data: bytes::Bytes,
+    ) -> Result<Resp>
+    where
+        Req: crate::api::Request,
+        Resp: Serialize + DeserializeOwned + Clone,
+    {
+        self.post_files(
+            method,
+            req,
+            vec![(field.to_string(), file_name.to_string(), data)],
+        )
+        .await
+    }
+
+    /// Same as [`Client::post_multipart`], but uploads several files, given as (field, file
+    /// name, data). Fields that aren't keys of `req` can be referenced from it as
+    /// `attach://<field>`, e.g., for the stickers of
+    /// [`API::create_new_sticker_set`](crate::API::create_new_sticker_set).
+    pub async fn post_files<Req, Resp>(
+        &self,
+        method: &str,
+        req: &Req,
+        files: Vec<(String, String, bytes::Bytes)>,
+    ) -> Result<Resp>
+    where
+        Req: crate::api::Request,
+        Resp: Serialize + DeserializeOwned + Clone,
+    {
+        if self.post_handler_fn.is_some() || self.post_handler.is_some() {
+            return self.post(method, req).await;
+        }
+
+        self.rate_limit(method, req).await;
+
+        debug!(
+            "POST /{} (multipart, {} bytes in {:?})",
+            method,
+            files.iter().map(|(_, _, data)| data.len()).sum::<usize>(),
+            files.iter().map(|(field, _, _)| field).collect::<Vec<_>>()
+        );
+        let mut form = reqwest::multipart::Form::new();
+        if let serde_json::Value::Object(fields) = serde_json::to_value(req)? {
+            for (key, value) in fields {
+                match value {
+                    _ if files.iter().any(|(field, _, _)| *field == key) => {}
+                    serde_json::Value::Null => {}
+                    serde_json::Value::String(s) => form = form.text(key, s),
+                    value => form = form.text(key, value.to_string()),
+                }
+            }
         }
-        let response = ApiResponse::<Resp>::from_str(&body)?;
+        for (field, file_name, data) in files {
+            let file = reqwest::multipart::Part::bytes(data.to_vec()).file_name(file_name);
+            form = form.part(field, file);
+        }
+
+        let response = self
+            .request(
+                reqwest::Method::POST,
+                format!("{}/{}", self.base_url, method),
+            )?
+            .multipart(form)
+            .send()
+            .await?;
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        Self::parse_response(method, status, &body)
+    }
+
+    fn parse_response<Resp>(method: &str, status: u16, body: &str) -> Result<Resp>
+    where
+        Resp: Serialize + DeserializeOwned + Clone,
+    {
+        let response = ApiResponse::<Resp>::from_http(status, body)?;
         debug!(
             "Response /{}:\n{}",
             method,
@@ -121,8 +368,10 @@ impl Client {
     pub async fn download_file(&self, file_path: &String) -> Result<bytes::Bytes> {
         debug!("Downloading file /{}:\n", file_path);
         let body = self
-            .client
-            .get(format!("{}/{}", self.file_url, file_path))
+            .request(
+                reqwest::Method::GET,
+                format!("{}/{}", self.file_url, file_path),
+            )?
             .send()
             .await?
             .bytes()
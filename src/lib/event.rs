@@ -1,19 +1,452 @@
 use crate::{
     api::{self, API},
-    Text,
+    handler::{BotState, StateMap},
+    Action, Route, State, Text,
 };
-use std::sync::Arc;
+use futures::{future::BoxFuture, Future};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A `Localizer` takes a language code and a message key, and returns the localized
+/// message. See [`Router::with_localizer`](crate::Router::with_localizer).
+pub type Localizer = Arc<dyn Fn(&str, &str) -> String + Send + Sync>;
+
+/// Runs a background job on behalf of a chat, see [`Event::spawn`].
+pub(crate) type Spawner = Arc<dyn Fn(BoxFuture<'static, anyhow::Result<()>>) + Send + Sync>;
+
+/// The language passed to the localizer when the sender's language is unknown.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// How often [`Event::keep_typing`] resends the typing action. Telegram shows a chat
+/// action for 5 seconds, so this refreshes it just before it expires.
+pub const TYPING_REFRESH_INTERVAL: Duration = Duration::from_secs(4);
+
+/// How long [`Event::my_permissions`] caches the bot's membership in a chat by default,
+/// see [`Router::with_permissions_ttl`](crate::Router::with_permissions_ttl).
+pub const PERMISSIONS_TTL: Duration = Duration::from_secs(60);
+
+/// How long a prompt sent with [`Event::ask`] waits for an answer by default, see
+/// [`Router::with_prompt_ttl`](crate::Router::with_prompt_ttl).
+pub const PROMPT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// The bot's membership in a chat, and when it was fetched.
+type CachedMember = (Instant, api::ChatMember);
+
+/// Caches the bot's user ID and its membership in each chat, keyed by account, for
+/// [`Event::my_permissions`]. Shared by all the events dispatched by a router.
+#[derive(Clone)]
+pub(crate) struct PermissionsCache {
+    ttl: Duration,
+    me: Arc<Mutex<HashMap<usize, i64>>>,
+    members: Arc<Mutex<HashMap<(usize, i64), CachedMember>>>,
+}
+
+impl PermissionsCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            me: Arc::new(Mutex::new(HashMap::new())),
+            members: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn get(&self, account: usize, chat_id: i64) -> Option<api::ChatMember> {
+        let mut members = self.members.lock().unwrap();
+        match members.get(&(account, chat_id)) {
+            Some((fetched, member)) if fetched.elapsed() < self.ttl => Some(member.clone()),
+            Some(_) => {
+                members.remove(&(account, chat_id));
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Record the bot's membership in a chat, e.g., from a `my_chat_member` update. Expired
+    /// entries are dropped, so chats that are never looked up again don't pile up.
+    pub(crate) fn insert(&self, account: usize, chat_id: i64, member: api::ChatMember) {
+        let mut members = self.members.lock().unwrap();
+        members.retain(|_, (fetched, _)| fetched.elapsed() < self.ttl);
+        members.insert((account, chat_id), (Instant::now(), member));
+    }
+}
+
+/// When each pending prompt in a chat was sent, and its `message_id`, by name.
+type ChatPrompts = HashMap<String, (Instant, i64)>;
+
+/// The prompts sent with [`Event::ask`] that are still waiting for an answer, keyed by
+/// account and chat. Prompts expire after `ttl`. Shared by all the events dispatched by
+/// a router.
+#[derive(Clone)]
+pub(crate) struct PendingPrompts {
+    ttl: Duration,
+    prompts: Arc<Mutex<HashMap<(usize, i64), ChatPrompts>>>,
+}
+
+impl PendingPrompts {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            prompts: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Record `message_id` as the pending prompt `name` in a chat. Each chat has at most
+    /// one pending prompt per name, so this replaces any earlier one. Expired prompts are
+    /// dropped, so chats that never answer don't pile up.
+    fn insert(&self, account: usize, chat_id: i64, name: String, message_id: i64) {
+        let mut prompts = self.prompts.lock().unwrap();
+        prompts.retain(|_, chat_prompts| {
+            chat_prompts.retain(|_, (sent, _)| sent.elapsed() < self.ttl);
+            !chat_prompts.is_empty()
+        });
+        prompts
+            .entry((account, chat_id))
+            .or_default()
+            .insert(name, (Instant::now(), message_id));
+    }
+
+    /// If `message_id` is a pending prompt in a chat, remove it, and return its name.
+    /// Expired prompts are removed, but not returned.
+    pub(crate) fn take(&self, account: usize, chat_id: i64, message_id: i64) -> Option<String> {
+        let mut prompts = self.prompts.lock().unwrap();
+        let chat_prompts = prompts.get_mut(&(account, chat_id))?;
+        let name = chat_prompts
+            .iter()
+            .find_map(|(name, (_, id))| (*id == message_id).then(|| name.clone()))?;
+        let (sent, _) = chat_prompts.remove(&name)?;
+        if chat_prompts.is_empty() {
+            prompts.remove(&(account, chat_id));
+        }
+        (sent.elapsed() < self.ttl).then_some(name)
+    }
+}
+
+/// Keeps sending a chat action until dropped, see [`Event::keep_typing`].
+#[must_use = "the chat action stops when the guard is dropped"]
+pub struct TypingGuard(tokio::task::AbortHandle);
+
+impl Drop for TypingGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
 
 /// `Event` represents an event sent to a chat handler.
 #[derive(Clone)]
 pub struct Event {
     pub api: Arc<API>,
     pub update: crate::Update,
+
+    /// Additional per-chat states, see [`Event::state`].
+    pub(crate) states: StateMap,
+
+    /// Used by [`Event::tr`] to localize messages.
+    pub(crate) localizer: Option<Localizer>,
+
+    /// Used by [`Event::spawn`] to run background jobs.
+    pub(crate) spawner: Option<Spawner>,
+
+    /// The route that matched this event, see [`Event::matched_route`].
+    pub(crate) matched_route: Option<Route>,
+
+    /// The account the update was received on, see [`Event::account`].
+    pub(crate) account: usize,
+
+    /// The action returned by the previous handler, see [`Event::prev_action`].
+    pub(crate) prev_action: Option<Action>,
+
+    /// Shared by all the handlers that run for an update, see
+    /// [`Event::last_sent_message_id`].
+    pub(crate) last_sent_message_id: Arc<Mutex<Option<i64>>>,
+
+    /// Used by [`Event::my_permissions`], shared by all the events dispatched by a router.
+    pub(crate) permissions: Option<PermissionsCache>,
+
+    /// Used by [`Event::ask`], shared by all the events dispatched by a router.
+    pub(crate) prompts: Option<PendingPrompts>,
+
+    /// The update as received from Telegram, see [`Event::raw_update`].
+    pub(crate) raw_update: Option<Arc<api::Update>>,
 }
 
 impl Event {
     pub fn new(api: Arc<API>, update: crate::Update) -> Self {
-        Self { api, update }
+        Self {
+            api,
+            update,
+            states: StateMap::new(),
+            localizer: None,
+            spawner: None,
+            matched_route: None,
+            account: 0,
+            prev_action: None,
+            last_sent_message_id: Arc::new(Mutex::new(None)),
+            permissions: None,
+            prompts: None,
+            raw_update: None,
+        }
+    }
+
+    pub(crate) fn with_account(mut self, account: usize) -> Self {
+        self.account = account;
+        self
+    }
+
+    /// Returns the index of the bot account this event was received on. This is 0 for the
+    /// router's own client, and counts up for each client added with
+    /// [`Router::with_account`](crate::Router::with_account). Replies sent via
+    /// [`Event::api`] always go out on the same account.
+    pub fn account(&self) -> usize {
+        self.account
+    }
+
+    pub(crate) fn with_matched_route(mut self, route: Route) -> Self {
+        self.matched_route = Some(route);
+        self
+    }
+
+    /// Returns the route (with its matcher) that the handler was registered with, and that
+    /// matched this event. Useful for handlers registered on multiple routes, e.g., both
+    /// `Route::Message` and `Route::EditedMessage`. Handlers added with `Route::Default`
+    /// see `Route::Any(Matcher::Any)`. Returns `None` if the event wasn't dispatched by a
+    /// router.
+    pub fn matched_route(&self) -> Option<&Route> {
+        self.matched_route.as_ref()
+    }
+
+    pub(crate) fn with_prev_action(mut self, action: Option<Action>) -> Self {
+        self.prev_action = action;
+        self
+    }
+
+    /// Returns the action returned by the previous handler that ran for this update (one
+    /// that continued dispatch, e.g., `Action::Next` or `Action::NextWith`), or `None` if
+    /// this is the first handler to run.
+    pub fn prev_action(&self) -> Option<&Action> {
+        self.prev_action.as_ref()
+    }
+
+    /// Returns the value passed down by the previous handler with `Action::NextWith`, if
+    /// any. For data that must outlive the current update, use the chat state instead.
+    pub fn prev_value(&self) -> Option<&serde_json::Value> {
+        match self.prev_action {
+            Some(Action::NextWith(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn with_raw_update(mut self, update: Arc<api::Update>) -> Self {
+        self.raw_update = Some(update);
+        self
+    }
+
+    /// Returns the update as received from Telegram, or `None` if the event wasn't
+    /// dispatched by a router. Use this to handle update types that mobot doesn't model
+    /// yet (see `Route::Unknown`), which are kept in [`api::Update::other`].
+    pub fn raw_update(&self) -> Option<&api::Update> {
+        self.raw_update.as_deref()
+    }
+
+    pub(crate) fn with_last_sent_message_id(mut self, id: Arc<Mutex<Option<i64>>>) -> Self {
+        self.last_sent_message_id = id;
+        self
+    }
+
+    /// Returns the ID of the message sent by the latest `Action::ReplyAndContinue` for this
+    /// update, or `None` if no earlier handler replied.
+    pub fn last_sent_message_id(&self) -> Option<i64> {
+        *self.last_sent_message_id.lock().unwrap()
+    }
+
+    pub(crate) fn with_permissions(mut self, permissions: PermissionsCache) -> Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Returns the bot's own membership in the chat, including its administrator rights,
+    /// e.g., to check if it can delete messages before trying:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// async fn moderate(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     if e.my_permissions().await?.can_delete_messages() {
+    ///         Ok(Action::Delete)
+    ///     } else {
+    ///         Ok(Action::ReplyText("Make me an admin to delete spam".into()))
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Events dispatched by a router share a per-chat cache, so this only calls
+    /// `getChatMember` once per [`PERMISSIONS_TTL`] (see
+    /// [`Router::with_permissions_ttl`](crate::Router::with_permissions_ttl)). The cache is
+    /// also refreshed when the bot's status in the chat changes, if the router receives
+    /// `my_chat_member` updates.
+    pub async fn my_permissions(&self) -> anyhow::Result<api::ChatMember> {
+        let chat_id = self.update.chat_id()?;
+        let cache = self.permissions.as_ref();
+        if let Some(member) = cache.and_then(|c| c.get(self.account, chat_id)) {
+            return Ok(member);
+        }
+
+        let cached_me = cache.and_then(|c| c.me.lock().unwrap().get(&self.account).copied());
+        let me = match cached_me {
+            Some(me) => me,
+            None => {
+                let me = self.api.get_me().await?.id;
+                if let Some(cache) = cache {
+                    cache.me.lock().unwrap().insert(self.account, me);
+                }
+                me
+            }
+        };
+
+        let member = self
+            .api
+            .get_chat_member(&api::GetChatMemberRequest::new(chat_id, me))
+            .await?;
+        if let Some(cache) = cache {
+            cache.insert(self.account, chat_id, member.clone());
+        }
+        Ok(member)
+    }
+
+    pub(crate) fn with_prompts(mut self, prompts: PendingPrompts) -> Self {
+        self.prompts = Some(prompts);
+        self
+    }
+
+    pub(crate) fn with_spawner(mut self, spawner: Spawner) -> Self {
+        self.spawner = Some(spawner);
+        self
+    }
+
+    /// Run `job` in the background, so the handler can return (and release the chat's
+    /// state) right away. If the job fails, the error is passed to the router's error
+    /// handler along with this chat's ID, just like handler errors.
+    ///
+    /// Background jobs are cancelled when the router shuts down.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// async fn handle(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     let event = e.clone();
+    ///     e.spawn(async move {
+    ///         tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    ///         event.send_message("Done!").await?;
+    ///         Ok(())
+    ///     });
+    ///
+    ///     Ok(Action::ReplyText("Working on it...".into()))
+    /// }
+    /// ```
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        match self.spawner {
+            Some(ref spawner) => spawner(Box::pin(job)),
+            None => {
+                tokio::spawn(async move {
+                    if let Err(err) = job.await {
+                        error!("Error in background job: {}", err);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Attach a localizer to this event, see [`Event::tr`].
+    pub fn with_localizer(mut self, localizer: Localizer) -> Self {
+        self.localizer = Some(localizer);
+        self
+    }
+
+    /// Localize the message `key` into the sender's language (from `from.language_code`),
+    /// using the localizer installed with [`Router::with_localizer`](crate::Router::with_localizer).
+    ///
+    /// If the sender's language is unknown, [`DEFAULT_LANGUAGE`] is used. If no localizer is
+    /// installed, the key is returned as is.
+    pub fn tr(&self, key: &str) -> String {
+        let Some(ref localizer) = self.localizer else {
+            return key.to_string();
+        };
+
+        let language = self
+            .update
+            .from_user()
+            .ok()
+            .and_then(|user| user.language_code.as_deref())
+            .unwrap_or(DEFAULT_LANGUAGE);
+
+        localizer(language, key)
+    }
+
+    /// Attach a map of additional per-chat states to this event.
+    pub fn with_states(mut self, states: StateMap) -> Self {
+        self.states = states;
+        self
+    }
+
+    /// Return this chat's state of type `T`, creating it (with `Default`) if it doesn't
+    /// exist. This is independent of the router's `State<S>` passed to the handler, and lets
+    /// unrelated features keep separate state types:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// #[derive(Clone, Default, BotState)]
+    /// struct Counter {
+    ///     count: usize,
+    /// }
+    ///
+    /// async fn handle_count(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     let counter = e.state::<Counter>();
+    ///     let mut counter = counter.get().write().await;
+    ///     counter.count += 1;
+    ///     Ok(Action::ReplyText(format!("count: {}", counter.count)))
+    /// }
+    /// ```
+    pub fn state<T: BotState>(&self) -> State<T> {
+        self.states.get::<T>()
+    }
+
+    /// Returns the chat the message was sent on behalf of, if any. This is set for
+    /// messages from anonymous group administrators and linked channels, which have no
+    /// `from` user.
+    pub fn sender_chat(&self) -> anyhow::Result<&api::Chat> {
+        self.update.sender_chat()
+    }
+
+    /// Returns where a forwarded message originally came from, e.g., to tell content
+    /// forwarded from a channel apart from content forwarded from a user. Fails if the
+    /// message wasn't forwarded.
+    pub fn forward_origin(&self) -> anyhow::Result<api::MessageOrigin> {
+        self.update.forward_origin()
+    }
+
+    /// Returns the payment confirmed by this message, e.g., to read the invoice payload and
+    /// the `telegram_payment_charge_id` when fulfilling an order. Fails if the message
+    /// isn't a `successful_payment` service message, see
+    /// [`Matcher::SuccessfulPayment`](crate::Matcher::SuccessfulPayment).
+    pub fn successful_payment(&self) -> anyhow::Result<&api::SuccessfulPayment> {
+        self.update.successful_payment()
+    }
+
+    /// Returns the payment refunded by this message, e.g., to revoke what was granted for
+    /// it. Fails if the message isn't a `refunded_payment` service message.
+    pub fn refunded_payment(&self) -> anyhow::Result<&api::RefundedPayment> {
+        self.update.refunded_payment()
+    }
+
+    /// Returns the arguments of the bot command in this message, splitting on whitespace
+    /// but keeping double-quoted strings together. For example, `/add "hello world" foo`
+    /// yields `["hello world", "foo"]`. Returns an empty list for messages without text.
+    pub fn command_args(&self) -> Vec<String> {
+        self.update.command_args().unwrap_or_default()
     }
 
     /// Acknowledge a callback query.
@@ -21,13 +454,49 @@ impl Event {
         let query_id = self.update.query_id()?.to_string();
 
         let mut req = api::AnswerCallbackQueryRequest::new(query_id);
-        if text.is_some() {
-            req = req.with_text(text.unwrap());
+        if let Some(text) = text {
+            req = req.with_text(text);
         }
 
         self.api.answer_callback_query(&req).await
     }
 
+    /// Returns the inline query this event is for. Inline queries aren't sent in a chat, so
+    /// use [`Event::answer`] to respond to them instead of sending messages.
+    pub fn inline_query(&self) -> anyhow::Result<&api::InlineQuery> {
+        self.update.get_inline_query()
+    }
+
+    /// Answer the inline query this event is for with `results`. To set other options
+    /// (e.g., caching or paging), build an [`api::AnswerInlineQuery`] and send it with
+    /// `e.api.answer_inline_query`.
+    pub async fn answer(
+        &self,
+        results: Vec<api::InlineQueryResultArticle>,
+    ) -> anyhow::Result<bool> {
+        let query_id = self.inline_query()?.id.clone();
+        self.api
+            .answer_inline_query(&api::AnswerInlineQuery {
+                results,
+                ..api::AnswerInlineQuery::new(query_id)
+            })
+            .await
+    }
+
+    /// Answer the inline query this event is for with no results, and a `button` above
+    /// them, e.g., to explain that nothing was found or to set up the bot. Queries should
+    /// always be answered, even with no results; otherwise, Telegram keeps showing the
+    /// results of an earlier query.
+    pub async fn answer_inline_empty(
+        &self,
+        button: api::InlineQueryResultsButton,
+    ) -> anyhow::Result<bool> {
+        let query_id = self.inline_query()?.id.clone();
+        self.api
+            .answer_inline_query(&api::AnswerInlineQuery::new(query_id).with_button(button))
+            .await
+    }
+
     /// Remove the inline keyboard from a message.
     pub async fn remove_inline_keyboard(&self) -> anyhow::Result<api::Message> {
         let chat_id = self.update.chat_id()?;
@@ -44,6 +513,20 @@ impl Event {
             .await
     }
 
+    /// Remove the inline keyboard from a message sent via the bot in inline mode. Use this
+    /// for callback queries that have an `inline_message_id` instead of a `message`.
+    pub async fn remove_inline_message_keyboard(&self) -> anyhow::Result<bool> {
+        let inline_message_id = self.update.inline_message_id()?;
+
+        self.api
+            .edit_inline_message_reply_markup(&api::EditMessageReplyMarkupRequest {
+                base: api::EditMessageBase::new()
+                    .with_inline_message_id(inline_message_id)
+                    .with_reply_markup(api::ReplyMarkup::inline_keyboard_markup(vec![vec![]])),
+            })
+            .await
+    }
+
     /// Send a chat action.
     pub async fn send_chat_action(&self, action: api::ChatAction) -> anyhow::Result<bool> {
         self.api
@@ -54,18 +537,227 @@ impl Event {
             .await
     }
 
+    /// Show "typing..." in the chat until the returned guard is dropped. Telegram clears
+    /// chat actions after 5 seconds, so the action is resent every
+    /// [`TYPING_REFRESH_INTERVAL`] (4 seconds). Since the guard stops the action when it
+    /// goes out of scope, early returns and errors in the handler are handled too:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # async fn slow_lookup() -> anyhow::Result<String> { Ok("".into()) }
+    /// async fn handler(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     let _typing = e.keep_typing();
+    ///     let answer = slow_lookup().await?;
+    ///     Ok(Action::ReplyText(answer))
+    /// }
+    /// ```
+    ///
+    /// Note that `let _ = e.keep_typing()` drops the guard (and stops the action) right
+    /// away, so bind it to a named variable.
+    pub fn keep_typing(&self) -> TypingGuard {
+        self.keep_chat_action(api::ChatAction::Typing, TYPING_REFRESH_INTERVAL)
+    }
+
+    /// Send `action` every `interval` until the returned guard is dropped, or sending it
+    /// fails (e.g., the bot was blocked). See [`Event::keep_typing`].
+    pub fn keep_chat_action(&self, action: api::ChatAction, interval: Duration) -> TypingGuard {
+        let event = self.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                if let Err(err) = event.send_chat_action(action.clone()).await {
+                    warn!("Stopped sending chat action {:?}: {}", action, err);
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+
+        TypingGuard(task.abort_handle())
+    }
+
+    /// Leave the chat this event came from.
+    pub async fn leave_chat(&self) -> anyhow::Result<bool> {
+        self.api
+            .leave_chat(&api::LeaveChatRequest::new(self.update.chat_id()?))
+            .await
+    }
+
+    /// Get the number of members in the chat this event came from.
+    pub async fn get_chat_member_count(&self) -> anyhow::Result<i64> {
+        self.api
+            .get_chat_member_count(&api::GetChatMemberCountRequest::new(self.update.chat_id()?))
+            .await
+    }
+
     /// Send a message to the chat.
     pub async fn send_message(&self, text: impl Into<Text>) -> anyhow::Result<api::Message> {
         let text = text.into();
 
         self.api
-            .send_message(
-                &api::SendMessageRequest::new(self.update.chat_id()?, text.clone())
-                    .with_parse_mode(text.into()),
-            )
+            .send_message(&api::SendMessageRequest {
+                parse_mode: Some(text.clone().into()),
+                message_thread_id: self.update.message_thread_id(),
+                business_connection_id: self.update.business_connection_id().map(String::from),
+                ..api::SendMessageRequest::new(self.update.chat_id()?, text)
+            })
+            .await
+    }
+
+    /// Reply to the message that triggered this event, and return the sent message. Unlike
+    /// [`Action::ReplyText`](crate::Action::ReplyText), which is fire-and-forget, this lets
+    /// the handler keep the returned `message_id` around for later edits.
+    ///
+    /// This fails if the message was deleted in the meantime, use [`Event::reply_with`]
+    /// to send the reply anyway.
+    pub async fn reply(&self, text: impl Into<Text>) -> anyhow::Result<api::Message> {
+        self.reply_with(text, api::ReplyParameters::new(self.update.message_id()?))
+            .await
+    }
+
+    /// Reply to a message with the given `reply_parameters`, e.g., to quote part of the
+    /// message, or to send the reply even if the message was deleted:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// async fn handle(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     let reply_parameters = api::ReplyParameters::new(e.update.message_id()?)
+    ///         .with_allow_sending_without_reply(true);
+    ///     e.reply_with("Got it!", reply_parameters).await?;
+    ///     Ok(Action::Done)
+    /// }
+    /// ```
+    pub async fn reply_with(
+        &self,
+        text: impl Into<Text>,
+        reply_parameters: api::ReplyParameters,
+    ) -> anyhow::Result<api::Message> {
+        let text = text.into();
+
+        self.api
+            .send_message(&api::SendMessageRequest {
+                parse_mode: Some(text.clone().into()),
+                message_thread_id: self.update.message_thread_id(),
+                business_connection_id: self.update.business_connection_id().map(String::from),
+                ..api::SendMessageRequest::new(self.update.chat_id()?, text)
+                    .with_reply_parameters(reply_parameters)
+            })
             .await
     }
 
+    /// Reply to the message that triggered this event with `text` escaped for MarkdownV2
+    /// (see [`Text::escape_markdown_v2`]), so user content renders literally instead of
+    /// being rejected by Telegram for unescaped characters like `.` or `!`. Unlike passing
+    /// [`Text::Markdown`] to [`Event::reply`], which treats the input as pre-formatted
+    /// markdown, this never interprets `text` as markup.
+    pub async fn reply_markdown_escaped(&self, text: &str) -> anyhow::Result<api::Message> {
+        self.reply(Text::escape_markdown_v2(text)).await
+    }
+
+    /// Send a message to the chat, and delete it after `ttl`. Returns the sent message.
+    /// Useful for status messages or one-time codes.
+    ///
+    /// The deletion runs as a background job (see [`Event::spawn`]), so it's cancelled if
+    /// the router shuts down first. If the deletion fails (e.g., the message was already
+    /// deleted), the error is logged.
+    pub async fn send_ephemeral(
+        &self,
+        text: impl Into<Text>,
+        ttl: Duration,
+    ) -> anyhow::Result<api::Message> {
+        let message = self.send_message(text).await?;
+
+        let api = Arc::clone(&self.api);
+        let (chat_id, message_id) = (message.chat.id, message.message_id);
+        self.spawn(async move {
+            tokio::time::sleep(ttl).await;
+            if let Err(err) = api
+                .delete_message(&api::DeleteMessageRequest::new(chat_id, message_id))
+                .await
+            {
+                warn!(
+                    "Error deleting ephemeral message {} in chat {}: {}",
+                    message_id, chat_id, err
+                );
+            }
+            Ok(())
+        });
+
+        Ok(message)
+    }
+
+    /// Send `prompt` to the chat with a force-reply keyboard, so the user's client opens a
+    /// reply to it, and return the prompt's `message_id`.
+    ///
+    /// The router keeps the prompt pending under `name` until it's answered, and routes the
+    /// answer to the handlers for [`Matcher::ReplyTo`](crate::Matcher::ReplyTo) with the
+    /// same name. Asking again with the same name replaces the earlier prompt. Prompts
+    /// expire after [`PROMPT_TTL`] (see
+    /// [`Router::with_prompt_ttl`](crate::Router::with_prompt_ttl)), after which answers
+    /// to them are routed like any other message.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # fn setup(router: &mut Router<()>) {
+    /// router
+    ///     .add_route(
+    ///         Route::Message(Matcher::BotCommand("name".into())),
+    ///         |e: Event, _| async move {
+    ///             e.ask("name", "What's your name?").await?;
+    ///             Ok(Action::Done)
+    ///         },
+    ///     )
+    ///     .add_route(
+    ///         Route::Message(Matcher::ReplyTo("name".into())),
+    ///         |e: Event, _| async move {
+    ///             Ok(Action::ReplyText(format!("Hello, {}!", e.update.text()?)))
+    ///         },
+    ///     );
+    /// # }
+    /// ```
+    pub async fn ask(
+        &self,
+        name: impl Into<String>,
+        prompt: impl Into<Text>,
+    ) -> anyhow::Result<i64> {
+        let text = prompt.into();
+
+        let message = self
+            .api
+            .send_message(&api::SendMessageRequest {
+                parse_mode: Some(text.clone().into()),
+                message_thread_id: self.update.message_thread_id(),
+                business_connection_id: self.update.business_connection_id().map(String::from),
+                ..api::SendMessageRequest::new(self.update.chat_id()?, text)
+                    .with_reply_markup(api::ReplyMarkup::force_reply())
+            })
+            .await?;
+
+        if let Some(ref prompts) = self.prompts {
+            prompts.insert(
+                self.account,
+                message.chat.id,
+                name.into(),
+                message.message_id,
+            );
+        }
+        Ok(message.message_id)
+    }
+
+    /// Send a message to the chat and pin it, and return the sent message. Useful for
+    /// announcements. The bot must be an administrator in groups and channels.
+    pub async fn send_and_pin(&self, text: impl Into<Text>) -> anyhow::Result<api::Message> {
+        let message = self.send_message(text).await?;
+
+        self.api
+            .pin_chat_message(&api::PinChatMessageRequest::new(
+                message.chat.id,
+                message.message_id,
+            ))
+            .await?;
+
+        Ok(message)
+    }
+
     /// Edit the message with the given text (uses the parsemode of the message)
     pub async fn edit_last_message(&self, text: impl Into<String>) -> anyhow::Result<api::Message> {
         self.edit_message(self.update.message_id()?, text).await
@@ -89,6 +781,17 @@ impl Event {
             .await
     }
 
+    /// Edit the inline message (sent via the bot in inline mode) that originated this
+    /// callback query.
+    pub async fn edit_inline_message(&self, text: impl Into<String>) -> anyhow::Result<bool> {
+        self.api
+            .edit_inline_message_text(
+                &api::EditMessageTextRequest::new(text.into())
+                    .with_inline_message_id(self.update.inline_message_id()?),
+            )
+            .await
+    }
+
     // Delete the last message
     pub async fn delete_last_message(&self) -> anyhow::Result<bool> {
         let chat_id = self.update.chat_id()?;
@@ -115,19 +818,32 @@ impl Event {
     ) -> anyhow::Result<api::Message> {
         let text = text.into();
         let chat_id = self.update.chat_id()?;
+        let buttons = menu
+            .iter()
+            .map(|item| api::InlineKeyboardButton::from(item).try_with_callback_data(item))
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         self.api
             .send_message(
                 &api::SendMessageRequest::new(chat_id, text.clone())
                     .with_parse_mode(text.into())
-                    .with_reply_markup(api::ReplyMarkup::inline_keyboard_markup(vec![menu
-                        .iter()
-                        .map(|item| api::InlineKeyboardButton::from(item).with_callback_data(item))
-                        .collect()])),
+                    .with_reply_markup(api::ReplyMarkup::inline_keyboard_markup(vec![buttons])),
             )
             .await
     }
 
+    /// Download the file with the given `file_id`, e.g., of a document or photo sent to the
+    /// bot. See [`API::download_by_file_id`].
+    pub async fn download(&self, file_id: &str) -> anyhow::Result<bytes::Bytes> {
+        self.api.download_by_file_id(file_id).await
+    }
+
+    /// Download the photo in this event's message, in the biggest size available.
+    pub async fn download_largest_photo(&self) -> anyhow::Result<bytes::Bytes> {
+        let file_id = self.update.largest_photo()?.file_id.clone();
+        self.download(&file_id).await
+    }
+
     /// Send a sticker to the chat.
     pub async fn send_sticker(&self, sticker: impl Into<String>) -> anyhow::Result<api::Message> {
         self.api
@@ -5,7 +5,11 @@ use anyhow::Result;
 use async_trait::async_trait;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::{mpsc, Mutex};
 
 use crate::{
@@ -14,6 +18,16 @@ use crate::{
     Update,
 };
 
+/// How long [`FakeChat::expect_text`] and [`FakeChat::collect`] wait for each update
+/// from the bot before failing.
+pub const DEFAULT_RECV_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The (scope, language code) a command list is set for, see [`FakeAPI::commands`].
+type CommandsKey = (String, String);
+
+/// The API calls made by the bot, as (method, request), see [`FakeAPI::call_log`].
+type CallLog = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
 /// `FakeChat` represents a chat session between a user and a mobot bot. It
 /// represents the user side of the chat, and provides methods for sending
 /// and receiving events as if a user did.
@@ -98,6 +112,45 @@ impl FakeChat {
         let mut rx = self.chat_rx.lock().await;
         rx.recv().await
     }
+
+    /// Wait for an update from the bot, and panic if it doesn't arrive within
+    /// [`DEFAULT_RECV_TIMEOUT`].
+    async fn recv_update_or_panic(&self) -> Update {
+        match tokio::time::timeout(DEFAULT_RECV_TIMEOUT, self.recv_update()).await {
+            Ok(Some(update)) => update,
+            Ok(None) => panic!("chat {}: channel closed", self.chat_id),
+            Err(_) => panic!(
+                "chat {}: no update from bot within {:?}",
+                self.chat_id, DEFAULT_RECV_TIMEOUT
+            ),
+        }
+    }
+
+    /// Wait for the next update from the bot, and assert that its text is `text`. Panics
+    /// if no update arrives within [`DEFAULT_RECV_TIMEOUT`]. Returns the update, so tests
+    /// can make further assertions on it.
+    pub async fn expect_text(&self, text: impl AsRef<str>) -> Update {
+        let update = self.recv_update_or_panic().await;
+        assert_eq!(update.to_string(), text.as_ref());
+        update
+    }
+
+    /// Assert that the bot sends nothing to this chat for `duration`.
+    pub async fn expect_none(&self, duration: Duration) {
+        if let Ok(Some(update)) = tokio::time::timeout(duration, self.recv_update()).await {
+            panic!("chat {}: expected no update, got: {}", self.chat_id, update);
+        }
+    }
+
+    /// Wait for the next `n` updates from the bot. Panics if any of them doesn't arrive
+    /// within [`DEFAULT_RECV_TIMEOUT`].
+    pub async fn collect(&self, n: usize) -> Vec<Update> {
+        let mut updates = Vec::with_capacity(n);
+        for _ in 0..n {
+            updates.push(self.recv_update_or_panic().await);
+        }
+        updates
+    }
 }
 
 /// `FakeAPI` is a fake Telegram API server. It implements the Telegram API, but instead of
@@ -119,6 +172,53 @@ pub struct FakeAPI {
 
     /// A map of chat IDs to a channel to send messages to.
     pub chat_map: Arc<Mutex<HashMap<i64, Arc<mpsc::Sender<Update>>>>>,
+
+    /// Public chat usernames (e.g., "@mychannel"), mapped to chat IDs. See
+    /// [`FakeAPI::set_chat_username`].
+    usernames: Arc<Mutex<HashMap<String, i64>>>,
+
+    /// Messages sent by users or the bot, keyed by (chat_id, message_id). These can be
+    /// forwarded or deleted by the bot.
+    messages: Arc<Mutex<HashMap<(i64, i64), api::Message>>>,
+
+    /// Queued error responses for each method, see [`FakeAPI::fail_next`].
+    failures: Arc<Mutex<HashMap<String, VecDeque<ApiResponse<()>>>>>,
+
+    /// The bot's command lists, as last set with `setMyCommands`, keyed by (scope, language
+    /// code). Scopes are serialized to JSON.
+    commands: Arc<Mutex<HashMap<CommandsKey, Vec<api::BotCommand>>>>,
+
+    /// The bot's name, description and short description, keyed by (field, language code).
+    profile: Arc<Mutex<HashMap<(&'static str, String), String>>>,
+
+    /// The bot's menu buttons, keyed by chat ID (0 for the default menu button).
+    menu_buttons: Arc<Mutex<HashMap<i64, api::MenuButton>>>,
+
+    /// Users' profile pictures, keyed by user ID, see [`FakeAPI::set_user_profile_photos`].
+    profile_photos: Arc<Mutex<HashMap<i64, Vec<Vec<api::PhotoSize>>>>>,
+
+    /// Chat actions sent by the bot, keyed by chat ID, see [`FakeAPI::chat_actions`].
+    chat_actions: Arc<Mutex<HashMap<i64, Vec<api::ChatAction>>>>,
+
+    /// The bot's webhook URL, see [`FakeAPI::set_webhook`].
+    webhook_url: Arc<Mutex<Option<String>>>,
+
+    /// The bot's answers to callback queries, keyed by query ID, see
+    /// [`FakeAPI::callback_answer`].
+    callback_answers: Arc<Mutex<HashMap<String, api::AnswerCallbackQueryRequest>>>,
+
+    /// Chat members set with [`FakeAPI::set_chat_member`], keyed by (chat_id, user_id).
+    chat_members: Arc<Mutex<HashMap<(i64, i64), api::ChatMember>>>,
+
+    /// The bot's answers to pre-checkout queries, keyed by query ID, see
+    /// [`FakeAPI::pre_checkout_answer`].
+    pre_checkout_answers: Arc<Mutex<HashMap<String, api::AnswerPreCheckoutQueryRequest>>>,
+
+    /// Sticker sets created by the bot, keyed by name.
+    sticker_sets: Arc<Mutex<HashMap<String, api::StickerSet>>>,
+
+    /// The API calls made by the bot, if enabled with [`FakeAPI::with_call_log`].
+    call_log: Option<CallLog>,
 }
 
 impl Default for FakeAPI {
@@ -138,9 +238,220 @@ impl FakeAPI {
             chat_tx: Arc::new(tx),
             chat_rx: Arc::new(Mutex::new(rx)),
             chat_map: Arc::new(Mutex::new(HashMap::new())),
+            usernames: Arc::new(Mutex::new(HashMap::new())),
+            messages: Arc::new(Mutex::new(HashMap::new())),
+            failures: Arc::new(Mutex::new(HashMap::new())),
+            commands: Arc::new(Mutex::new(HashMap::new())),
+            profile: Arc::new(Mutex::new(HashMap::new())),
+            menu_buttons: Arc::new(Mutex::new(HashMap::new())),
+            profile_photos: Arc::new(Mutex::new(HashMap::new())),
+            chat_actions: Arc::new(Mutex::new(HashMap::new())),
+            webhook_url: Arc::new(Mutex::new(None)),
+            pre_checkout_answers: Arc::new(Mutex::new(HashMap::new())),
+            chat_members: Arc::new(Mutex::new(HashMap::new())),
+            callback_answers: Arc::new(Mutex::new(HashMap::new())),
+            sticker_sets: Arc::new(Mutex::new(HashMap::new())),
+            call_log: None,
         }
     }
 
+    /// Record the API calls made by the bot, so tests can assert the exact sequence of
+    /// calls with [`FakeAPI::call_log`]. Polling for updates (`getUpdates`) isn't recorded.
+    pub fn with_call_log(mut self) -> Self {
+        self.call_log = Some(Arc::new(Mutex::new(vec![])));
+        self
+    }
+
+    /// Returns the API calls made by the bot so far, in order, as (method, request). Calls
+    /// that failed (e.g., with [`FakeAPI::fail_next`]) are included.
+    ///
+    /// ```
+    /// # use mobot::fake::FakeAPI;
+    /// # async fn test() {
+    /// let fakeserver = FakeAPI::new().with_call_log();
+    /// // ... run the bot ...
+    /// let methods: Vec<_> = fakeserver
+    ///     .call_log()
+    ///     .await
+    ///     .into_iter()
+    ///     .map(|(method, _)| method)
+    ///     .collect();
+    /// assert_eq!(methods, ["sendChatAction", "sendMessage"]);
+    /// # }
+    /// ```
+    ///
+    /// Panics if the call log wasn't enabled with [`FakeAPI::with_call_log`].
+    pub async fn call_log(&self) -> Vec<(String, serde_json::Value)> {
+        self.call_log
+            .as_ref()
+            .expect("call log not enabled, use FakeAPI::with_call_log")
+            .lock()
+            .await
+            .clone()
+    }
+
+    /// Returns the message sent by a user (or the bot) with the given chat and message ID,
+    /// if it exists (and wasn't deleted by the bot).
+    pub async fn get_message(&self, chat_id: i64, message_id: i64) -> Option<api::Message> {
+        self.messages
+            .lock()
+            .await
+            .get(&(chat_id, message_id))
+            .cloned()
+    }
+
+    /// Returns the bot's command list for the default scope and all languages, as last set
+    /// with `setMyCommands`.
+    pub async fn commands(&self) -> Vec<api::BotCommand> {
+        self.commands
+            .lock()
+            .await
+            .get(&Self::commands_key(None, None))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the key for the command list of a scope and language. Like Telegram, a
+    /// missing scope means the default scope.
+    fn commands_key(
+        scope: Option<api::BotCommandScope>,
+        language_code: Option<String>,
+    ) -> CommandsKey {
+        let scope =
+            scope.unwrap_or_else(|| api::BotCommandScope::new(api::BotCommnandScopeType::Default));
+        (
+            serde_json::to_string(&scope).unwrap(),
+            language_code.unwrap_or_default(),
+        )
+    }
+
+    /// Returns the chat actions (e.g., typing) sent by the bot to the chat, in order.
+    pub async fn chat_actions(&self, chat_id: i64) -> Vec<api::ChatAction> {
+        self.chat_actions
+            .lock()
+            .await
+            .get(&chat_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Set a webhook, as if the bot had called `setWebhook`. Like Telegram, `getUpdates`
+    /// fails until the webhook is deleted with `deleteWebhook`.
+    pub async fn set_webhook(&self, url: impl Into<String>) {
+        *self.webhook_url.lock().await = Some(url.into());
+    }
+
+    /// Returns the bot's answer to the pre-checkout query with the given ID, if it was
+    /// answered.
+    pub async fn pre_checkout_answer(
+        &self,
+        query_id: &str,
+    ) -> Option<api::AnswerPreCheckoutQueryRequest> {
+        self.pre_checkout_answers
+            .lock()
+            .await
+            .get(query_id)
+            .cloned()
+    }
+
+    /// Returns the bot's answer to the callback query with the given ID, if it was
+    /// answered.
+    pub async fn callback_answer(&self, query_id: &str) -> Option<api::AnswerCallbackQueryRequest> {
+        self.callback_answers.lock().await.get(query_id).cloned()
+    }
+
+    /// Returns the bot's user, as returned by `getMe`.
+    pub fn me(&self) -> api::User {
+        self.bot_name.as_str().into()
+    }
+
+    /// Set the member returned by `getChatMember` for `member.user` in `chat_id`, e.g., to
+    /// make the bot (see [`FakeAPI::me`]) an administrator. Other users of fake chats are
+    /// plain members.
+    pub async fn set_chat_member(&self, chat_id: i64, member: api::ChatMember) {
+        self.chat_members
+            .lock()
+            .await
+            .insert((chat_id, member.user.id), member);
+    }
+
+    /// Set the profile pictures returned by `getUserProfilePhotos` for `user_id`.
+    pub async fn set_user_profile_photos(&self, user_id: i64, photos: Vec<Vec<api::PhotoSize>>) {
+        self.profile_photos.lock().await.insert(user_id, photos);
+    }
+
+    /// Returns true if there's a fake chat with the given ID. Fake chats can only be
+    /// addressed by their numeric ID.
+    async fn has_chat(&self, chat_id: &api::ChatId) -> bool {
+        let chat_id = self.resolve(chat_id).await;
+        let chat_map = self.chat_map.lock().await;
+        chat_id.is_some_and(|id| chat_map.contains_key(&id))
+    }
+
+    /// Returns the numeric ID of the chat, looking up usernames set with
+    /// [`FakeAPI::set_chat_username`].
+    async fn resolve(&self, chat_id: &api::ChatId) -> Option<i64> {
+        match chat_id {
+            api::ChatId::Id(id) => Some(*id),
+            api::ChatId::Username(username) => self.usernames.lock().await.get(username).copied(),
+        }
+    }
+
+    /// Returns the message that a message sent to `chat_id` with `reply` replies to. Fails
+    /// like Telegram does if the message doesn't exist, unless sending without a reply is
+    /// allowed.
+    async fn reply_to(
+        &self,
+        chat_id: i64,
+        reply: Option<api::ReplyParameters>,
+    ) -> Result<Option<Box<api::Message>>, &'static str> {
+        let Some(reply) = reply else {
+            return Ok(None);
+        };
+
+        let original = match reply.chat_id {
+            Some(ref chat_id) => match self.resolve(chat_id).await {
+                Some(reply_chat_id) => self.get_message(reply_chat_id, reply.message_id).await,
+                None => None,
+            },
+            None => self.get_message(chat_id, reply.message_id).await,
+        };
+        match original {
+            Some(original) => Ok(Some(Box::new(original))),
+            None if reply.allow_sending_without_reply == Some(true) => Ok(None),
+            None => Err("Bad Request: message to be replied not found"),
+        }
+    }
+
+    /// Give the chat a public username (e.g., "@mychannel"), so the bot can address it by
+    /// username instead of its numeric ID.
+    pub async fn set_chat_username(&self, chat_id: i64, username: impl Into<String>) {
+        self.usernames.lock().await.insert(username.into(), chat_id);
+    }
+
+    /// Make the next call to `method` (e.g., "sendMessage") fail with `response`, instead
+    /// of being handled. Calls queue up, so calling this twice fails the next two calls.
+    ///
+    /// This is useful for testing error handling paths, e.g., a user blocking the bot:
+    ///
+    /// ```
+    /// # use mobot::{api::ApiResponse, fake::FakeAPI};
+    /// # async fn test() {
+    /// let fakeserver = FakeAPI::new();
+    /// fakeserver
+    ///     .fail_next("sendMessage", ApiResponse::Err("Forbidden: bot was blocked by the user"))
+    ///     .await;
+    /// # }
+    /// ```
+    pub async fn fail_next(&self, method: impl Into<String>, response: ApiResponse<()>) {
+        self.failures
+            .lock()
+            .await
+            .entry(method.into())
+            .or_default()
+            .push_back(response);
+    }
+
     /// Create a new `FakeChat` object.
     pub async fn create_chat(&self, from: impl Into<String>) -> FakeChat {
         // Create a new Chat ID and channel for this chat session.
@@ -163,6 +474,12 @@ impl FakeAPI {
     /// Wait for an event from the bot and return it as a standard Telegram update. Typically,
     /// this is called by the router in a loop.
     async fn get_updates(&self, req: api::GetUpdatesRequest) -> ApiResponse<Vec<api::Update>> {
+        if self.webhook_url.lock().await.is_some() {
+            return ApiResponse::Err(
+                "Conflict: can't use getUpdates method while webhook is active; use deleteWebhook to delete the webhook first",
+            );
+        }
+
         let update_id = {
             let mut update_id = self.update_id.lock().await;
             *update_id += 1;
@@ -174,6 +491,14 @@ impl FakeAPI {
 
         tokio::select! {
             Some(msg) = rx.recv() => {
+                // Keep track of user messages, so the bot can forward or delete them.
+                if let Ok(m) = msg.get_message_or_post() {
+                    self.messages
+                        .lock()
+                        .await
+                        .insert((m.chat.id, m.message_id), m.clone());
+                }
+
                 // Wrap the message in an `api::Update` and return it back to the caller.
                 match &msg {
                     Update::Message(msg) => {
@@ -190,6 +515,13 @@ impl FakeAPI {
                             ..Default::default()
                         }])
                     }
+                    Update::BusinessMessage(msg) => {
+                        ApiResponse::Ok(vec![api::Update {
+                            update_id,
+                            business_message: Some(msg.clone()),
+                            ..Default::default()
+                        }])
+                    }
                     Update::CallbackQuery(query) => {
                         ApiResponse::Ok(vec![api::Update {
                             update_id,
@@ -197,6 +529,34 @@ impl FakeAPI {
                             ..Default::default()
                         }])
                     }
+                    Update::ChatJoinRequest(req) => {
+                        ApiResponse::Ok(vec![api::Update {
+                            update_id,
+                            chat_join_request: Some(req.clone()),
+                            ..Default::default()
+                        }])
+                    }
+                    Update::MyChatMember(update) => {
+                        ApiResponse::Ok(vec![api::Update {
+                            update_id,
+                            my_chat_member: Some(update.clone()),
+                            ..Default::default()
+                        }])
+                    }
+                    Update::ChatMember(update) => {
+                        ApiResponse::Ok(vec![api::Update {
+                            update_id,
+                            chat_member: Some(update.clone()),
+                            ..Default::default()
+                        }])
+                    }
+                    Update::PreCheckoutQuery(query) => {
+                        ApiResponse::Ok(vec![api::Update {
+                            update_id,
+                            pre_checkout_query: Some(query.clone()),
+                            ..Default::default()
+                        }])
+                    }
                     _ => { unimplemented!() }
                 }
             }
@@ -207,12 +567,27 @@ impl FakeAPI {
     }
 
     async fn send_message(&self, req: api::SendMessageRequest) -> ApiResponse<api::Message> {
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+
         let mut message = api::Message::fake(self.bot_name.as_str());
-        message.chat.id = req.chat_id;
+        message.chat.id = chat_id;
         message.text = Some(req.text);
-        message.reply_to_message = None;
+        message.reply_to_message = match self.reply_to(chat_id, req.reply_parameters).await {
+            Ok(original) => original,
+            Err(err) => return ApiResponse::Err(err),
+        };
+        message.message_thread_id = req.message_thread_id;
+        message.is_topic_message = req.message_thread_id.map(|_| true);
+        message.business_connection_id = req.business_connection_id;
+
+        self.messages
+            .lock()
+            .await
+            .insert((message.chat.id, message.message_id), message.clone());
 
-        if let Some(chat) = self.chat_map.lock().await.get(&req.chat_id) {
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
             chat.send(Update::Message(message.clone())).await.unwrap();
         } else {
             warn!("Can't find Chat with id = {}", req.chat_id);
@@ -225,9 +600,17 @@ impl FakeAPI {
         &self,
         req: api::EditMessageTextRequest,
     ) -> ApiResponse<api::Message> {
+        let chat_id = match req.base.chat_id {
+            Some(ref chat_id) => self.resolve(chat_id).await,
+            None => None,
+        };
+        let (Some(chat_id), Some(message_id)) = (chat_id, req.base.message_id) else {
+            return ApiResponse::Err("Bad Request: chat_id and message_id are required");
+        };
+
         let mut message = api::Message::fake(self.bot_name.as_str());
-        message.chat.id = req.base.chat_id.unwrap();
-        message.message_id = req.base.message_id.unwrap();
+        message.chat.id = chat_id;
+        message.message_id = message_id;
         message.text = Some(req.text);
 
         if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
@@ -245,10 +628,18 @@ impl FakeAPI {
         &self,
         req: api::EditMessageReplyMarkupRequest,
     ) -> ApiResponse<api::Message> {
+        let chat_id = match req.base.chat_id {
+            Some(ref chat_id) => self.resolve(chat_id).await,
+            None => None,
+        };
+        let (Some(chat_id), Some(message_id)) = (chat_id, req.base.message_id) else {
+            return ApiResponse::Err("Bad Request: chat_id and message_id are required");
+        };
+
         let mut message = api::Message::fake(self.bot_name.as_str());
-        message.chat.id = req.base.chat_id.unwrap();
-        message.message_id = req.base.message_id.unwrap();
-        message.reply_markup = Some(req.base.reply_markup.unwrap().into());
+        message.chat.id = chat_id;
+        message.message_id = message_id;
+        message.reply_markup = req.base.reply_markup.map(Into::into);
 
         if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
             chat.send(Update::EditedMessage(message.clone()))
@@ -260,6 +651,562 @@ impl FakeAPI {
 
         ApiResponse::Ok(message)
     }
+
+    async fn send_animation(&self, req: api::SendAnimationRequest) -> ApiResponse<api::Message> {
+        let mut message = api::Message::fake(self.bot_name.as_str());
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        message.chat.id = chat_id;
+        message.reply_to_message = match self.reply_to(chat_id, req.reply_parameters).await {
+            Ok(original) => original,
+            Err(err) => return ApiResponse::Err(err),
+        };
+        message.caption = req.caption;
+        message.animation = Some(api::Animation {
+            file_id: serde_json::to_value(&req.animation)
+                .ok()
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default(),
+            ..Default::default()
+        });
+
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
+            chat.send(Update::Message(message.clone())).await.unwrap();
+        } else {
+            warn!("Can't find Chat with id = {}", req.chat_id);
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    async fn send_location(&self, req: api::SendLocationRequest) -> ApiResponse<api::Message> {
+        let mut message = api::Message::fake(self.bot_name.as_str());
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        message.chat.id = chat_id;
+        message.reply_to_message = match self.reply_to(chat_id, req.reply_parameters).await {
+            Ok(original) => original,
+            Err(err) => return ApiResponse::Err(err),
+        };
+        message.location = Some(api::Location {
+            latitude: req.latitude,
+            longitude: req.longitude,
+            horizontal_accuracy: req.horizontal_accuracy,
+            live_period: req.live_period,
+            heading: req.heading,
+            proximity_alert_radius: req.proximity_alert_radius,
+        });
+
+        // Keep track of the message, so live locations can be edited.
+        self.messages
+            .lock()
+            .await
+            .insert((message.chat.id, message.message_id), message.clone());
+
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
+            chat.send(Update::Message(message.clone())).await.unwrap();
+        } else {
+            warn!("Can't find Chat with id = {}", req.chat_id);
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    /// Update the location of a live location message sent by the bot. If `req` is `None`,
+    /// the live location is stopped.
+    async fn edit_live_location(
+        &self,
+        base: api::EditMessageBase,
+        req: Option<api::EditMessageLiveLocationRequest>,
+    ) -> ApiResponse<api::Message> {
+        let chat_id = match base.chat_id {
+            Some(ref chat_id) => self.resolve(chat_id).await,
+            None => None,
+        };
+        let (Some(chat_id), Some(message_id)) = (chat_id, base.message_id) else {
+            return ApiResponse::Err("Bad Request: chat_id and message_id are required");
+        };
+
+        let message = {
+            let mut messages = self.messages.lock().await;
+            let Some(location) = messages
+                .get_mut(&(chat_id, message_id))
+                .and_then(|m| m.location.as_mut())
+                .filter(|l| l.live_period.is_some())
+            else {
+                return ApiResponse::Err("Bad Request: message can't be edited");
+            };
+
+            match req {
+                Some(req) => {
+                    location.latitude = req.latitude;
+                    location.longitude = req.longitude;
+                    location.horizontal_accuracy = req.horizontal_accuracy;
+                    location.heading = req.heading;
+                    location.proximity_alert_radius = req.proximity_alert_radius;
+                }
+                None => location.live_period = None,
+            }
+            messages[&(chat_id, message_id)].clone()
+        };
+
+        if let Some(chat) = self.chat_map.lock().await.get(&chat_id) {
+            chat.send(Update::EditedMessage(message.clone()))
+                .await
+                .unwrap();
+        } else {
+            warn!("Can't find Chat with id = {}", chat_id);
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    async fn send_venue(&self, req: api::SendVenueRequest) -> ApiResponse<api::Message> {
+        let mut message = api::Message::fake(self.bot_name.as_str());
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        message.chat.id = chat_id;
+        message.reply_to_message = match self.reply_to(chat_id, req.reply_parameters).await {
+            Ok(original) => original,
+            Err(err) => return ApiResponse::Err(err),
+        };
+        let location = api::Location {
+            latitude: req.latitude,
+            longitude: req.longitude,
+            ..Default::default()
+        };
+        message.location = Some(location.clone());
+        message.venue = Some(api::Venue {
+            location,
+            title: req.title,
+            address: req.address,
+            foursquare_id: req.foursquare_id,
+            google_place_id: req.google_place_id,
+        });
+
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
+            chat.send(Update::Message(message.clone())).await.unwrap();
+        } else {
+            warn!("Can't find Chat with id = {}", req.chat_id);
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    async fn forward_message(&self, req: api::ForwardMessageRequest) -> ApiResponse<api::Message> {
+        let original = match self.resolve(&req.from_chat_id).await {
+            Some(from_chat_id) => self.get_message(from_chat_id, req.message_id).await,
+            None => None,
+        };
+
+        let Some(original) = original else {
+            return ApiResponse::Err("Bad Request: message to forward not found");
+        };
+
+        let mut message = original.clone();
+        message.message_id = rand::random();
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        message.chat.id = chat_id;
+        message.forward_origin =
+            original
+                .from
+                .clone()
+                .map(|sender_user| api::MessageOrigin::User {
+                    date: original.date,
+                    sender_user,
+                });
+        message.forward_from = original.from;
+        message.forward_date = Some(original.date);
+
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
+            chat.send(Update::Message(message.clone())).await.unwrap();
+        } else {
+            warn!("Can't find Chat with id = {}", req.chat_id);
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    async fn forward_messages(
+        &self,
+        req: api::ForwardMessagesRequest,
+    ) -> ApiResponse<Vec<api::MessageId>> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err("Bad Request: chat not found");
+        }
+
+        // Like Telegram, skip messages that can't be forwarded.
+        let mut ids = vec![];
+        for message_id in req.message_ids {
+            let mut forward = api::ForwardMessageRequest::new(
+                req.chat_id.clone(),
+                req.from_chat_id.clone(),
+                message_id,
+            );
+            forward.message_thread_id = req.message_thread_id;
+            if let Some(message) = self.forward_message(forward).await.result {
+                ids.push(api::MessageId {
+                    message_id: message.message_id,
+                });
+            }
+        }
+
+        ApiResponse::Ok(ids)
+    }
+
+    async fn copy_messages(
+        &self,
+        req: api::CopyMessagesRequest,
+    ) -> ApiResponse<Vec<api::MessageId>> {
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        let Some(from_chat_id) = self.resolve(&req.from_chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+
+        // Copies carry no link to the original, and missing messages are skipped.
+        let mut ids = vec![];
+        for message_id in req.message_ids {
+            let Some(mut message) = self.get_message(from_chat_id, message_id).await else {
+                continue;
+            };
+            message.message_id = rand::random();
+            message.chat.id = chat_id;
+            message.forward_origin = None;
+            message.forward_from = None;
+            message.forward_date = None;
+            message.message_thread_id = req.message_thread_id;
+            if req.remove_caption == Some(true) {
+                message.caption = None;
+                message.caption_entities = None;
+            }
+
+            self.messages
+                .lock()
+                .await
+                .insert((chat_id, message.message_id), message.clone());
+
+            if let Some(chat) = self.chat_map.lock().await.get(&chat_id) {
+                chat.send(Update::Message(message.clone())).await.unwrap();
+            }
+
+            ids.push(api::MessageId {
+                message_id: message.message_id,
+            });
+        }
+
+        ApiResponse::Ok(ids)
+    }
+
+    async fn delete_message(&self, req: api::DeleteMessageRequest) -> ApiResponse<bool> {
+        let deleted = match self.resolve(&req.chat_id).await {
+            Some(chat_id) => self
+                .messages
+                .lock()
+                .await
+                .remove(&(chat_id, req.message_id)),
+            None => None,
+        };
+
+        if deleted.is_none() {
+            return ApiResponse::Err("Bad Request: message to delete not found");
+        }
+
+        ApiResponse::Ok(true)
+    }
+
+    async fn leave_chat(&self, req: api::LeaveChatRequest) -> ApiResponse<bool> {
+        let chat_id = self.resolve(&req.chat_id).await;
+        let mut chat_map = self.chat_map.lock().await;
+        if chat_id.and_then(|id| chat_map.remove(&id)).is_none() {
+            return ApiResponse::Err(format!("Can't find Chat with id = {}", req.chat_id));
+        }
+
+        ApiResponse::Ok(true)
+    }
+
+    async fn get_chat_member(
+        &self,
+        req: api::GetChatMemberRequest,
+    ) -> ApiResponse<api::ChatMember> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err("Bad Request: chat not found");
+        }
+
+        let chat_id = self.resolve(&req.chat_id).await.unwrap_or_default();
+        let member = self
+            .chat_members
+            .lock()
+            .await
+            .get(&(chat_id, req.user_id))
+            .cloned();
+        ApiResponse::Ok(member.unwrap_or_else(|| api::ChatMember {
+            status: "member".into(),
+            user: api::User {
+                id: req.user_id,
+                ..Default::default()
+            },
+            ..Default::default()
+        }))
+    }
+
+    async fn get_chat_member_count(&self, req: api::GetChatMemberCountRequest) -> ApiResponse<i64> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err(format!("Can't find Chat with id = {}", req.chat_id));
+        }
+
+        // Fake chats only have the user and the bot.
+        ApiResponse::Ok(2)
+    }
+
+    async fn pin_chat_message(&self, req: api::PinChatMessageRequest) -> ApiResponse<bool> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err(format!("Can't find Chat with id = {}", req.chat_id));
+        }
+
+        ApiResponse::Ok(true)
+    }
+
+    async fn answer_chat_join_request(
+        &self,
+        req: api::ApproveChatJoinRequestRequest,
+    ) -> ApiResponse<bool> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err(format!("Can't find Chat with id = {}", req.chat_id));
+        }
+
+        ApiResponse::Ok(true)
+    }
+
+    async fn set_my_commands(&self, req: api::SetMyCommandsRequest) -> ApiResponse<bool> {
+        self.commands.lock().await.insert(
+            Self::commands_key(req.scope, req.language_code),
+            req.commands,
+        );
+        ApiResponse::Ok(true)
+    }
+
+    async fn get_my_commands(
+        &self,
+        req: api::GetMyCommandsRequest,
+    ) -> ApiResponse<Vec<api::BotCommand>> {
+        ApiResponse::Ok(
+            self.commands
+                .lock()
+                .await
+                .get(&Self::commands_key(req.scope, req.language_code))
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Set a profile field (e.g., "name") for a language. Empty values remove the field.
+    async fn set_profile(
+        &self,
+        field: &'static str,
+        value: Option<String>,
+        language_code: Option<String>,
+    ) -> ApiResponse<bool> {
+        let key = (field, language_code.unwrap_or_default());
+        let mut profile = self.profile.lock().await;
+        match value {
+            Some(value) if !value.is_empty() => profile.insert(key, value),
+            _ => profile.remove(&key),
+        };
+        ApiResponse::Ok(true)
+    }
+
+    /// Get a profile field for a language, falling back to the default language.
+    async fn get_profile(&self, field: &'static str, language_code: Option<String>) -> String {
+        let profile = self.profile.lock().await;
+        profile
+            .get(&(field, language_code.unwrap_or_default()))
+            .or_else(|| profile.get(&(field, String::new())))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    async fn set_chat_menu_button(&self, req: api::SetChatMenuButtonRequest) -> ApiResponse<bool> {
+        self.menu_buttons.lock().await.insert(
+            req.chat_id.unwrap_or_default(),
+            req.menu_button.unwrap_or_default(),
+        );
+        ApiResponse::Ok(true)
+    }
+
+    async fn send_chat_action(&self, req: api::SendChatActionRequest) -> ApiResponse<bool> {
+        if !self.has_chat(&req.chat_id).await {
+            return ApiResponse::Err("Bad Request: chat not found");
+        }
+
+        self.chat_actions
+            .lock()
+            .await
+            .entry(self.resolve(&req.chat_id).await.unwrap_or_default())
+            .or_default()
+            .push(req.action);
+        ApiResponse::Ok(true)
+    }
+
+    async fn send_invoice(&self, req: api::SendInvoiceRequest) -> ApiResponse<api::Message> {
+        let mut message = api::Message::fake(self.bot_name.as_str());
+        let Some(chat_id) = self.resolve(&req.chat_id).await else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        };
+        message.chat.id = chat_id;
+        message.reply_to_message = match self.reply_to(chat_id, req.reply_parameters).await {
+            Ok(original) => original,
+            Err(err) => return ApiResponse::Err(err),
+        };
+        message.invoice = Some(api::Invoice {
+            title: req.title,
+            description: req.description,
+            start_parameter: req.start_parameter.unwrap_or_default(),
+            currency: req.currency,
+            total_amount: req.prices.iter().map(|p| p.amount).sum(),
+        });
+
+        if let Some(chat) = self.chat_map.lock().await.get(&message.chat.id) {
+            chat.send(Update::Message(message.clone())).await.unwrap();
+        } else {
+            return ApiResponse::Err("Bad Request: chat not found");
+        }
+
+        ApiResponse::Ok(message)
+    }
+
+    fn new_sticker(set_name: &str, sticker: api::InputSticker) -> api::Sticker {
+        let file_id = match sticker.sticker {
+            api::InputFile::Remote(file_id) => file_id,
+            api::InputFile::Upload { file_name, .. } => file_name,
+        };
+        api::Sticker {
+            file_id,
+            width: 512,
+            height: 512,
+            is_animated: sticker.format == api::StickerFormat::Animated,
+            is_video: sticker.format == api::StickerFormat::Video,
+            emoji: sticker.emoji_list.into_iter().next(),
+            set_name: Some(set_name.to_string()),
+            file_size: None,
+        }
+    }
+
+    async fn create_new_sticker_set(
+        &self,
+        req: api::CreateNewStickerSetRequest,
+    ) -> ApiResponse<bool> {
+        let mut sticker_sets = self.sticker_sets.lock().await;
+        if sticker_sets.contains_key(&req.name) {
+            return ApiResponse::Err("Bad Request: sticker set name is already occupied");
+        }
+        if req.stickers.is_empty() {
+            return ApiResponse::Err("Bad Request: there are no stickers in the request");
+        }
+
+        let stickers = req
+            .stickers
+            .into_iter()
+            .map(|sticker| Self::new_sticker(&req.name, sticker))
+            .collect();
+        sticker_sets.insert(
+            req.name.clone(),
+            api::StickerSet {
+                name: req.name,
+                title: req.title,
+                sticker_type: req.sticker_type.unwrap_or_default(),
+                stickers,
+            },
+        );
+        ApiResponse::Ok(true)
+    }
+
+    async fn add_sticker_to_set(&self, req: api::AddStickerToSetRequest) -> ApiResponse<bool> {
+        match self.sticker_sets.lock().await.get_mut(&req.name) {
+            Some(set) => {
+                set.stickers.push(Self::new_sticker(&req.name, req.sticker));
+                ApiResponse::Ok(true)
+            }
+            None => ApiResponse::Err("Bad Request: STICKERSET_INVALID"),
+        }
+    }
+
+    async fn get_sticker_set(
+        &self,
+        req: api::GetStickerSetRequest,
+    ) -> ApiResponse<api::StickerSet> {
+        match self.sticker_sets.lock().await.get(&req.name) {
+            Some(set) => ApiResponse::Ok(set.clone()),
+            None => ApiResponse::Err("Bad Request: STICKERSET_INVALID"),
+        }
+    }
+
+    async fn answer_callback_query(
+        &self,
+        req: api::AnswerCallbackQueryRequest,
+    ) -> ApiResponse<bool> {
+        self.callback_answers
+            .lock()
+            .await
+            .insert(req.callback_query_id.clone(), req);
+        ApiResponse::Ok(true)
+    }
+
+    async fn answer_pre_checkout_query(
+        &self,
+        req: api::AnswerPreCheckoutQueryRequest,
+    ) -> ApiResponse<bool> {
+        if !req.ok && req.error_message.is_none() {
+            return ApiResponse::Err("Bad Request: error_message is required");
+        }
+
+        self.pre_checkout_answers
+            .lock()
+            .await
+            .insert(req.pre_checkout_query_id.clone(), req);
+        ApiResponse::Ok(true)
+    }
+
+    async fn get_user_profile_photos(
+        &self,
+        req: api::GetUserProfilePhotosRequest,
+    ) -> ApiResponse<api::UserProfilePhotos> {
+        let profile_photos = self.profile_photos.lock().await;
+        let photos = profile_photos
+            .get(&req.user_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        ApiResponse::Ok(api::UserProfilePhotos {
+            total_count: photos.len() as i64,
+            photos: photos
+                .iter()
+                .skip(req.offset.unwrap_or_default() as usize)
+                .take(req.limit.unwrap_or(100) as usize)
+                .cloned()
+                .collect(),
+        })
+    }
+
+    async fn get_chat_menu_button(
+        &self,
+        req: api::GetChatMenuButtonRequest,
+    ) -> ApiResponse<api::MenuButton> {
+        let menu_buttons = self.menu_buttons.lock().await;
+        ApiResponse::Ok(
+            menu_buttons
+                .get(&req.chat_id.unwrap_or_default())
+                .or_else(|| menu_buttons.get(&0))
+                .cloned()
+                .unwrap_or_default(),
+        )
+    }
 }
 
 #[async_trait]
@@ -269,6 +1216,35 @@ impl Post for FakeAPI {
         use serde_json::to_string as from_json;
 
         debug!("method = {}, req = {}", method, req);
+
+        if let Some(ref call_log) = self.call_log {
+            if method != "getUpdates" {
+                call_log
+                    .lock()
+                    .await
+                    .push((method.clone(), to_json(req.as_str())?));
+            }
+        }
+
+        let failure = self
+            .failures
+            .lock()
+            .await
+            .get_mut(&method)
+            .and_then(|queue| queue.pop_front());
+        if let Some(response) = failure {
+            return Ok(from_json(&response)?);
+        }
+
+        // Messages sent in inline mode aren't tracked, so edits to them are accepted as is.
+        // Like Telegram, return `true` instead of the edited message.
+        if method.starts_with("editMessage") {
+            let base: api::EditMessageBase = to_json(req.as_str())?;
+            if base.inline_message_id.is_some() {
+                return Ok(from_json(&ApiResponse::Ok(true))?);
+            }
+        }
+
         let response = match method.as_str() {
             "getUpdates" => from_json(&self.get_updates(to_json(req.as_str())?).await),
             "sendMessage" => from_json(&self.send_message(to_json(req.as_str())?).await),
@@ -280,6 +1256,107 @@ impl Post for FakeAPI {
             "editMessageReplyMarkup" => {
                 from_json(&self.edit_message_reply_markup(to_json(req.as_str())?).await)
             }
+            "sendAnimation" => from_json(&self.send_animation(to_json(req.as_str())?).await),
+            "sendLocation" => from_json(&self.send_location(to_json(req.as_str())?).await),
+            "editMessageLiveLocation" => {
+                let req: api::EditMessageLiveLocationRequest = to_json(req.as_str())?;
+                from_json(&self.edit_live_location(req.base.clone(), Some(req)).await)
+            }
+            "stopMessageLiveLocation" => {
+                let req: api::StopMessageLiveLocationRequest = to_json(req.as_str())?;
+                from_json(&self.edit_live_location(req.base, None).await)
+            }
+            "sendVenue" => from_json(&self.send_venue(to_json(req.as_str())?).await),
+            "forwardMessage" => from_json(&self.forward_message(to_json(req.as_str())?).await),
+            "forwardMessages" => from_json(&self.forward_messages(to_json(req.as_str())?).await),
+            "copyMessages" => from_json(&self.copy_messages(to_json(req.as_str())?).await),
+            "deleteMessage" => from_json(&self.delete_message(to_json(req.as_str())?).await),
+            "leaveChat" => from_json(&self.leave_chat(to_json(req.as_str())?).await),
+            "getMe" => from_json(&ApiResponse::Ok(self.me())),
+            "answerCallbackQuery" => {
+                from_json(&self.answer_callback_query(to_json(req.as_str())?).await)
+            }
+            "getChatMember" => from_json(&self.get_chat_member(to_json(req.as_str())?).await),
+            "getChatMemberCount" => {
+                from_json(&self.get_chat_member_count(to_json(req.as_str())?).await)
+            }
+            "pinChatMessage" => from_json(&self.pin_chat_message(to_json(req.as_str())?).await),
+            "sendInvoice" => from_json(&self.send_invoice(to_json(req.as_str())?).await),
+            "createNewStickerSet" => {
+                from_json(&self.create_new_sticker_set(to_json(req.as_str())?).await)
+            }
+            "addStickerToSet" => from_json(&self.add_sticker_to_set(to_json(req.as_str())?).await),
+            "getStickerSet" => from_json(&self.get_sticker_set(to_json(req.as_str())?).await),
+            "answerPreCheckoutQuery" => {
+                from_json(&self.answer_pre_checkout_query(to_json(req.as_str())?).await)
+            }
+            "approveChatJoinRequest" | "declineChatJoinRequest" => {
+                from_json(&self.answer_chat_join_request(to_json(req.as_str())?).await)
+            }
+            "setMyCommands" => from_json(&self.set_my_commands(to_json(req.as_str())?).await),
+            "getMyCommands" => from_json(&self.get_my_commands(to_json(req.as_str())?).await),
+            "setMyName" => {
+                let req: api::SetMyNameRequest = to_json(req.as_str())?;
+                from_json(&self.set_profile("name", req.name, req.language_code).await)
+            }
+            "setMyDescription" => {
+                let req: api::SetMyDescriptionRequest = to_json(req.as_str())?;
+                from_json(
+                    &self
+                        .set_profile("description", req.description, req.language_code)
+                        .await,
+                )
+            }
+            "setMyShortDescription" => {
+                let req: api::SetMyShortDescriptionRequest = to_json(req.as_str())?;
+                from_json(
+                    &self
+                        .set_profile(
+                            "short_description",
+                            req.short_description,
+                            req.language_code,
+                        )
+                        .await,
+                )
+            }
+            "getMyName" => {
+                let req: api::GetMyNameRequest = to_json(req.as_str())?;
+                from_json(&ApiResponse::Ok(api::BotName {
+                    name: self.get_profile("name", req.language_code).await,
+                }))
+            }
+            "getMyDescription" => {
+                let req: api::GetMyDescriptionRequest = to_json(req.as_str())?;
+                from_json(&ApiResponse::Ok(api::BotDescription {
+                    description: self.get_profile("description", req.language_code).await,
+                }))
+            }
+            "getMyShortDescription" => {
+                let req: api::GetMyShortDescriptionRequest = to_json(req.as_str())?;
+                from_json(&ApiResponse::Ok(api::BotShortDescription {
+                    short_description: self
+                        .get_profile("short_description", req.language_code)
+                        .await,
+                }))
+            }
+            "setChatMenuButton" => {
+                from_json(&self.set_chat_menu_button(to_json(req.as_str())?).await)
+            }
+            "getChatMenuButton" => {
+                from_json(&self.get_chat_menu_button(to_json(req.as_str())?).await)
+            }
+            "deleteWebhook" => {
+                *self.webhook_url.lock().await = None;
+                from_json(&ApiResponse::Ok(true))
+            }
+            "getWebhookInfo" => from_json(&ApiResponse::Ok(api::WebhookInfo {
+                url: self.webhook_url.lock().await.clone().unwrap_or_default(),
+                ..Default::default()
+            })),
+            "sendChatAction" => from_json(&self.send_chat_action(to_json(req.as_str())?).await),
+            "getUserProfilePhotos" => {
+                from_json(&self.get_user_profile_photos(to_json(req.as_str())?).await)
+            }
             _ => {
                 warn!("Unknown method: {}", method);
                 from_json(&ApiResponse::<()>::Err(format!(
@@ -1,4 +1,8 @@
-use std::sync::Arc;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use async_trait::async_trait;
 use futures::{future::BoxFuture, Future};
@@ -36,6 +40,62 @@ impl<T: BotState> State<T> {
     }
 }
 
+/// The account and chat ID a [`StateMap`] holds states for.
+type ChatKey = (usize, i64);
+
+/// A type-erased state, see [`StateMap`].
+type AnyState = Box<dyn Any + Send + Sync>;
+
+/// `StateMap` holds additional, type-erased states for a chat, keyed by type. This lets
+/// unrelated features of a bot keep their own state types, instead of merging everything
+/// into the router's single `S`. States are created with `Default` on first access.
+///
+/// Handlers access it via [`Event::state`].
+#[derive(Clone, Default)]
+pub struct StateMap {
+    /// The states of all the chats sharing this map's storage (see [`StateMap::for_chat`]).
+    states: Arc<Mutex<HashMap<(ChatKey, TypeId), AnyState>>>,
+    chat: ChatKey,
+}
+
+impl StateMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the map of the given chat, sharing storage with this one. Nothing is stored
+    /// for the chat until one of its states is accessed, so chats that never use
+    /// [`Event::state`] cost nothing.
+    pub(crate) fn for_chat(&self, account: usize, chat_id: i64) -> Self {
+        Self {
+            states: Arc::clone(&self.states),
+            chat: (account, chat_id),
+        }
+    }
+
+    /// Returns the number of chats with at least one state, among all the chats sharing
+    /// this map's storage.
+    pub(crate) fn chat_count(&self) -> usize {
+        let states = self.states.lock().unwrap();
+        let mut chats: Vec<_> = states.keys().map(|(chat, _)| chat).collect();
+        chats.sort();
+        chats.dedup();
+        chats.len()
+    }
+
+    /// Return the state of type `T`, creating it if it doesn't exist.
+    pub fn get<T: BotState>(&self) -> State<T> {
+        self.states
+            .lock()
+            .unwrap()
+            .entry((self.chat, TypeId::of::<T>()))
+            .or_insert_with(|| Box::new(State::<T>::default()))
+            .downcast_ref::<State<T>>()
+            .expect("StateMap entry has the wrong type")
+            .clone()
+    }
+}
+
 /// BotHandlerFns are async functions that take an `Event` and a `State` and return an `Action`
 #[async_trait]
 pub trait BotHandlerFn<S: BotState>: Send + Sync {
@@ -1,29 +1,65 @@
 use crate::{Action, Event, Update};
 
-/// This handler logs every message received.
+/// This handler logs every update received. It never fails, and always returns
+/// `Action::Next`, so it's safe to install as the first handler on any route.
 pub async fn log_handler<S>(e: Event, _: S) -> Result<Action, anyhow::Error> {
+    let text = e.update.to_string();
+
     match e.update {
         Update::Message(message)
         | Update::EditedMessage(message)
         | Update::ChannelPost(message)
-        | Update::EditedChannelPost(message) => {
+        | Update::EditedChannelPost(message)
+        | Update::BusinessMessage(message) => {
             let chat_id = message.chat.id;
             let from = message.from.unwrap_or_default();
-            let text = message.text.unwrap_or_default();
 
             info!("({}) Message from {}: {}", chat_id, from.first_name, text);
-
-            Ok(Action::Next)
         }
         Update::CallbackQuery(query) => {
-            let chat_id = query.message.unwrap_or_default().chat.id;
             let from = query.from;
-            let data = query.data.unwrap_or_default();
-
-            info!("({}) Callback from {}: {}", chat_id, from.first_name, data);
 
-            Ok(Action::Next)
+            match query.message {
+                Some(message) => info!(
+                    "({}) Callback from {}: {}",
+                    message.chat.id, from.first_name, text
+                ),
+                None => info!(
+                    "(inline:{}) Callback from {}: {}",
+                    query.inline_message_id.unwrap_or_default(),
+                    from.first_name,
+                    text
+                ),
+            }
+        }
+        Update::InlineQuery(query) => {
+            info!(
+                "({}) Inline query from {}: {}",
+                query.from.id, query.from.first_name, text
+            );
+        }
+        Update::ChatJoinRequest(req) => {
+            info!(
+                "({}) Join request from {} ({})",
+                req.chat.id, req.from.first_name, req.from.id
+            );
+        }
+        Update::MyChatMember(update) | Update::ChatMember(update) => {
+            info!(
+                "({}) Member update from {}: {} -> {}",
+                update.chat.id, update.from.first_name, update.old_chat_member.status, text
+            );
+        }
+        Update::PreCheckoutQuery(query) => {
+            info!(
+                "({}) Pre-checkout query: {} {} for {}",
+                query.from.id, query.total_amount, query.currency, text
+            );
+        }
+        Update::Unknown => {
+            info!("Unknown update type");
         }
-        _ => Err(anyhow::anyhow!("Unknown message type")),
     }
+
+    Ok(Action::Next)
 }
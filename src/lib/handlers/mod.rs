@@ -1,7 +1,9 @@
 pub mod auth;
 pub mod done;
 pub mod log;
+pub mod retry;
 
 pub use self::log::log_handler;
 pub use auth::auth_handler;
 pub use done::done_handler;
+pub use retry::{RetryHandler, WithRetry};
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::Future;
+
+use crate::{
+    handler::{BotHandlerFn, BotState, HandlerFn},
+    Action, Event, State,
+};
+
+/// `RetryHandler` re-runs a handler that fails, see [`WithRetry::with_retry`].
+pub struct RetryHandler<S: BotState> {
+    handler: Box<dyn BotHandlerFn<S>>,
+    retries: usize,
+    backoff: Duration,
+}
+
+impl<S: BotState> RetryHandler<S> {
+    pub fn new(handler: Box<dyn BotHandlerFn<S>>, retries: usize, backoff: Duration) -> Self {
+        Self {
+            handler,
+            retries,
+            backoff,
+        }
+    }
+}
+
+#[async_trait]
+impl<S: BotState> BotHandlerFn<S> for RetryHandler<S> {
+    async fn run(&self, event: Event, state: State<S>) -> Result<Action, anyhow::Error> {
+        let mut backoff = self.backoff;
+        for attempt in 1..=self.retries {
+            match self.handler.run(event.clone(), state.clone()).await {
+                Ok(action) => return Ok(action),
+                Err(err) => {
+                    warn!(
+                        "Handler failed (attempt {} of {}), retrying in {:?}: {}",
+                        attempt,
+                        self.retries + 1,
+                        backoff,
+                        err
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+
+        self.handler.run(event, state).await
+    }
+}
+
+/// Adds [`WithRetry::with_retry`] to handler functions and boxed handlers.
+pub trait WithRetry<S: BotState> {
+    /// Wrap the handler so that it's re-run up to `retries` times if it returns an error,
+    /// waiting `backoff` before the first retry, and doubling the wait after each one. Only
+    /// the error of the last attempt is passed to the router's error handler.
+    ///
+    /// ```no_run
+    /// # use mobot::{handlers::WithRetry, *};
+    /// # use std::time::Duration;
+    /// async fn fetch_quote(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    ///     // Call a flaky external API...
+    ///     Ok(Action::ReplyText("quote".into()))
+    /// }
+    ///
+    /// # let mut router = Router::<()>::new(Client::new("token".to_string()));
+    /// router.add_route(
+    ///     Route::Message(Matcher::BotCommand("quote".into())),
+    ///     fetch_quote.with_retry(3, Duration::from_millis(500)),
+    /// );
+    /// ```
+    ///
+    /// Every attempt gets the same state, so changes made by a failed attempt are seen by
+    /// the next one. Handlers that are retried should be idempotent, e.g., by only updating
+    /// the state (or sending messages) once everything that can fail has succeeded.
+    fn with_retry(self, retries: usize, backoff: Duration) -> Box<dyn BotHandlerFn<S>>;
+}
+
+impl<S: BotState> WithRetry<S> for Box<dyn BotHandlerFn<S>> {
+    fn with_retry(self, retries: usize, backoff: Duration) -> Box<dyn BotHandlerFn<S>> {
+        Box::new(RetryHandler::new(self, retries, backoff))
+    }
+}
+
+impl<S, Func, Fut> WithRetry<S> for Func
+where
+    S: BotState,
+    Func: Send + Sync + 'static + Fn(Event, State<S>) -> Fut,
+    Fut: Send + 'static + Future<Output = Result<Action, anyhow::Error>>,
+{
+    fn with_retry(self, retries: usize, backoff: Duration) -> Box<dyn BotHandlerFn<S>> {
+        let handler: Box<dyn BotHandlerFn<S>> = Box::new(HandlerFn::new(self));
+        handler.with_retry(retries, backoff)
+    }
+}
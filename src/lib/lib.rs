@@ -9,41 +9,41 @@ around managing routing and state for your bot.
 The key components of the framework are:
 
 - [`Client`] is the main entry point to the Telegram API. It is used to send
- requests to the Telegram API.
+  requests to the Telegram API.
 
 - [`Router`] is the main entry point to the bot. It is used to register
-handlers for different types of events, and keeps track of the state of
-the bot, passing it to the right handler.
+  handlers for different types of events, and keeps track of the state of
+  the bot, passing it to the right handler.
 
 - [`API`] is used to make direct calls to the Telegram API. An instance of `API` is
-passed to all handlers within the [`Event`] argument.
+  passed to all handlers within the [`Event`] argument.
 
 - [`Handler`]s are functions that handle events. They are registered with
-the [`Router`], and are called when an event is received.
+  the [`Router`], and are called when an event is received.
 
-Each [`Handler`] is passed an [`Event`] and a [`State`], and returns an
-[`Action`].
+  Each [`Handler`] is passed an [`Event`] and a [`State`], and returns an
+  [`Action`].
 
 - [`Action`]s are the result of [`Handler`] calls. They are used to send
-responses to the Telegram API.
+  responses to the Telegram API.
 
 - [`Event`]s are the events that the bot receives. They are passed to
-[`Handler`]s, and can be used to determine what action to take.
+  [`Handler`]s, and can be used to determine what action to take.
 
 - [`State`] is the user-defined state of the bot. It is passed to `Handler`s, as
-a generic parameter and can be used to store information about the bot. `State`
-must implement the [`Default`] and [`Clone`] traits. [`Default`] is used to
-initialize the state of a new chat session, and [`Clone`] is used while passing
-the state to the handlers. `State`s are typically wrapped in an [`std::sync::Arc`], so
-that they can be shared between threads.
+  a generic parameter and can be used to store information about the bot. `State`
+  must implement the [`Default`] and [`Clone`] traits. [`Default`] is used to
+  initialize the state of a new chat session, and [`Clone`] is used while passing
+  the state to the handlers. `State`s are typically wrapped in an [`std::sync::Arc`], so
+  that they can be shared between threads.
 
 ## Example
 
 In the example below we create a bot that replies to every message with the
-text "Hello world!".
+text "Hello world!". The [`prelude`] exports the commonly used types.
 
 ```no_run
-use mobot::*;
+use mobot::prelude::*;
 
 #[tokio::main]
 async fn main() {
@@ -119,6 +119,27 @@ router
 # }
 ```
 
+## Multiple state types
+
+Bots with unrelated features don't have to merge all their state into a single type. Use
+[`Event::state`] to get a per-chat state of any type that implements [`BotState`]. These
+states are created on first access, and are independent of the router's `State<S>`.
+
+```no_run
+# use mobot::*;
+#[derive(Clone, Default, BotState)]
+struct Game {
+    score: usize,
+}
+
+async fn handle_game(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+    let game = e.state::<Game>();
+    let mut game = game.get().write().await;
+    game.score += 10;
+    Ok(Action::ReplyText(format!("Score: {}", game.score)))
+}
+```
+
 # Working with routes
 
 [`Route`]s are used to determine which handler should be called for a given event. Every
@@ -229,23 +250,28 @@ pub mod event;
 pub mod fake;
 pub mod handler;
 pub mod handlers;
+pub mod prelude;
 pub mod progress;
+pub mod ratelimit;
 pub mod router;
 pub mod text;
 pub mod update;
+pub mod webhook;
 
-pub use action::Action;
+pub use action::{Action, ErrorAction};
 pub use api::api::*;
 pub use client::{ApiToken, Client};
 pub use event::Event;
-pub use handler::{BotHandler, BotHandlerFn, Handler, State};
+pub use handler::{BotHandler, BotHandlerFn, Handler, State, StateMap};
 pub use progress::ProgressBar;
-pub use router::{Matcher, Route, Router};
-pub use text::Text;
+pub use ratelimit::RateLimiter;
+pub use router::{Matcher, PollHealth, Route, Router, RouterHandle};
+pub use text::{Text, TextBuilder};
 pub use update::Update;
 
 /// Expose mobot_derive macros
 pub use mobot_derive::BotRequest;
+pub use mobot_derive::BotRequestBuilder;
 pub use mobot_derive::BotState;
 
 /// This method initializes [`env_logger`] from the environment, defaulting to `info` level logging.
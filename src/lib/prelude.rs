@@ -0,0 +1,38 @@
+/*!
+The `prelude` exports the types most bots need, without the rest of the crate root (which
+also re-exports every API request and response type).
+
+```no_run
+use mobot::prelude::*;
+
+#[derive(Clone, Default, BotState)]
+struct App {
+    counter: usize,
+}
+
+async fn handle(_: Event, state: State<App>) -> Result<Action, anyhow::Error> {
+    let mut state = state.get().write().await;
+    state.counter += 1;
+    Ok(Action::ReplyText(format!("pong({})", state.counter)))
+}
+
+#[tokio::main]
+async fn main() {
+    let client = Client::new(std::env::var("TELEGRAM_TOKEN").unwrap());
+    let mut router = Router::new(client);
+    router.add_route(Route::Message(Matcher::Any), handle);
+    router.start().await;
+}
+```
+
+Other API types are available via the [`api`] module, e.g., `api::SendMessageRequest`.
+*/
+
+pub use crate::{
+    api::{self, API},
+    handler::BotState,
+    Action, Client, ErrorAction, Event, Matcher, Route, Router, State, Text, Update,
+};
+
+/// The derive macro for [`BotState`].
+pub use mobot_derive::BotState;
@@ -36,6 +36,26 @@ fn progress_str(i: i64, state: ProgressState) -> String {
     }
 }
 
+/// Formats an elapsed time as, e.g., "45s" or "2m 5s".
+fn elapsed_str(elapsed: std::time::Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+/// Aborts the wrapped task when dropped. This makes sure that the task spawned by
+/// [`ProgressBar::start`] doesn't keep running if the progress loop exits early.
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 /// A ProgressBar that can be rendered in a telegram message. This calls a long running async
 /// task and shows a progress bar while the task is running. The progress bar is updated every
 /// `update_interval` seconds. If the task completes before the `timeout` then the progress bar
@@ -51,6 +71,11 @@ pub struct ProgressBar {
     /// `update_interval` seconds.
     pub update_interval: std::time::Duration,
 
+    /// The minimum time between edits of the progress bar message. Updates that come
+    /// sooner are dropped, so the bar doesn't hit Telegram's rate limits for editing
+    /// messages. Defaults to one second.
+    pub min_edit_interval: std::time::Duration,
+
     /// The string to show when the task fails.
     pub failed_str: String,
 
@@ -59,6 +84,13 @@ pub struct ProgressBar {
 
     /// If true, then show the result of the task after the progress bar.
     pub show_result: bool,
+
+    /// If set, then once the task has been running for this long, the elapsed time is shown
+    /// after the progress bar, so users know the bot isn't stuck.
+    pub show_elapsed_after: Option<std::time::Duration>,
+
+    /// The string to show before the elapsed time, see [`ProgressBar::with_elapsed_after`].
+    pub elapsed_prefix: String,
 }
 
 impl Default for ProgressBar {
@@ -66,9 +98,12 @@ impl Default for ProgressBar {
         Self {
             timeout: std::time::Duration::from_secs(60),
             update_interval: std::time::Duration::from_millis(500),
+            min_edit_interval: std::time::Duration::from_secs(1),
             failed_str: '\u{2718}'.into(),
             done_str: '\u{2714}'.into(),
             show_result: false,
+            show_elapsed_after: None,
+            elapsed_prefix: "still working\u{2026}".into(),
         }
     }
 }
@@ -93,12 +128,52 @@ impl ProgressBar {
         self
     }
 
+    /// Set the minimum time between edits of the progress bar message. Telegram allows
+    /// about one message per second in each chat (see [`RateLimiter`](crate::RateLimiter)),
+    /// which includes edits, so lowering this can get the bot rate limited.
+    pub fn with_min_edit_interval(mut self, min_edit_interval: std::time::Duration) -> Self {
+        self.min_edit_interval = min_edit_interval;
+        self
+    }
+
+    /// Show the elapsed time after the progress bar (e.g., "still working… 45s") once the
+    /// task has been running for longer than `threshold`. Useful for tasks whose duration
+    /// isn't known in advance.
+    pub fn with_elapsed_after(mut self, threshold: std::time::Duration) -> Self {
+        self.show_elapsed_after = Some(threshold);
+        self
+    }
+
+    /// Set the string shown before the elapsed time. Defaults to "still working…".
+    pub fn with_elapsed_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.elapsed_prefix = prefix.into();
+        self
+    }
+
+    /// Returns the progress bar string for a running task, with the elapsed time appended
+    /// if it's past the `show_elapsed_after` threshold.
+    fn working_str(&self, count: i64, elapsed: std::time::Duration) -> String {
+        let bar = progress_str(count, ProgressState::Working);
+        match self.show_elapsed_after {
+            Some(threshold) if elapsed >= threshold => {
+                format!("{} {} {}", bar, self.elapsed_prefix, elapsed_str(elapsed))
+            }
+            _ => bar,
+        }
+    }
+
     /// Start the progress bar. This calls the async function `f` and shows a progress bar while
     /// the task is running. The progress bar is updated every `update_interval` seconds. If the
     /// task completes before the `timeout` then the progress bar is replaced with a checkmark.
-    /// If the task fails, then the progress bar is replaced with a cross.
+    /// If the task fails, then the progress bar is replaced with a cross. The message is
+    /// edited at most once every `min_edit_interval`.
     ///
     /// If `show_result` is true, then the result of the task is shown after the progress bar.
+    /// If `show_elapsed_after` is set, then the elapsed time is shown while the task runs
+    /// past it.
+    ///
+    /// The task is aborted if the progress bar fails to update the message, or if the returned
+    /// future is dropped before the task completes.
     ///
     /// Returns the result of the task.
     pub async fn start<F, R>(&self, e: &Event, f: F) -> anyhow::Result<R>
@@ -110,10 +185,16 @@ impl ProgressBar {
         let mut message = e.send_message("...").await?;
 
         let (completed_tx, mut completed_rx) = tokio::sync::oneshot::channel();
-        tokio::spawn(async {
+        let task = tokio::spawn(async {
             _ = completed_tx.send(f.await);
         });
 
+        // If we return early (e.g., editing the message fails), or this future is dropped,
+        // cancel the task instead of leaving it running in the background.
+        let _task_guard = AbortOnDrop(task.abort_handle());
+
+        let started = tokio::time::Instant::now();
+        let mut last_edit: Option<tokio::time::Instant> = None;
         let mut count = 0;
         let mut done = false;
         let mut result: R = R::default();
@@ -123,13 +204,21 @@ impl ProgressBar {
                 // Update the progress bar.
                 _ = tokio::time::sleep(self.update_interval) => {
                     count += 1;
-                    message = e.edit_message(message.message_id, progress_str(count, ProgressState::Working)).await?;
+
+                    // Drop updates that come too soon after the last edit, so we don't get
+                    // rate limited.
+                    if last_edit.is_some_and(|t| t.elapsed() < self.min_edit_interval) {
+                        continue;
+                    }
+                    last_edit = Some(tokio::time::Instant::now());
+                    message = e.edit_message(message.message_id, self.working_str(count, started.elapsed())).await?;
                     e.send_chat_action(api::ChatAction::Typing).await?;
                 }
 
                 // Timeout.
                 _ = tokio::time::sleep(std::time::Duration::from_secs(30)) => {
                     done = true;
+                    self.wait_to_edit(last_edit).await;
                     message = e.edit_message(message.message_id,
                         format!("{} {}", progress_str(count, ProgressState::Failed(self.failed_str.as_str())),
                             "Something's wrong!")).await?;
@@ -139,6 +228,7 @@ impl ProgressBar {
                 v = &mut completed_rx => {
                     done = true;
                     result = v??;
+                    self.wait_to_edit(last_edit).await;
                     message = e.edit_message(message.message_id,
                         progress_str(count, ProgressState::Done(self.done_str.as_str()))).await?;
                     e.delete_message(message.message_id).await?;
@@ -149,4 +239,12 @@ impl ProgressBar {
 
         Ok(result)
     }
+
+    /// Wait until `min_edit_interval` has passed since the last edit, so that the final edit
+    /// isn't dropped or rate limited.
+    async fn wait_to_edit(&self, last_edit: Option<tokio::time::Instant>) {
+        if let Some(last_edit) = last_edit {
+            tokio::time::sleep_until(last_edit + self.min_edit_interval).await;
+        }
+    }
 }
@@ -0,0 +1,193 @@
+/// Token-bucket rate limiting for outgoing messages, see [`RateLimiter`].
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Drop the buckets of idle chats once we're tracking this many chats.
+const MAX_IDLE_CHATS: usize = 10_000;
+
+/// A token bucket that holds up to `capacity` tokens, and refills at `rate` tokens per
+/// second. Tokens can be borrowed (making the balance negative), in which case the caller
+/// has to wait until the debt is paid off.
+#[derive(Debug, Clone)]
+struct Bucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: Limit, now: Instant) -> Self {
+        let capacity = limit.count as f64;
+        Self {
+            capacity,
+            rate: capacity / limit.per.as_secs_f64(),
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Take a token, and return how long the caller has to wait before using it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.capacity
+    }
+}
+
+/// Allow `count` requests `per` duration.
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub count: u32,
+    pub per: Duration,
+}
+
+impl Limit {
+    /// Panics if `count` or `per` is zero, since such a limit would never let a request
+    /// through.
+    pub fn new(count: u32, per: Duration) -> Self {
+        assert!(count > 0, "rate limit count must be positive");
+        assert!(!per.is_zero(), "rate limit duration must be positive");
+        Self { count, per }
+    }
+}
+
+/// The buckets for a single chat. Groups and channels have an additional per-minute limit.
+#[derive(Debug)]
+struct ChatBuckets {
+    chat: Bucket,
+    group: Option<Bucket>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    global: Bucket,
+    chats: HashMap<String, ChatBuckets>,
+}
+
+/// `RateLimiter` delays outgoing messages to stay within Telegram's limits, instead of
+/// having them rejected with 429 (Too Many Requests). By default, it allows 30 messages
+/// per second across all chats, 1 message per second in each chat, and 20 messages per
+/// minute in each group or channel. Enable it with
+/// [`Client::with_rate_limiter`](crate::Client::with_rate_limiter):
+///
+/// ```no_run
+/// # use mobot::*;
+/// let client = Client::new("token".to_string()).with_rate_limiter(RateLimiter::new());
+/// ```
+///
+/// Only requests that send messages (`send*`, `forward*` and `copy*` methods) are limited.
+#[derive(Debug)]
+pub struct RateLimiter {
+    global_limit: Limit,
+    chat_limit: Limit,
+    group_limit: Limit,
+    buckets: Mutex<Buckets>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RateLimiter {
+    /// Returns a rate limiter with Telegram's documented limits.
+    pub fn new() -> Self {
+        let global_limit = Limit::new(30, Duration::from_secs(1));
+        Self {
+            global_limit,
+            chat_limit: Limit::new(1, Duration::from_secs(1)),
+            group_limit: Limit::new(20, Duration::from_secs(60)),
+            buckets: Mutex::new(Buckets {
+                global: Bucket::new(global_limit, Instant::now()),
+                chats: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Set the limit across all chats. Panics if `count` or `per` is zero.
+    pub fn with_global_limit(mut self, count: u32, per: Duration) -> Self {
+        self.global_limit = Limit::new(count, per);
+        self.buckets.get_mut().unwrap().global = Bucket::new(self.global_limit, Instant::now());
+        self
+    }
+
+    /// Set the limit for each chat. Panics if `count` or `per` is zero.
+    pub fn with_chat_limit(mut self, count: u32, per: Duration) -> Self {
+        self.chat_limit = Limit::new(count, per);
+        self
+    }
+
+    /// Set the additional limit for each group or channel. Panics if `count` or `per` is
+    /// zero.
+    pub fn with_group_limit(mut self, count: u32, per: Duration) -> Self {
+        self.group_limit = Limit::new(count, per);
+        self
+    }
+
+    /// Returns true if requests to `method` are rate limited.
+    pub fn limits(method: &str) -> bool {
+        method.starts_with("send") || method.starts_with("forward") || method.starts_with("copy")
+    }
+
+    /// Wait until a message can be sent to `chat_id` (or just within the global limit if
+    /// `chat_id` is `None`). Group and channel chat IDs are negative, or usernames.
+    pub async fn acquire(&self, chat_id: Option<&str>) {
+        let wait = self.reserve(chat_id, Instant::now());
+        if !wait.is_zero() {
+            debug!("Rate limited: waiting {:?} to send to {:?}", wait, chat_id);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Take a token from all the buckets for `chat_id`, and return how long to wait
+    /// before sending.
+    fn reserve(&self, chat_id: Option<&str>, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let mut wait = buckets.global.reserve(now);
+
+        if let Some(chat_id) = chat_id {
+            if buckets.chats.len() >= MAX_IDLE_CHATS {
+                buckets.chats.retain(|_, b| {
+                    !(b.chat.is_full(now) && b.group.as_mut().is_none_or(|g| g.is_full(now)))
+                });
+            }
+
+            let is_group = chat_id.starts_with('-') || chat_id.starts_with('@');
+            let chat = buckets
+                .chats
+                .entry(chat_id.to_string())
+                .or_insert_with(|| ChatBuckets {
+                    chat: Bucket::new(self.chat_limit, now),
+                    group: is_group.then(|| Bucket::new(self.group_limit, now)),
+                });
+
+            wait = wait.max(chat.chat.reserve(now));
+            if let Some(ref mut group) = chat.group {
+                wait = wait.max(group.reserve(now));
+            }
+        }
+
+        wait
+    }
+}
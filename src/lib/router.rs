@@ -4,31 +4,76 @@
 ///
 /// Chat Handlers are called for every message that is sent to the bot that is part
 /// of a chat session. The router keeps track of the state of each chat session,
-/// and passes the relevant state for the current Chat ID to the handler.
+/// and passes the relevant state for the current Chat ID to the handler. Stateless bots
+/// (using `State<()>`, or any other zero-sized state) don't keep any per-chat state.
 ///
 /// User handlers are called for every message that is sent to the bot from any specific
 /// user.
-use std::{cmp::max, collections::HashMap, sync::Arc, time::Duration};
+///
+/// In forum supergroups, all topics share the same chat ID, so they also share the same
+/// chat state. Use [`Matcher::Topic`] to register different handlers for different topics,
+/// and keep any per-topic data in your state keyed by `message_thread_id`.
+///
+/// A router can also serve multiple bot accounts (tokens) with the same handlers, see
+/// [`Router::with_account`]. Chat state is then keyed by account and chat ID, so the same
+/// user talking to two of the bots gets a separate state with each.
+use std::{
+    cmp::{max, min},
+    collections::{HashMap, HashSet, VecDeque},
+    mem::{discriminant, Discriminant},
+    ops::RangeInclusive,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::{future::BoxFuture, Future};
-use tokio::sync::{mpsc, Notify, RwLock};
+use tokio::{
+    sync::{mpsc, watch, Notify, RwLock},
+    task::JoinHandle,
+};
 
 use crate::{
-    api::{self, GetUpdatesRequest, SendMessageRequest, SendStickerRequest, API},
-    handler::{BotHandler, BotState},
-    Action, Client, Event, State, Update,
+    api::{
+        self, BotCommand, GetUpdatesRequest, SendMessageRequest, SendStickerRequest,
+        SetMyCommandsRequest, API,
+    },
+    event::{Localizer, PendingPrompts, PermissionsCache, Spawner, PERMISSIONS_TTL, PROMPT_TTL},
+    handler::{BotHandler, BotState, StateMap},
+    Action, Client, ErrorAction, Event, State, Update,
 };
 
 use anyhow::anyhow;
 
+/// Initial and maximum delays between retries when polling /getUpdates fails.
+const MIN_POLL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_POLL_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The number of consecutive malformed /getUpdates responses before the router starts
+/// looking for an update it can't parse, to skip it.
+const MAX_MALFORMED_POLLS: u32 = 3;
+
 type Arw<T> = Arc<RwLock<T>>;
-type HandlerMap<S> = HashMap<Route, Vec<(Matcher, Box<dyn BotHandler<S>>)>>;
-type ErrorHandler<S> =
-    Box<dyn Fn(Arc<API>, i64, State<S>, anyhow::Error) -> BoxFuture<'static, ()> + Send + Sync>;
+type Handlers<S> = Vec<(Matcher, Box<dyn BotHandler<S>>)>;
+
+/// Handlers are grouped by the kind of route they're added for (e.g., all `Route::Message`
+/// handlers), along with that route with `Matcher::Any`. Keying on the route variant
+/// rather than the route means matchers don't need to be hashable.
+type HandlerMap<S> = HashMap<Discriminant<Route>, (Route, Handlers<S>)>;
+
+/// Per-chat state is keyed by the account index (see [`Router::with_account`]) and chat ID.
+type ChatKey = (usize, i64);
+type ErrorHandler<S> = Box<
+    dyn Fn(Arc<API>, i64, State<S>, anyhow::Error) -> BoxFuture<'static, ErrorAction> + Send + Sync,
+>;
+type PostHook = Arc<dyn Fn(Event, Option<Action>) -> BoxFuture<'static, ()> + Send + Sync>;
 
 /// `Matcher` is used to match a message against a route. It is used to determine
 /// which handler should be called for a given message.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+///
+/// Text matchers (`Exact`, `Prefix`, `Regex`, `BotCommand`, `Empty`, `Length`) match
+/// against the message text. To also match against the captions of media messages (photos,
+/// documents, etc.), use [`Router::with_match_captions`].
+#[derive(Debug, Clone)]
 pub enum Matcher {
     /// Match any message
     Any,
@@ -50,6 +95,55 @@ pub enum Matcher {
 
     /// Match messages that represent a general file
     Document,
+
+    /// Match messages that represent an animation (GIF)
+    Animation,
+
+    /// Match messages that share a venue (a location with a title and address)
+    Venue,
+
+    /// Match messages that share a location (including venues). Use this with
+    /// `Route::EditedMessage` to receive updates to live locations.
+    Location,
+
+    /// Match `users_shared` service messages, sent when the user picks users with a
+    /// [`KeyboardButtonRequestUsers`](api::KeyboardButtonRequestUsers) button
+    UsersShared,
+
+    /// Match `chat_shared` service messages, sent when the user picks a chat with a
+    /// [`KeyboardButtonRequestChat`](api::KeyboardButtonRequestChat) button
+    ChatShared,
+
+    /// Match `successful_payment` service messages, sent when the user pays an invoice
+    /// sent with [`API::send_invoice`](api::API::send_invoice)
+    SuccessfulPayment,
+
+    /// Match `refunded_payment` service messages, sent when a payment is refunded
+    RefundedPayment,
+
+    /// Match messages without text (e.g., stickers or photos), or with empty text
+    Empty,
+
+    /// Match messages whose text length (in characters) is within the given range. For
+    /// example, use `Length(4097..=usize::MAX)` to reject overly long input.
+    Length(RangeInclusive<usize>),
+
+    /// Match messages posted in the forum topic with the given `message_thread_id`. This
+    /// lets you register different handlers for different topics of a forum supergroup.
+    Topic(i64),
+
+    /// Match answers to the prompt sent with [`Event::ask`] under the given name. The
+    /// router checks replies against the prompts pending in each chat before normal
+    /// routing, so these handlers run before any others for an answer. The prompt is no
+    /// longer pending once answered, or once it expires (see [`Router::with_prompt_ttl`]).
+    ///
+    /// This matches a name rather than a `message_id`: the ID of a prompt is only known
+    /// once it's sent at runtime, long after routes are added, so a route can't name it.
+    /// The router maps each pending prompt's ID to its name instead.
+    ///
+    /// Pending prompts are only known to a running router, so [`Route::match_update`]
+    /// never matches this.
+    ReplyTo(String),
 }
 
 impl Matcher {
@@ -60,7 +154,29 @@ impl Matcher {
             Self::Prefix(m) => s.starts_with(m),
             Self::Regex(m) => regex::Regex::new(m).unwrap().is_match(s),
             Self::BotCommand(m) => s.starts_with(&format!("/{}", m)),
-            Self::Document | Self::Photo => false,
+            Self::Empty => s.is_empty(),
+            Self::Length(range) => range.contains(&s.chars().count()),
+            Self::Document
+            | Self::Photo
+            | Self::Animation
+            | Self::Venue
+            | Self::Location
+            | Self::UsersShared
+            | Self::ChatShared
+            | Self::SuccessfulPayment
+            | Self::RefundedPayment
+            | Self::Topic(_)
+            | Self::ReplyTo(_) => false,
+        }
+    }
+
+    /// Match the text of an update, or `None` if the update has no text. Only `Empty`
+    /// matches updates without text.
+    pub fn match_text(&self, s: Option<&str>) -> bool {
+        match (self, s) {
+            (Self::Empty, None) => true,
+            (_, Some(s)) => self.match_str(s),
+            (_, None) => false,
         }
     }
 }
@@ -76,12 +192,18 @@ impl From<Route> for Matcher {
             Route::EditedChannelPost(matcher) => matcher,
             Route::CallbackQuery(matcher) => matcher,
             Route::InlineQuery(matcher) => matcher,
+            Route::BusinessMessage(matcher) => matcher,
+            Route::ChatJoinRequest(matcher) => matcher,
+            Route::MyChatMember(matcher) => matcher,
+            Route::ChatMember(matcher) => matcher,
+            Route::PreCheckoutQuery(matcher) => matcher,
+            Route::Unknown(matcher) => matcher,
         }
     }
 }
 
 /// `Route` is used to determine which handler should be called for a given message or query.
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone)]
 pub enum Route {
     /// Handle any event (alias for Any(Any))
     Default,
@@ -106,32 +228,118 @@ pub enum Route {
 
     /// Handle inline queries
     InlineQuery(Matcher),
+
+    /// Handle messages from connected business accounts
+    BusinessMessage(Matcher),
+
+    /// Handle requests to join a chat. `Matcher::Any` matches all requests, and text
+    /// matchers match against the bio of the user requesting to join.
+    ChatJoinRequest(Matcher),
+
+    /// Handle changes to the bot's own status in a chat, e.g., when it's added to or
+    /// removed from a group. `Matcher::Any` matches all changes, and text matchers match
+    /// against the new status (e.g., `Matcher::Exact("kicked".into())`).
+    MyChatMember(Matcher),
+
+    /// Handle changes to the status of other chat members. Like `MyChatMember`, text
+    /// matchers match against the new status. Telegram only sends these updates when
+    /// explicitly requested, which the router does if a `ChatMember` route is registered.
+    ChatMember(Matcher),
+
+    /// Handle pre-checkout queries, sent when the user confirms the payment of an invoice.
+    /// These must be answered with [`API::answer_pre_checkout_query`](api::API::answer_pre_checkout_query)
+    /// within 10 seconds. `Matcher::Any` matches all queries, and text matchers match
+    /// against the invoice payload.
+    PreCheckoutQuery(Matcher),
+
+    /// Handle update types that mobot doesn't model yet. Use [`Event::raw_update`] to get
+    /// their contents from [`api::Update::other`]. `Matcher::Any` matches all such updates,
+    /// and text matchers match against the update type (e.g., `"message_reaction"`).
+    ///
+    /// Unknown updates are only dispatched to these routes, never to `Route::Default`.
+    /// Note that some update types are only sent by Telegram if requested explicitly, see
+    /// [`Router::with_extra_update_types`].
+    Unknown(Matcher),
 }
 
-fn get_update_parts(update: &api::Update) -> anyhow::Result<(i64, Route)> {
+/// All the update types the router can handle, in the order they appear in
+/// `api::Update`.
+const UPDATE_TYPES: &[&str] = &[
+    "message",
+    "edited_message",
+    "channel_post",
+    "edited_channel_post",
+    "inline_query",
+    "callback_query",
+    "business_message",
+    "chat_join_request",
+    "my_chat_member",
+    "chat_member",
+    "pre_checkout_query",
+];
+
+fn get_update_parts(update: &api::Update) -> anyhow::Result<(Option<i64>, Route)> {
     if let Some(ref m) = update.message {
         debug!("New message: {:#?}", m);
-        Ok((m.chat.id, Route::Message(Matcher::Any)))
+        Ok((Some(m.chat.id), Route::Message(Matcher::Any)))
     } else if let Some(ref m) = update.edited_message {
         debug!("Edited message: {:#?}", m);
-        Ok((m.chat.id, Route::EditedMessage(Matcher::Any)))
+        Ok((Some(m.chat.id), Route::EditedMessage(Matcher::Any)))
     } else if let Some(ref m) = update.channel_post {
         debug!("Channel post: {:#?}", m);
-        Ok((m.chat.id, Route::ChannelPost(Matcher::Any)))
+        Ok((Some(m.chat.id), Route::ChannelPost(Matcher::Any)))
     } else if let Some(ref m) = update.edited_channel_post {
         debug!("Edited channel post: {:#?}", m);
-        Ok((m.chat.id, Route::EditedChannelPost(Matcher::Any)))
+        Ok((Some(m.chat.id), Route::EditedChannelPost(Matcher::Any)))
     } else if let Some(ref q) = update.callback_query {
         debug!("Callback query: {:#?}", q);
+        // Callback queries on inline messages have no message (and hence no chat), so key
+        // them by the user who sent the query, just like inline queries.
         Ok((
-            q.message.as_ref().map(|m| m.chat.id).unwrap_or(0),
+            Some(q.message.as_ref().map(|m| m.chat.id).unwrap_or(q.from.id)),
             Route::CallbackQuery(Matcher::Any),
         ))
     } else if let Some(ref q) = update.inline_query {
         debug!("Inline query: {:#?}", q);
-        Ok((q.from.id, Route::InlineQuery(Matcher::Any)))
+        Ok((Some(q.from.id), Route::InlineQuery(Matcher::Any)))
+    } else if let Some(ref m) = update.business_message {
+        debug!("Business message: {:#?}", m);
+        Ok((Some(m.chat.id), Route::BusinessMessage(Matcher::Any)))
+    } else if let Some(ref r) = update.chat_join_request {
+        debug!("Chat join request: {:#?}", r);
+        Ok((Some(r.chat.id), Route::ChatJoinRequest(Matcher::Any)))
+    } else if let Some(ref m) = update.my_chat_member {
+        debug!("My chat member: {:#?}", m);
+        Ok((Some(m.chat.id), Route::MyChatMember(Matcher::Any)))
+    } else if let Some(ref m) = update.chat_member {
+        debug!("Chat member: {:#?}", m);
+        Ok((Some(m.chat.id), Route::ChatMember(Matcher::Any)))
+    } else if let Some(ref q) = update.pre_checkout_query {
+        debug!("Pre-checkout query: {:#?}", q);
+        Ok((Some(q.from.id), Route::PreCheckoutQuery(Matcher::Any)))
     } else {
-        anyhow::bail!("Unknown update type")
+        debug!("Unknown update: {:#?}", update.other);
+        Ok((unknown_update_chat_id(update), Route::Unknown(Matcher::Any)))
+    }
+}
+
+/// Returns the chat ID of an update type mobot doesn't model, taken from its `chat`,
+/// `from` or `user` field, or `None` if it has none (e.g., `poll` updates).
+fn unknown_update_chat_id(update: &api::Update) -> Option<i64> {
+    update.other.values().find_map(|value| {
+        ["chat", "from", "user"]
+            .iter()
+            .find_map(|field| value.get(field)?.get("id")?.as_i64())
+    })
+}
+
+/// Returns the text that text matchers are tested against: the message text, or if
+/// `match_captions` is set, the caption of media messages.
+fn message_text(message: &api::Message, match_captions: bool) -> Option<&str> {
+    if match_captions {
+        message.text_or_caption()
+    } else {
+        message.text.as_deref()
     }
 }
 
@@ -146,6 +354,31 @@ impl Route {
             Self::EditedChannelPost(_) => Self::EditedChannelPost(Matcher::Any),
             Self::CallbackQuery(_) => Self::CallbackQuery(Matcher::Any),
             Self::InlineQuery(_) => Self::InlineQuery(Matcher::Any),
+            Self::BusinessMessage(_) => Self::BusinessMessage(Matcher::Any),
+            Self::ChatJoinRequest(_) => Self::ChatJoinRequest(Matcher::Any),
+            Self::MyChatMember(_) => Self::MyChatMember(Matcher::Any),
+            Self::ChatMember(_) => Self::ChatMember(Matcher::Any),
+            Self::PreCheckoutQuery(_) => Self::PreCheckoutQuery(Matcher::Any),
+            Self::Unknown(_) => Self::Unknown(Matcher::Any),
+        }
+    }
+
+    /// Returns the Telegram update type (as used in `allowed_updates`) that this route
+    /// handles, or `None` if the route handles all update types.
+    pub fn update_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Default | Self::Any(_) | Self::Unknown(_) => None,
+            Self::Message(_) => Some("message"),
+            Self::EditedMessage(_) => Some("edited_message"),
+            Self::ChannelPost(_) => Some("channel_post"),
+            Self::EditedChannelPost(_) => Some("edited_channel_post"),
+            Self::CallbackQuery(_) => Some("callback_query"),
+            Self::InlineQuery(_) => Some("inline_query"),
+            Self::BusinessMessage(_) => Some("business_message"),
+            Self::ChatJoinRequest(_) => Some("chat_join_request"),
+            Self::MyChatMember(_) => Some("my_chat_member"),
+            Self::ChatMember(_) => Some("chat_member"),
+            Self::PreCheckoutQuery(_) => Some("pre_checkout_query"),
         }
     }
 
@@ -159,10 +392,28 @@ impl Route {
             Self::EditedChannelPost(_) => Self::EditedChannelPost(matcher.clone()),
             Self::CallbackQuery(_) => Self::CallbackQuery(matcher.clone()),
             Self::InlineQuery(_) => Self::InlineQuery(matcher.clone()),
+            Self::BusinessMessage(_) => Self::BusinessMessage(matcher.clone()),
+            Self::ChatJoinRequest(_) => Self::ChatJoinRequest(matcher.clone()),
+            Self::MyChatMember(_) => Self::MyChatMember(matcher.clone()),
+            Self::ChatMember(_) => Self::ChatMember(matcher.clone()),
+            Self::PreCheckoutQuery(_) => Self::PreCheckoutQuery(matcher.clone()),
+            Self::Unknown(_) => Self::Unknown(matcher.clone()),
         }
     }
 
+    /// Returns true if the update matches this route. Text matchers only consider the
+    /// message text, see [`Route::match_update_with_captions`].
     pub fn match_update(&self, update: &api::Update) -> bool {
+        self.matches(update, false)
+    }
+
+    /// Same as [`Route::match_update`], but text matchers fall back to the caption of media
+    /// messages without text.
+    pub fn match_update_with_captions(&self, update: &api::Update) -> bool {
+        self.matches(update, true)
+    }
+
+    fn matches(&self, update: &api::Update, match_captions: bool) -> bool {
         match self {
             Self::Message(m) => match m {
                 Matcher::Photo => update
@@ -175,55 +426,141 @@ impl Route {
                     .as_ref()
                     .and_then(|m| m.document.as_ref())
                     .is_some(),
+                Matcher::Animation => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.animation.as_ref())
+                    .is_some(),
+                Matcher::Venue => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.venue.as_ref())
+                    .is_some(),
+                Matcher::Location => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.location.as_ref())
+                    .is_some(),
+                Matcher::UsersShared => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.users_shared.as_ref())
+                    .is_some(),
+                Matcher::ChatShared => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.chat_shared.as_ref())
+                    .is_some(),
+                Matcher::SuccessfulPayment => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.successful_payment.as_ref())
+                    .is_some(),
+                Matcher::RefundedPayment => update
+                    .message
+                    .as_ref()
+                    .and_then(|m| m.refunded_payment.as_ref())
+                    .is_some(),
+                Matcher::Topic(id) => {
+                    update.message.as_ref().and_then(|m| m.message_thread_id) == Some(*id)
+                }
                 _ => update
                     .message
                     .as_ref()
-                    .and_then(|m| m.text.as_ref())
-                    .map_or(false, |t| m.match_str(t)),
+                    .is_some_and(|msg| m.match_text(message_text(msg, match_captions))),
+            },
+            Self::EditedMessage(m) => match m {
+                Matcher::Location => update
+                    .edited_message
+                    .as_ref()
+                    .and_then(|m| m.location.as_ref())
+                    .is_some(),
+                Matcher::Topic(id) => {
+                    update
+                        .edited_message
+                        .as_ref()
+                        .and_then(|m| m.message_thread_id)
+                        == Some(*id)
+                }
+                _ => update
+                    .edited_message
+                    .as_ref()
+                    .is_some_and(|msg| m.match_text(message_text(msg, match_captions))),
             },
-            Self::EditedMessage(m) => update
-                .edited_message
-                .as_ref()
-                .and_then(|m| m.text.as_ref())
-                .map_or(false, |t| m.match_str(t)),
             Self::ChannelPost(m) => update
                 .channel_post
                 .as_ref()
-                .and_then(|m| m.text.as_ref())
-                .map_or(false, |t| m.match_str(t)),
+                .is_some_and(|msg| m.match_text(message_text(msg, match_captions))),
             Self::EditedChannelPost(m) => update
                 .edited_channel_post
                 .as_ref()
-                .and_then(|m| m.text.as_ref())
-                .map_or(false, |t| m.match_str(t)),
+                .is_some_and(|msg| m.match_text(message_text(msg, match_captions))),
             Self::CallbackQuery(m) => update
                 .callback_query
                 .as_ref()
-                .and_then(|m| m.data.as_ref())
-                .map_or(false, |t| m.match_str(t)),
+                .is_some_and(|q| m.match_text(q.data.as_deref())),
             Self::InlineQuery(m) => update
                 .inline_query
                 .as_ref()
-                .map_or(false, |t| m.match_str(&t.query)),
+                .is_some_and(|q| m.match_text(Some(&q.query))),
+            Self::BusinessMessage(m) => update
+                .business_message
+                .as_ref()
+                .is_some_and(|msg| m.match_text(message_text(msg, match_captions))),
+            Self::ChatJoinRequest(m) => update
+                .chat_join_request
+                .as_ref()
+                .is_some_and(|r| matches!(m, Matcher::Any) || m.match_text(r.bio.as_deref())),
+            Self::MyChatMember(m) => update
+                .my_chat_member
+                .as_ref()
+                .is_some_and(|u| m.match_text(Some(&u.new_chat_member.status))),
+            Self::ChatMember(m) => update
+                .chat_member
+                .as_ref()
+                .is_some_and(|u| m.match_text(Some(&u.new_chat_member.status))),
+            Self::PreCheckoutQuery(m) => update
+                .pre_checkout_query
+                .as_ref()
+                .is_some_and(|q| m.match_text(Some(&q.invoice_payload))),
+            Self::Unknown(m) => {
+                matches!(m, Matcher::Any) || update.other.keys().any(|t| m.match_text(Some(t)))
+            }
             Self::Any(matcher) => {
                 let mut matched = false;
                 if let Some(ref m) = update.message {
-                    matched |= m.text.as_ref().map_or(false, |t| matcher.match_str(t));
+                    matched |= matcher.match_text(message_text(m, match_captions));
                 }
                 if let Some(ref m) = update.edited_message {
-                    matched |= m.text.as_ref().map_or(false, |t| matcher.match_str(t));
+                    matched |= matcher.match_text(message_text(m, match_captions));
                 }
                 if let Some(ref m) = update.channel_post {
-                    matched |= m.text.as_ref().map_or(false, |t| matcher.match_str(t));
+                    matched |= matcher.match_text(message_text(m, match_captions));
                 }
                 if let Some(ref m) = update.edited_channel_post {
-                    matched |= m.text.as_ref().map_or(false, |t| matcher.match_str(t));
+                    matched |= matcher.match_text(message_text(m, match_captions));
                 }
                 if let Some(ref q) = update.callback_query {
-                    matched |= q.data.as_ref().map_or(false, |t| matcher.match_str(t));
+                    matched |= matcher.match_text(q.data.as_deref());
                 }
                 if let Some(ref q) = update.inline_query {
-                    matched |= matcher.match_str(&q.query);
+                    matched |= matcher.match_text(Some(&q.query));
+                }
+                if let Some(ref m) = update.business_message {
+                    matched |= matcher.match_text(message_text(m, match_captions));
+                }
+                if let Some(ref r) = update.chat_join_request {
+                    matched |=
+                        matches!(matcher, Matcher::Any) || matcher.match_text(r.bio.as_deref());
+                }
+                if let Some(ref u) = update.my_chat_member {
+                    matched |= matcher.match_text(Some(&u.new_chat_member.status));
+                }
+                if let Some(ref u) = update.chat_member {
+                    matched |= matcher.match_text(Some(&u.new_chat_member.status));
+                }
+                if let Some(ref q) = update.pre_checkout_query {
+                    matched |= matcher.match_text(Some(&q.invoice_payload));
                 }
                 matched
             }
@@ -232,43 +569,247 @@ impl Route {
     }
 }
 
+/// The (account, update_id) pairs of recent updates, oldest first.
+#[derive(Default)]
+struct SeenUpdates {
+    ids: HashSet<(usize, i64)>,
+    order: VecDeque<(usize, i64)>,
+}
+
+/// Remembers the last `window` updates received, to drop duplicate deliveries. See
+/// [`Router::with_dedup`].
+#[derive(Clone)]
+struct UpdateDedup {
+    window: usize,
+    seen: Arc<std::sync::Mutex<SeenUpdates>>,
+}
+
+impl UpdateDedup {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            seen: Arc::new(std::sync::Mutex::new(SeenUpdates::default())),
+        }
+    }
+
+    /// Record the update, and return false if it was already seen.
+    fn insert(&self, account: usize, update_id: i64) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        if !seen.ids.insert((account, update_id)) {
+            return false;
+        }
+
+        seen.order.push_back((account, update_id));
+        if seen.order.len() > self.window {
+            if let Some(oldest) = seen.order.pop_front() {
+                seen.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Creates the initial state for a new chat, see [`Router::with_state_fn`].
+type StateFn<S> = Arc<dyn Fn() -> S + Send + Sync>;
+
 pub struct Router<S: BotState> {
     pub api: Arc<API>,
+
+    /// Additional bot accounts served by this router, see [`Router::with_account`].
+    accounts: Vec<Arc<API>>,
     state: Option<Arc<RwLock<S>>>,
+    state_fn: Option<StateFn<S>>,
 
     error_handler: Arc<ErrorHandler<S>>,
 
+    /// Called once per update after dispatch, see [`Router::with_post_hook`].
+    post_hook: Option<PostHook>,
+
     /// TODO: locks are too fine grained, break it up
     init_handlers: Option<HandlerMap<S>>,
     handlers: Arw<HandlerMap<S>>,
-    handler_state: Arw<HashMap<i64, State<S>>>,
+    handler_state: Arw<HashMap<ChatKey, State<S>>>,
+
+    /// Additional type-erased states for each chat, see [`Event::state`].
+    chat_states: StateMap,
+
+    /// Localizes messages for [`Event::tr`].
+    localizer: Option<Localizer>,
+
+    /// Commands registered with [`Router::add_command`], for the Telegram command menu
+    commands: Vec<BotCommand>,
 
     /// Telegram getUpdates HTTP poll timeout
     timeout_s: i64,
 
+    /// Maximum number of updates per getUpdates call, see [`Router::with_poll_limit`]
+    poll_limit: Option<i64>,
+
+    /// Dispatch each batch of updates in `update_id` order
+    sort_updates: bool,
+
+    /// Match text routes against captions too, see [`Router::with_match_captions`]
+    match_captions: bool,
+
+    /// Delete any webhook before polling, see [`Router::with_delete_webhook`]
+    delete_webhook: bool,
+
+    /// The bot's membership in each chat, see [`Event::my_permissions`]
+    permissions: PermissionsCache,
+
+    /// Prompts waiting for an answer in each chat, see [`Event::ask`]
+    prompts: PendingPrompts,
+
+    /// Drops duplicate updates, see [`Router::with_dedup`]
+    dedup: Option<UpdateDedup>,
+
+    /// Update types to request in addition to the routed ones, see
+    /// [`Router::with_extra_update_types`]
+    extra_update_types: Vec<String>,
+
+    /// Health of the poll loop of each account, see [`Router::health`]
+    health: Vec<Arc<watch::Sender<PollHealth>>>,
+
+    /// Set to true on shutdown, to cancel background jobs (see [`Event::spawn`])
+    cancel_jobs: watch::Sender<bool>,
+
     /// Shutdown notifier
     shutdown: Arc<Notify>,
     shutdown_tx: Arc<mpsc::Sender<()>>,
     shutdown_rx: mpsc::Receiver<()>,
 }
 
-async fn default_error_handler<S: BotState>(
+/// `PollHealth` reports the health of the router's `getUpdates` poll loop. See
+/// [`Router::health`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PollHealth {
+    /// When `getUpdates` last succeeded, or `None` if it hasn't succeeded yet.
+    pub last_success: Option<Instant>,
+
+    /// The number of consecutive failed `getUpdates` calls. Reset to 0 on success.
+    pub consecutive_failures: u32,
+}
+
+/// `RouterHandle` is a handle to a router running in a background task, returned by
+/// [`Router::spawn`].
+pub struct RouterHandle {
+    /// The API client used by the router (for its first account, see
+    /// [`Router::with_account`]).
+    pub api: Arc<API>,
+
+    health: Vec<watch::Receiver<PollHealth>>,
+    shutdown_tx: Arc<mpsc::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl RouterHandle {
+    /// Returns a receiver for the health of the poll loop of the router's first account,
+    /// see [`Router::health`].
+    pub fn health(&self) -> watch::Receiver<PollHealth> {
+        self.health[0].clone()
+    }
+
+    /// Returns a receiver for the health of the poll loop of `account`, or `None` if
+    /// there's no such account. See [`Router::account_health`].
+    pub fn account_health(&self, account: usize) -> Option<watch::Receiver<PollHealth>> {
+        self.health.get(account).cloned()
+    }
+
+    /// Stop the router, and wait for it to exit. Pending polls are abandoned, so this
+    /// doesn't wait for the poll timeout.
+    pub async fn shutdown(self) {
+        _ = self.shutdown_tx.send(()).await;
+        self.join().await;
+    }
+
+    /// Wait for the router to exit.
+    pub async fn join(self) {
+        if let Err(err) = self.task.await {
+            error!("Router task failed: {}", err);
+        }
+    }
+}
+
+/// Router state that's shared with the task handling each update.
+#[derive(Clone)]
+struct UpdateContext<S: BotState> {
     api: Arc<API>,
+    account: usize,
+    handler_state: Arw<HashMap<ChatKey, State<S>>>,
+    state_fn: Option<StateFn<S>>,
+    chat_states: StateMap,
+    localizer: Option<Localizer>,
+    handlers: Arw<HandlerMap<S>>,
+    error_handler: Arc<ErrorHandler<S>>,
+    post_hook: Option<PostHook>,
+    cancel_jobs: watch::Receiver<bool>,
+    match_captions: bool,
+    permissions: PermissionsCache,
+    prompts: PendingPrompts,
+    dedup: Option<UpdateDedup>,
+}
+
+/// Settings for the `getUpdates` poll loop of each account.
+#[derive(Clone)]
+struct PollOptions {
+    timeout_s: i64,
+    poll_limit: Option<i64>,
+    sort_updates: bool,
+    allowed_updates: Vec<String>,
+}
+
+/// The outcome of dispatching an update, passed to the post hook.
+#[derive(Default)]
+struct Outcome {
+    /// The event passed to the last handler that ran.
+    event: Option<Event>,
+
+    /// The action returned by the last handler that ran, or `None` if it failed.
+    action: Option<Action>,
+}
+
+/// The default error handler logs the error. In debug builds, it also replies to the chat
+/// with the error message. In release builds, it stays silent so internal errors aren't
+/// leaked to users.
+async fn default_error_handler<S: BotState>(
+    _: Arc<API>,
     chat_id: i64,
     _: State<S>,
     err: anyhow::Error,
+) -> ErrorAction {
+    error!("Error in chat {}: {}", chat_id, err);
+
+    if cfg!(debug_assertions) {
+        ErrorAction::Reply(format!("Handler error: {}", err))
+    } else {
+        ErrorAction::Silent
+    }
+}
+
+/// Run the error handler, and carry out the returned `ErrorAction`.
+async fn handle_error<S: BotState>(
+    api: Arc<API>,
+    error_handler: &ErrorHandler<S>,
+    chat_id: Option<i64>,
+    state: State<S>,
+    err: anyhow::Error,
 ) {
-    error!("Error: {}", err);
-    let result = api
-        .send_message(&SendMessageRequest {
-            chat_id,
-            text: format!("Handler error: {}", err),
-            ..Default::default()
-        })
-        .await;
+    // Updates without a chat (e.g., `poll_answer`) have nowhere to send a reply to.
+    let Some(chat_id) = chat_id else {
+        error!("Error handling update without a chat: {}", err);
+        return;
+    };
 
-    if let Err(err) = result {
-        error!("Error in default error handler: {}", err);
+    match error_handler(Arc::clone(&api), chat_id, state, err).await {
+        ErrorAction::Reply(text) => {
+            if let Err(err) = api
+                .send_message(&SendMessageRequest::new(chat_id, text))
+                .await
+            {
+                error!("Error sending error reply: {}", err);
+            }
+        }
+        ErrorAction::Silent => {}
     }
 }
 
@@ -279,36 +820,245 @@ impl<S: BotState> Router<S> {
 
         Self {
             api: Arc::new(API::new(client)),
+            accounts: vec![],
             state: None,
+            state_fn: None,
             error_handler: Arc::new(Box::new(move |a, b, c, d| {
                 Box::pin(default_error_handler(a, b, c, d))
             })),
+            post_hook: None,
             init_handlers: Some(HashMap::new()),
             handlers: Arc::new(RwLock::new(HashMap::new())),
             handler_state: Arc::new(RwLock::new(HashMap::new())),
+            chat_states: StateMap::new(),
+            localizer: None,
+            commands: vec![],
             timeout_s: 60,
+            poll_limit: None,
+            sort_updates: true,
+            match_captions: false,
+            delete_webhook: false,
+            permissions: PermissionsCache::new(PERMISSIONS_TTL),
+            prompts: PendingPrompts::new(PROMPT_TTL),
+            dedup: None,
+            extra_update_types: vec![],
+            health: vec![Arc::new(watch::channel(PollHealth::default()).0)],
+            cancel_jobs: watch::channel(false).0,
             shutdown: Arc::new(Notify::new()),
             shutdown_tx: Arc::new(shutdown_tx),
             shutdown_rx,
         }
     }
 
+    /// Serve another bot account with the same handlers. Each account has its own poll
+    /// loop, and handlers reply via the account the update was received on, so the
+    /// handler registration code doesn't need to be duplicated per token:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// let router = Router::<()>::new(Client::new("token1".to_string()))
+    ///     .with_account(Client::new("token2".to_string()));
+    /// ```
+    ///
+    /// The router's own client is account 0, and accounts added here are numbered in order
+    /// from 1. Use [`Event::account`] to tell them apart in handlers. Chat state is keyed
+    /// by account and chat ID, so a user talking to two of the bots has a separate state
+    /// with each. The shared state passed to [`Router::with_state`] is cloned into each
+    /// of them as usual.
+    pub fn with_account(mut self, client: Client) -> Self {
+        self.accounts.push(Arc::new(API::new(client)));
+        self.health
+            .push(Arc::new(watch::channel(PollHealth::default()).0));
+        self
+    }
+
+    /// Returns the API clients for all accounts served by this router, indexed by account
+    /// (see [`Router::with_account`]).
+    pub fn apis(&self) -> Vec<Arc<API>> {
+        std::iter::once(&self.api)
+            .chain(self.accounts.iter())
+            .cloned()
+            .collect()
+    }
+
     pub fn with_poll_timeout_s(mut self, timeout_s: i64) -> Self {
         self.timeout_s = timeout_s;
         self
     }
 
+    /// Set the maximum number of updates to fetch with each `getUpdates` call (1-100).
+    /// Defaults to 100, Telegram's maximum.
+    ///
+    /// The updates of each chat in a batch are handled in their own task, so different
+    /// chats are handled concurrently, and the next batch is fetched without waiting for
+    /// them. A smaller limit fetches fewer updates at a time, which narrows (but doesn't
+    /// eliminate) the window for updates to be handled out of order. Ordering (see
+    /// [`Router::with_sorted_updates`]) only applies within a batch.
+    pub fn with_poll_limit(mut self, limit: i64) -> Self {
+        self.poll_limit = Some(limit);
+        self
+    }
+
+    /// By default, the updates in each `getUpdates` batch are sorted by `update_id`, so
+    /// they're dispatched in the order Telegram received them, even if the batch arrives
+    /// out of order. Pass `false` to dispatch them in the order they were returned.
+    ///
+    /// Either way, the updates of a chat within a batch are handled one at a time, in
+    /// that order, while different chats are handled concurrently.
+    pub fn with_sorted_updates(mut self, sort_updates: bool) -> Self {
+        self.sort_updates = sort_updates;
+        self
+    }
+
+    /// If set, text matchers (e.g., `Matcher::Prefix`, `Matcher::BotCommand`) also match
+    /// against the captions of media messages, so a `/report` command works whether it's
+    /// sent as text or as a photo caption. Defaults to `false` (text only).
+    pub fn with_match_captions(mut self, match_captions: bool) -> Self {
+        self.match_captions = match_captions;
+        self
+    }
+
+    /// If true, the router deletes the bot's webhook (keeping pending updates) before it
+    /// starts polling. Telegram rejects `getUpdates` with 409 (Conflict) while a webhook is
+    /// set, so enable this for bots that may have been run with a webhook before. Off by
+    /// default.
+    pub fn with_delete_webhook(mut self, delete_webhook: bool) -> Self {
+        self.delete_webhook = delete_webhook;
+        self
+    }
+
+    /// Drop updates whose `update_id` was among the last `window` updates received on the
+    /// same account, instead of handling them twice. Telegram can deliver an update more
+    /// than once, e.g., when it retries a webhook that didn't respond in time. Applies to
+    /// both polling and [`Router::dispatch`]. Off by default.
+    pub fn with_dedup(mut self, window: usize) -> Self {
+        self.dedup = Some(UpdateDedup::new(window));
+        self
+    }
+
+    /// Request the given update types (e.g., `"message_reaction"`) from Telegram, in
+    /// addition to the ones the registered routes handle. Use this for update types that
+    /// mobot doesn't model (see [`Route::Unknown`]), which Telegram only sends if they're
+    /// requested by name. See also [`Router::allowed_updates`].
+    pub fn with_extra_update_types(mut self, update_types: &[&str]) -> Self {
+        self.extra_update_types
+            .extend(update_types.iter().map(|t| t.to_string()));
+        self
+    }
+
+    /// Set how long [`Event::my_permissions`] caches the bot's membership in each chat.
+    /// Defaults to [`PERMISSIONS_TTL`].
+    pub fn with_permissions_ttl(mut self, ttl: Duration) -> Self {
+        self.permissions = PermissionsCache::new(ttl);
+        self
+    }
+
+    /// Set how long prompts sent with [`Event::ask`] wait for an answer. Defaults to
+    /// [`PROMPT_TTL`].
+    pub fn with_prompt_ttl(mut self, ttl: Duration) -> Self {
+        self.prompts = PendingPrompts::new(ttl);
+        self
+    }
+
+    /// Set the initial state for new chats. Each chat starts with its own copy of `state`,
+    /// cloned when the chat is first seen; changes to one chat's state don't affect others.
+    /// Without this, chats start with `S::default()`.
     pub fn with_state(mut self, state: S) -> Self {
         self.state = Some(Arc::new(RwLock::new(state)));
         self
     }
 
-    pub fn with_error_handler<Func, Fut>(mut self, func: Func) -> Self
+    /// Set a function that creates the initial state for new chats. Unlike
+    /// [`Router::with_state`], which clones a template (under a lock) for every new chat,
+    /// each chat's state is freshly constructed, so this is cheaper for large states. Takes
+    /// precedence over [`Router::with_state`] and states attached to handlers.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # let client = Client::new("token".to_string());
+    /// #[derive(BotState, Clone, Default)]
+    /// struct App {
+    ///     history: Vec<String>,
+    /// }
+    ///
+    /// let router = Router::new(client).with_state_fn(|| App {
+    ///     history: Vec::with_capacity(100),
+    /// });
+    /// ```
+    pub fn with_state_fn<Func>(mut self, func: Func) -> Self
+    where
+        Func: Fn() -> S + Send + Sync + 'static,
+    {
+        self.state_fn = Some(Arc::new(func));
+        self
+    }
+
+    /// Set a localizer, which is used by [`Event::tr`] to translate messages into the
+    /// sender's language. The localizer is called with the language code (e.g., "en", "es")
+    /// and the message key. If the sender has no `language_code`, "en" is used.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # let client = Client::new("token".to_string());
+    /// let router = Router::<()>::new(client).with_localizer(|lang, key| match (lang, key) {
+    ///     ("es", "hello") => "¡Hola!".into(),
+    ///     (_, "hello") => "Hello!".into(),
+    ///     (_, key) => key.into(),
+    /// });
+    /// ```
+    pub fn with_localizer<Func>(mut self, func: Func) -> Self
+    where
+        Func: Fn(&str, &str) -> String + Send + Sync + 'static,
+    {
+        self.localizer = Some(Arc::new(func));
+        self
+    }
+
+    /// Set the error handler, which is called when a handler returns an error. The error
+    /// handler returns an [`ErrorAction`] (or `()`, which is treated as `ErrorAction::Silent`)
+    /// telling the router whether to reply to the chat.
+    pub fn with_error_handler<Func, Fut, R>(mut self, func: Func) -> Self
     where
         Func: Send + Sync + 'static + Fn(Arc<API>, i64, State<S>, anyhow::Error) -> Fut,
+        Fut: Send + 'static + Future<Output = R>,
+        R: Into<ErrorAction>,
+    {
+        let func = Arc::new(func);
+        self.error_handler = Arc::new(Box::new(move |a, b, c, d| {
+            let func = Arc::clone(&func);
+            Box::pin(async move { func(a, b, c, d).await.into() })
+        }));
+        self
+    }
+
+    /// Set a hook that's called once for every update, after dispatch completes, with the
+    /// final action. The action is the one returned by the last handler that ran (the one
+    /// that stopped dispatch, or the last one that continued it), or `None` if no handler
+    /// matched or the last handler failed. The event is the one passed to that handler
+    /// (see [`Event::matched_route`]).
+    ///
+    /// Unlike handlers, the hook always runs, which makes it a good place for audit
+    /// logging. It runs after the action has been carried out, and after the error handler
+    /// if a handler failed.
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # let client = Client::new("token".to_string());
+    /// let router = Router::<()>::new(client).with_post_hook(|e, action| async move {
+    ///     log::info!(
+    ///         "chat {:?}: handled by {:?}: {:?}",
+    ///         e.update.chat_id().ok(),
+    ///         e.matched_route(),
+    ///         action
+    ///     );
+    /// });
+    /// ```
+    pub fn with_post_hook<Func, Fut>(mut self, func: Func) -> Self
+    where
+        Func: Send + Sync + 'static + Fn(Event, Option<Action>) -> Fut,
         Fut: Send + 'static + Future<Output = ()>,
     {
-        self.error_handler = Arc::new(Box::new(move |a, b, c, d| Box::pin(func(a, b, c, d))));
+        self.post_hook = Some(Arc::new(move |e, action| Box::pin(func(e, action))));
         self
     }
 
@@ -322,112 +1072,551 @@ impl<S: BotState> Router<S> {
         }
 
         // Note that Route::Default gets converted to Route::Any(Matcher::Any)
+        let route = Route::any(&r);
         self.init_handlers
             .as_mut()
-            .expect("Can't call add_chat_route after start()")
-            .entry(Route::any(&r))
-            .or_default()
+            .expect("Can't call add_route after start() or dispatch()")
+            .entry(discriminant(&route))
+            .or_insert_with(|| (route, vec![]))
+            .1
             .push((r.into(), h));
 
         self
     }
 
+    /// Add a handler for the bot command `/command`, and add the command to the list returned
+    /// by [`Router::commands`]. Use [`Router::set_my_commands`] to publish the list to Telegram,
+    /// so the command menu stays in sync with the registered handlers. See also [`commands!`].
+    pub fn add_command(
+        &mut self,
+        command: impl Into<String>,
+        description: impl Into<String>,
+        h: impl Into<Box<dyn BotHandler<S>>>,
+    ) -> &mut Self {
+        let command = command.into();
+        self.add_route(Route::Message(Matcher::BotCommand(command.clone())), h);
+        self.commands.push(BotCommand {
+            command,
+            description: description.into(),
+        });
+        self
+    }
+
+    /// Returns the commands registered with [`Router::add_command`], in registration order.
+    pub fn commands(&self) -> &[BotCommand] {
+        &self.commands
+    }
+
+    /// Publish the commands registered with [`Router::add_command`] to Telegram via
+    /// `setMyCommands`, for every account served by this router.
+    pub async fn set_my_commands(&self) -> anyhow::Result<bool> {
+        let req = SetMyCommandsRequest {
+            commands: self.commands.clone(),
+            ..Default::default()
+        };
+
+        let mut ok = true;
+        for api in self.apis() {
+            ok &= api.set_my_commands(&req).await?;
+        }
+        Ok(ok)
+    }
+
+    /// Returns the list of update types to request from Telegram, based on the registered
+    /// routes. If a `Route::Default` or `Route::Any` handler is installed, this returns an
+    /// empty list, which tells Telegram to send all update types (except `chat_member`).
+    /// If a `Route::ChatMember` handler is also installed, or extra update types were added
+    /// with [`Router::with_extra_update_types`], all the update types the router handles
+    /// are listed explicitly instead, since these must be requested by name.
+    pub fn allowed_updates(&self) -> Vec<String> {
+        let routes: Vec<Route> = match self.init_handlers {
+            Some(ref handlers) => handlers.values().map(|(r, _)| r.clone()).collect(),
+            None => self
+                .handlers
+                .try_read()
+                .map(|handlers| handlers.values().map(|(r, _)| r.clone()).collect())
+                .unwrap_or_default(),
+        };
+
+        let mut allowed_updates: Vec<String> = if routes.iter().any(|r| r.update_type().is_none()) {
+            let chat_member = routes.iter().any(|r| matches!(r, Route::ChatMember(_)));
+            if !chat_member && self.extra_update_types.is_empty() {
+                return vec![];
+            }
+            UPDATE_TYPES.iter().map(|t| t.to_string()).collect()
+        } else {
+            routes
+                .iter()
+                .filter_map(|r| r.update_type())
+                .map(String::from)
+                .collect()
+        };
+
+        allowed_updates.extend(self.extra_update_types.iter().cloned());
+        allowed_updates.sort();
+        allowed_updates.dedup();
+        allowed_updates
+    }
+
+    /// Returns the number of chats with additional states (see [`Event::state`]). States are
+    /// only kept for chats whose handlers used them.
+    pub fn stateful_chats(&self) -> usize {
+        self.chat_states.chat_count()
+    }
+
+    /// Returns a receiver for the health of the poll loop, which is updated after every
+    /// `getUpdates` call. Use this for liveness or readiness probes, e.g., to report
+    /// not-ready when Telegram has been unreachable for a while:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # let client = Client::new("token".to_string());
+    /// let router = Router::<()>::new(client);
+    /// let health = router.health();
+    ///
+    /// // Later, in your readiness probe:
+    /// let ready = health.borrow().consecutive_failures < 3;
+    /// ```
+    ///
+    /// This is the health of the router's first account. Use [`Router::account_health`]
+    /// for the others.
+    pub fn health(&self) -> watch::Receiver<PollHealth> {
+        self.health[0].subscribe()
+    }
+
+    /// Returns a receiver for the health of the poll loop of `account` (see
+    /// [`Router::with_account`]), or `None` if there's no such account.
+    pub fn account_health(&self, account: usize) -> Option<watch::Receiver<PollHealth>> {
+        self.health.get(account).map(|health| health.subscribe())
+    }
+
+    /// Returns a notifier that's notified when the router exits, and a sender to signal it
+    /// to shut down. See also [`Router::spawn`].
     pub fn shutdown(&self) -> (Arc<Notify>, Arc<mpsc::Sender<()>>) {
         (Arc::clone(&self.shutdown), Arc::clone(&self.shutdown_tx))
     }
 
+    /// Start the router in a background task, and return a [`RouterHandle`] to it. Unlike
+    /// [`Router::start`], this consumes the router, and the handle can be used to call the
+    /// API and shut down the router:
+    ///
+    /// ```no_run
+    /// # use mobot::*;
+    /// # async fn run() -> anyhow::Result<()> {
+    /// let mut router = Router::<()>::new(Client::new("token".to_string()));
+    /// router.add_route(Route::Default, |_: Event, _: State<()>| async move {
+    ///     Ok(Action::ReplyText("hello".into()))
+    /// });
+    ///
+    /// let handle = router.spawn();
+    /// handle.api.get_me().await?;
+    /// handle.shutdown().await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn(mut self) -> RouterHandle {
+        let api = Arc::clone(&self.api);
+        let health = self
+            .health
+            .iter()
+            .map(|health| health.subscribe())
+            .collect();
+        let shutdown_tx = Arc::clone(&self.shutdown_tx);
+
+        RouterHandle {
+            api,
+            health,
+            shutdown_tx,
+            task: tokio::spawn(async move { self.start().await }),
+        }
+    }
+
     /// Start the router. This will block forever.
+    ///
+    /// The updates of a chat within a batch are handled one at a time, in `update_id` order
+    /// (see [`Router::with_sorted_updates`]). Each account (see [`Router::with_account`]) is
+    /// polled concurrently.
     pub async fn start(&mut self) {
-        let mut last_update_id = 0;
+        let options = PollOptions {
+            timeout_s: self.timeout_s,
+            poll_limit: self.poll_limit,
+            sort_updates: self.sort_updates,
+            allowed_updates: self.allowed_updates(),
+        };
+        debug!("Requesting update types: {:?}", options.allowed_updates);
+
+        self.init_routes();
+
+        if self.delete_webhook {
+            for api in self.apis() {
+                if let Err(err) = api.delete_webhook(false).await {
+                    error!("Error deleting webhook: {}", err);
+                }
+            }
+        }
 
-        // Move chat handlers from init_chat_handlers to chat_handlers so it can be passed on
-        // to other tasks.
-        self.handlers = Arc::new(RwLock::new(self.init_handlers.take().unwrap()));
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let polls = futures::future::join_all((0..=self.accounts.len()).map(|account| {
+            Self::poll(
+                self.update_context(account),
+                options.clone(),
+                Arc::clone(&self.health[account]),
+                stop_rx.clone(),
+            )
+        }));
 
-        loop {
-            if self.shutdown_rx.try_recv().is_ok() {
+        let shutdown_rx = &mut self.shutdown_rx;
+        tokio::join!(polls, async move {
+            if shutdown_rx.recv().await.is_some() {
                 info!("Received shutdown signal");
-                break;
             }
+            stop_tx.send_replace(true);
+        });
+
+        self.cancel_jobs.send_replace(true);
+        self.shutdown.notify_waiters();
+    }
+
+    /// Poll `getUpdates` for the account in `context`, and handle the updates of each chat
+    /// in its own task, until `stop` is set. A poll that's in flight when `stop` is set is abandoned;
+    /// its updates weren't confirmed (by the offset of the next poll), so Telegram sends
+    /// them again the next time the bot starts.
+    ///
+    /// If a batch of updates can't be parsed [`MAX_MALFORMED_POLLS`] times in a row, updates
+    /// are fetched one at a time until the one that can't be parsed is found, and skipped.
+    /// Otherwise, a single bad update would stop the bot from receiving any more updates.
+    async fn poll(
+        context: UpdateContext<S>,
+        options: PollOptions,
+        health: Arc<watch::Sender<PollHealth>>,
+        mut stop: watch::Receiver<bool>,
+    ) {
+        let mut last_update_id = 0;
+        let mut poll_backoff = MIN_POLL_BACKOFF;
+        let mut malformed_polls = 0;
+        let mut conflict_reported = false;
+
+        while !*stop.borrow() {
+            let probing = malformed_polls >= MAX_MALFORMED_POLLS;
 
             debug!(
-                "Polling /getUpdates with last_update_id = {} timeout = {}s",
-                last_update_id, self.timeout_s
+                "Polling /getUpdates for account {} with last_update_id = {} timeout = {}s",
+                context.account, last_update_id, options.timeout_s
             );
 
-            let updates = match self
-                .api
-                .get_updates(
-                    &GetUpdatesRequest::new()
-                        .with_timeout(self.timeout_s)
-                        .with_offset(last_update_id + 1),
-                )
-                .await
-            {
-                Ok(updates) => updates,
+            let mut req = GetUpdatesRequest::new()
+                .with_timeout(options.timeout_s)
+                .with_offset(last_update_id + 1)
+                .with_allowed_updates(options.allowed_updates.clone());
+            if probing {
+                req = req.with_limit(1);
+            } else if let Some(limit) = options.poll_limit {
+                req = req.with_limit(limit);
+            }
+
+            let result = tokio::select! {
+                result = context.api.get_updates(&req) => result,
+                _ = stop.wait_for(|stop| *stop) => break,
+            };
+
+            let mut updates = match result {
+                Ok(updates) => {
+                    // Keep probing until the bad update is found, or there are no more.
+                    if !probing || updates.is_empty() {
+                        malformed_polls = 0;
+                    }
+                    conflict_reported = false;
+                    poll_backoff = MIN_POLL_BACKOFF;
+                    health.send_replace(PollHealth {
+                        last_success: Some(Instant::now()),
+                        consecutive_failures: 0,
+                    });
+                    updates
+                }
                 Err(err) => {
-                    error!("Error polling /getUpdates: {}", err);
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    health.send_modify(|health| health.consecutive_failures += 1);
+
+                    let malformed = matches!(
+                        err.downcast_ref::<api::ApiError>(),
+                        Some(api::ApiError::Malformed(_))
+                    );
+                    if malformed {
+                        malformed_polls += 1;
+                        if probing {
+                            // The next update on its own can't be parsed, so skip it.
+                            match Self::next_update_id(&context.api, last_update_id + 1).await {
+                                Ok(update_id) => {
+                                    error!(
+                                        "Skipping update {} that can't be parsed: {}",
+                                        update_id, err
+                                    );
+                                    last_update_id = update_id;
+                                    malformed_polls = 0;
+                                    continue;
+                                }
+                                Err(err) => error!("Error finding update to skip: {}", err),
+                            }
+                        } else if malformed_polls >= MAX_MALFORMED_POLLS {
+                            warn!(
+                                "Error parsing /getUpdates (looking for bad update): {}",
+                                err
+                            );
+                            continue;
+                        }
+                    }
+
+                    // Conflicts don't go away by retrying, so explain what's going on (once).
+                    let conflict = matches!(
+                        err.downcast_ref::<api::ApiError>(),
+                        Some(api::ApiError::Conflict(_))
+                    );
+                    if conflict && !conflict_reported {
+                        error!(
+                            "Telegram rejected /getUpdates with 409 (Conflict). Either another \
+                             instance of this bot is polling with the same token, or a webhook \
+                             is set (use Router::with_delete_webhook to delete it on startup)."
+                        );
+                        conflict_reported = true;
+                    }
+
+                    // Back off exponentially, with jitter so that many bot instances don't
+                    // retry in lockstep after a Telegram outage.
+                    let jitter = poll_backoff.mul_f64(rand::random::<f64>() * 0.5);
+                    error!(
+                        "Error polling /getUpdates (retrying in {:?}): {}",
+                        poll_backoff + jitter,
+                        err
+                    );
+                    tokio::select! {
+                        _ = tokio::time::sleep(poll_backoff + jitter) => {}
+                        _ = stop.wait_for(|stop| *stop) => break,
+                    }
+                    poll_backoff = min(poll_backoff * 2, MAX_POLL_BACKOFF);
                     continue;
                 }
             };
 
+            if options.sort_updates {
+                updates.sort_by_key(|update| update.update_id);
+            }
+
+            // Handle the updates of each chat in order, so that a handler never sees an
+            // update before the ones that came before it in the same chat.
+            let mut chats: HashMap<Option<i64>, Vec<api::Update>> = HashMap::new();
             for update in updates {
                 debug!("Received update: {:#?}", update);
                 last_update_id = max(last_update_id, update.update_id);
 
-                let chat_update = update.clone();
-                let handlers = Arc::clone(&self.handlers);
-                let error_handler = Arc::clone(&self.error_handler);
-                let handler_state = Arc::clone(&self.handler_state);
-                let api = Arc::clone(&self.api);
+                let chat_id = get_update_parts(&update)
+                    .ok()
+                    .and_then(|(chat_id, _)| chat_id);
+                chats.entry(chat_id).or_default().push(update);
+            }
+
+            for updates in chats.into_values() {
+                let context = context.clone();
                 tokio::spawn(async move {
-                    if let Err(err) = Self::handle_chat_update(
-                        api,
-                        handler_state,
-                        handlers,
-                        error_handler,
-                        chat_update,
-                    )
-                    .await
-                    {
-                        error!("Error handling chat update: {}", err);
+                    for update in updates {
+                        if let Err(err) = Self::handle_chat_update(context.clone(), update).await {
+                            error!("Error handling chat update: {}", err);
+                        }
                     }
                 });
             }
         }
+    }
 
-        self.shutdown.notify_waiters();
+    /// Returns the ID of the next pending update at `offset`, without parsing the update.
+    async fn next_update_id(api: &API, offset: i64) -> anyhow::Result<i64> {
+        let updates = api
+            .call_raw(
+                "getUpdates",
+                serde_json::json!({"offset": offset, "limit": 1, "timeout": 0}),
+            )
+            .await?;
+
+        updates[0]["update_id"]
+            .as_i64()
+            .ok_or(anyhow!("no pending update at offset {}", offset))
     }
 
-    async fn handle_chat_update(
+    /// Run the handlers for a single update, and return the actions they returned. This
+    /// doesn't poll Telegram, so it's useful for testing handlers deterministically. Any
+    /// replies are still sent via the router's API client, so use a fake one (e.g.,
+    /// [`FakeAPI`](crate::fake::FakeAPI)) in tests.
+    ///
+    /// Routes can't be added after the first dispatch.
+    pub async fn dispatch(&mut self, update: api::Update) -> anyhow::Result<Vec<Action>> {
+        self.init_routes();
+        Self::handle_chat_update(self.update_context(0), update).await
+    }
+
+    /// Move the handlers added with [`Router::add_route`] to where they can be shared with
+    /// the tasks handling updates. Once this is done, no more routes can be added.
+    fn init_routes(&mut self) {
+        if let Some(handlers) = self.init_handlers.take() {
+            self.handlers = Arc::new(RwLock::new(handlers));
+        }
+    }
+
+    /// Returns the context for handling updates received on `account`.
+    fn update_context(&self, account: usize) -> UpdateContext<S> {
+        UpdateContext {
+            api: Arc::clone(&self.apis()[account]),
+            account,
+            handler_state: Arc::clone(&self.handler_state),
+            state_fn: self.state_fn.clone(),
+            chat_states: self.chat_states.clone(),
+            localizer: self.localizer.clone(),
+            handlers: Arc::clone(&self.handlers),
+            error_handler: Arc::clone(&self.error_handler),
+            post_hook: self.post_hook.clone(),
+            cancel_jobs: self.cancel_jobs.subscribe(),
+            match_captions: self.match_captions,
+            permissions: self.permissions.clone(),
+            prompts: self.prompts.clone(),
+            dedup: self.dedup.clone(),
+        }
+    }
+
+    /// Returns a spawner that runs background jobs for the chat `chat_id`, passing any
+    /// errors to the error handler. Jobs are cancelled when `cancel_jobs` is set.
+    fn job_spawner(
         api: Arc<API>,
-        handler_state: Arc<RwLock<HashMap<i64, State<S>>>>,
-        handlers: Arw<HandlerMap<S>>,
         error_handler: Arc<ErrorHandler<S>>,
+        chat_id: Option<i64>,
+        state: State<S>,
+        cancel_jobs: watch::Receiver<bool>,
+    ) -> Spawner {
+        Arc::new(move |job| {
+            let api = Arc::clone(&api);
+            let error_handler = Arc::clone(&error_handler);
+            let state = state.clone();
+            let mut cancel_jobs = cancel_jobs.clone();
+
+            tokio::spawn(async move {
+                tokio::select! {
+                    result = job => {
+                        if let Err(err) = result {
+                            handle_error(api, &error_handler, chat_id, state, err).await;
+                        }
+                    }
+                    _ = async { cancel_jobs.wait_for(|cancelled| *cancelled).await.is_ok() } => {
+                        debug!("Cancelled background job for chat {:?}", chat_id);
+                    }
+                }
+            });
+        })
+    }
+
+    /// Run the handlers for a single update, and return the actions they returned, in
+    /// order. The actions are also carried out, just like they are in [`Router::start`].
+    /// Once done, the post hook is called with the outcome.
+    async fn handle_chat_update(
+        context: UpdateContext<S>,
         update: api::Update,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Vec<Action>> {
+        if let Some(ref dedup) = context.dedup {
+            if !dedup.insert(context.account, update.update_id) {
+                debug!("Dropping duplicate update {}", update.update_id);
+                return Ok(vec![]);
+            }
+        }
+
+        // The future running the handlers is large, so it's boxed to keep it off the
+        // caller's stack, e.g., a test thread calling `dispatch`.
+        let mut outcome = Outcome::default();
+        let Some(post_hook) = context.post_hook.clone() else {
+            return Box::pin(Self::run_handlers(context, update, &mut outcome)).await;
+        };
+
+        let (api, account) = (Arc::clone(&context.api), context.account);
+        let result = Box::pin(Self::run_handlers(context, update.clone(), &mut outcome)).await;
+        let event = outcome
+            .event
+            .unwrap_or_else(|| Event::new(api, update.into()).with_account(account));
+        post_hook(event, outcome.action).await;
+        result
+    }
+
+    /// Run the handlers for a single update, recording the last handler's event and action
+    /// in `outcome`.
+    async fn run_handlers(
+        context: UpdateContext<S>,
+        update: api::Update,
+        outcome: &mut Outcome,
+    ) -> anyhow::Result<Vec<Action>> {
+        let UpdateContext {
+            api,
+            account,
+            handler_state,
+            state_fn,
+            chat_states,
+            localizer,
+            handlers,
+            error_handler,
+            cancel_jobs,
+            match_captions,
+            permissions,
+            prompts,
+            ..
+        } = context;
         let (chat_id, route) = get_update_parts(&update)?;
+
+        // Keep the bot's own permissions up to date when its status changes.
+        if let (Some(ref m), Some(chat_id)) = (&update.my_chat_member, chat_id) {
+            permissions.insert(account, chat_id, m.new_chat_member.clone());
+        }
         let message_event: Update = update.clone().into();
+        let raw_update = Arc::new(update.clone());
+
+        // Replies to messages from business accounts must be sent on the same connection.
+        let business_connection_id = message_event.business_connection_id().map(String::from);
+
+        // Text replies to messages in forum topics are sent to the same topic.
+        let message_thread_id = message_event.message_thread_id();
+        // Updates without a chat (e.g., `poll_answer`) get no per-chat state.
+        let states = match chat_id {
+            Some(chat_id) => chat_states.for_chat(account, chat_id),
+            None => StateMap::new(),
+        };
+        let reply_chat_id = || chat_id.ok_or_else(|| anyhow!("Update has no chat to reply to"));
+
+        // Answers to a pending prompt go to its `Matcher::ReplyTo` handlers before any others.
+        let answered = update
+            .message
+            .as_ref()
+            .and_then(|m| m.reply_to_message.as_ref())
+            .zip(chat_id)
+            .and_then(|(prompt, chat_id)| prompts.take(account, chat_id, prompt.message_id));
+        let is_answer = |matcher: &Matcher| match matcher {
+            Matcher::ReplyTo(name) => answered.as_deref() == Some(name.as_str()),
+            _ => false,
+        };
 
         let mut handler_groups = vec![];
         let h = handlers.read().await;
 
         // Check to see if there's a handler stack for this message's route.
-        if let Some(handlers) = h.get(&route) {
-            handler_groups.push(handlers);
+        if let Some((group_route, handlers)) = h.get(&discriminant(&route)) {
+            handler_groups.push((group_route, handlers));
+        } else if matches!(route, Route::Unknown(_)) {
+            // Unknown updates aren't passed to the default handler, which most likely
+            // can't handle them, and they have no chat to report errors to.
+            anyhow::bail!("Unknown update type");
         } else {
             // Check to see if there's a default handler.
-            if let Some(handlers) = h.get(&Route::Any(Matcher::Any)) {
-                handler_groups.push(handlers);
+            if let Some((group_route, handlers)) = h.get(&discriminant(&Route::Any(Matcher::Any))) {
+                handler_groups.push((group_route, handlers));
             }
         }
 
         if handler_groups.is_empty() {
             // No default handler installed, so we can't do anything with this message. Call
             // the error handler.
-            error_handler(
+            handle_error(
                 Arc::clone(&api),
+                &error_handler,
                 chat_id,
                 State::default(),
                 anyhow!(format!("No handlers installed for route: #{:?}", route)),
@@ -436,41 +1625,87 @@ impl<S: BotState> Router<S> {
         }
 
         // Go through each handler in the stack and see if it matches the update.
-        'top: for handler_group in handler_groups {
-            for matcher_handler in handler_group {
-                let (matcher, handler) = matcher_handler;
-                if !route.with(matcher).match_update(&update) {
+        let mut actions = vec![];
+        let last_sent_message_id = Arc::new(std::sync::Mutex::new(None));
+        'top: for (group_route, handler_group) in handler_groups {
+            let (answer_handlers, other_handlers): (Vec<_>, Vec<_>) = handler_group
+                .iter()
+                .partition(|(matcher, _)| is_answer(matcher));
+            for (matcher, handler) in answer_handlers.into_iter().chain(other_handlers) {
+                // `Route::Default` handles every event, including messages without text
+                // (e.g., stickers), which text matchers never match.
+                let handles_all =
+                    matches!(group_route, Route::Any(_)) && matches!(matcher, Matcher::Any);
+                if !is_answer(matcher)
+                    && !handles_all
+                    && !route.with(matcher).matches(&update, match_captions)
+                {
                     // Route doesn't match, so skip this handler.
                     continue;
                 }
 
-                // If we don't have a state for this chat, create one by cloning
-                // the initial state stored in the handler.
-                let state = {
-                    let mut state = handler_state.write().await;
-                    state
-                        .entry(chat_id)
-                        .or_insert(State::from(handler.get_state()).await)
-                        .clone()
+                // If we don't have a state for this chat, create one with the state
+                // function, or by cloning the initial state stored in the handler.
+                // Zero-sized states (e.g., `()` for stateless bots) carry no data, so
+                // don't keep one around per chat.
+                let new_state = || async {
+                    match state_fn {
+                        Some(ref state_fn) => State::new(state_fn()),
+                        None => State::from(handler.get_state()).await,
+                    }
+                };
+                let state = match chat_id {
+                    Some(chat_id) if std::mem::size_of::<S>() != 0 => {
+                        let mut handler_state = handler_state.write().await;
+                        match handler_state.get(&(account, chat_id)) {
+                            Some(state) => state.clone(),
+                            None => {
+                                let state = new_state().await;
+                                handler_state.insert((account, chat_id), state.clone());
+                                state
+                            }
+                        }
+                    }
+                    _ => new_state().await,
                 };
 
-                // Run the handler
-                let reply = handler
-                    .run(
-                        Event::new(Arc::clone(&api), message_event.clone()),
+                let mut event = Event::new(Arc::clone(&api), message_event.clone())
+                    .with_account(account)
+                    .with_matched_route(group_route.with(matcher))
+                    .with_prev_action(actions.last().cloned())
+                    .with_last_sent_message_id(Arc::clone(&last_sent_message_id))
+                    .with_permissions(permissions.clone())
+                    .with_prompts(prompts.clone())
+                    .with_raw_update(Arc::clone(&raw_update))
+                    .with_states(states.clone())
+                    .with_spawner(Self::job_spawner(
+                        Arc::clone(&api),
+                        Arc::clone(&error_handler),
+                        chat_id,
                         state.clone(),
-                    )
-                    .await;
+                        cancel_jobs.clone(),
+                    ));
+                if let Some(ref localizer) = localizer {
+                    event = event.with_localizer(Arc::clone(localizer));
+                }
+
+                // Run the handler
+                outcome.event = Some(event.clone());
+                let reply = handler.run(event, state.clone()).await;
 
                 // Handler failed, run the default error handler
                 if let Err(err) = reply {
-                    error_handler(Arc::clone(&api), chat_id, state, err).await;
-                    return Ok(());
+                    outcome.action = None;
+                    handle_error(Arc::clone(&api), &error_handler, chat_id, state, err).await;
+                    return Ok(actions);
                 }
 
-                match reply.unwrap() {
+                let action = reply.unwrap();
+                outcome.action = Some(action.clone());
+                actions.push(action.clone());
+                match action {
                     // Handler returned Next, run the next handler in the stack.
-                    Action::Next => {}
+                    Action::Next | Action::NextWith(_) => {}
 
                     // Handler returned Done, stop running handlers.
                     Action::Done => {
@@ -480,37 +1715,154 @@ impl<S: BotState> Router<S> {
                     // Handler returned Reply, send the message to the chat, and stop running handlers.
                     Action::ReplyText(text) => {
                         api.send_message(&SendMessageRequest {
-                            chat_id,
+                            chat_id: reply_chat_id()?.into(),
                             text,
+                            message_thread_id,
+                            business_connection_id: business_connection_id.clone(),
                             ..Default::default()
                         })
                         .await?;
                         break 'top;
                     }
 
+                    // Handler returned AckCallback, answer the callback query, and run the next
+                    // handler in the stack.
+                    Action::AckCallback(text) => {
+                        api.answer_callback_query(&api::AnswerCallbackQueryRequest {
+                            callback_query_id: message_event.query_id()?.to_string(),
+                            text,
+                            ..Default::default()
+                        })
+                        .await?;
+                    }
+
+                    // Handler returned ReplyAndContinue, send the message to the chat, and run
+                    // the next handler in the stack.
+                    Action::ReplyAndContinue(text) => {
+                        let message = api
+                            .send_message(&SendMessageRequest {
+                                chat_id: reply_chat_id()?.into(),
+                                text,
+                                message_thread_id,
+                                business_connection_id: business_connection_id.clone(),
+                                ..Default::default()
+                            })
+                            .await?;
+                        *last_sent_message_id.lock().unwrap() = Some(message.message_id);
+                    }
+
                     // Handler returned ReplyMarkdown, send the MarkDown message to the chat, and
                     // stop running handlers.
                     Action::ReplyMarkdown(text) => {
                         api.send_message(&SendMessageRequest {
-                            chat_id,
+                            chat_id: reply_chat_id()?.into(),
                             text,
                             parse_mode: Some(api::ParseMode::MarkdownV2),
+                            message_thread_id,
+                            business_connection_id: business_connection_id.clone(),
                             ..Default::default()
                         })
                         .await?;
                         break 'top;
                     }
 
+                    // Handler returned Reply, send the text to the chat with its parse mode, and
+                    // stop running handlers.
+                    Action::Reply(text) => {
+                        api.send_message(&SendMessageRequest {
+                            parse_mode: Some(text.clone().into()),
+                            message_thread_id,
+                            business_connection_id: business_connection_id.clone(),
+                            ..SendMessageRequest::new(reply_chat_id()?, text)
+                        })
+                        .await?;
+                        break 'top;
+                    }
+
+                    // Handler returned ReplyWith, send the message to the chat, and stop running
+                    // handlers.
+                    Action::ReplyWith(req) => {
+                        api.send_message(&SendMessageRequest {
+                            chat_id: reply_chat_id()?.into(),
+                            message_thread_id: req.message_thread_id.or(message_thread_id),
+                            business_connection_id: req
+                                .business_connection_id
+                                .clone()
+                                .or_else(|| business_connection_id.clone()),
+                            ..*req
+                        })
+                        .await?;
+                        break 'top;
+                    }
+
                     // Handler returned ReplySticker, send the sticker to the chat, and stop running
                     // handlers.
                     Action::ReplySticker(sticker) => {
-                        api.send_sticker(&SendStickerRequest::new(chat_id, sticker))
-                            .await?;
+                        api.send_sticker(&SendStickerRequest {
+                            business_connection_id: business_connection_id.clone(),
+                            ..SendStickerRequest::new(reply_chat_id()?, sticker)
+                        })
+                        .await?;
+                        break 'top;
+                    }
+
+                    // Handler returned Forward, forward the triggering message, and stop running
+                    // handlers.
+                    Action::Forward { to_chat_id } => {
+                        api.forward_message(&api::ForwardMessageRequest::new(
+                            to_chat_id,
+                            reply_chat_id()?,
+                            message_event.message_id()?,
+                        ))
+                        .await?;
+                        break 'top;
+                    }
+
+                    // Handler returned Delete, delete the triggering message, and stop running
+                    // handlers.
+                    Action::Delete => {
+                        api.delete_message(&api::DeleteMessageRequest::new(
+                            reply_chat_id()?,
+                            message_event.message_id()?,
+                        ))
+                        .await?;
                         break 'top;
                     }
                 }
             }
         }
-        Ok(())
+        Ok(actions)
     }
 }
+
+/// Register many bot command handlers at once with [`Router::add_command`]. Each entry is
+/// `"command": "description" => handler`. The registered commands can then be published to
+/// the Telegram command menu with [`Router::set_my_commands`].
+///
+/// ```no_run
+/// # use mobot::*;
+/// async fn start(_: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+///     Ok(Action::ReplyText("Welcome!".into()))
+/// }
+///
+/// async fn help(_: Event, _: State<()>) -> Result<Action, anyhow::Error> {
+///     Ok(Action::ReplyText("Try /start".into()))
+/// }
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let mut router = Router::<()>::new(Client::new("token".to_string()));
+/// commands!(router,
+///     "start": "Start the bot" => start,
+///     "help": "Show help" => help,
+/// );
+/// router.set_my_commands().await?;
+/// router.start().await;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! commands {
+    ($router:expr, $($command:literal : $description:literal => $handler:expr),+ $(,)?) => {
+        $( $router.add_command($command, $description, $handler); )+
+    };
+}
@@ -8,6 +8,7 @@ use crate::api;
 pub enum Text {
     Plain(String),
     Markdown(String),
+    Html(String),
 }
 
 impl From<Text> for String {
@@ -15,6 +16,7 @@ impl From<Text> for String {
         match text {
             Text::Plain(text) => text,
             Text::Markdown(text) => text,
+            Text::Html(text) => text,
         }
     }
 }
@@ -24,6 +26,7 @@ impl From<Text> for api::ParseMode {
         match text {
             Text::Plain(_) => api::ParseMode::Text,
             Text::Markdown(_) => api::ParseMode::MarkdownV2,
+            Text::Html(_) => api::ParseMode::HTML,
         }
     }
 }
@@ -39,3 +42,221 @@ impl From<String> for Text {
         Text::Plain(text)
     }
 }
+
+impl Text {
+    /// Returns a new [`TextBuilder`], for composing formatted messages without
+    /// having to escape them by hand.
+    pub fn builder() -> TextBuilder {
+        TextBuilder::new()
+    }
+
+    /// Returns `text` escaped for MarkdownV2, so it renders literally.
+    ///
+    /// ```
+    /// # use mobot::Text;
+    /// assert_eq!(Text::escape_markdown_v2("1 + 1 = 2.").to_string(), r"1 \+ 1 \= 2\.");
+    /// ```
+    pub fn escape_markdown_v2(text: &str) -> Text {
+        Text::Markdown(api::escape_md(text))
+    }
+}
+
+/// A segment of a [`TextBuilder`].
+#[derive(Clone, Debug)]
+enum Segment {
+    Plain(String),
+    Bold(TextBuilder),
+    Italic(TextBuilder),
+    Code(String),
+    Spoiler(TextBuilder),
+    CustomEmoji {
+        placeholder: String,
+        custom_emoji_id: String,
+    },
+}
+
+/// `TextBuilder` composes formatted text from segments, and renders it as either
+/// MarkdownV2 or HTML, escaping the contents of each segment as needed.
+///
+/// ```
+/// # use mobot::Text;
+/// let text = Text::builder()
+///     .text("Ending: ")
+///     .spoiler(Text::builder().text("they ").bold("all"))
+///     .text(" live!");
+///
+/// assert_eq!(
+///     text.to_html().to_string(),
+///     r#"Ending: <span class="tg-spoiler">they <b>all</b></span> live!"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TextBuilder {
+    segments: Vec<Segment>,
+}
+
+impl TextBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append plain text.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.segments.push(Segment::Plain(text.into()));
+        self
+    }
+
+    /// Append bold text.
+    pub fn bold(mut self, text: impl Into<TextBuilder>) -> Self {
+        self.segments.push(Segment::Bold(text.into()));
+        self
+    }
+
+    /// Append italic text.
+    pub fn italic(mut self, text: impl Into<TextBuilder>) -> Self {
+        self.segments.push(Segment::Italic(text.into()));
+        self
+    }
+
+    /// Append inline code.
+    pub fn code(mut self, text: impl Into<String>) -> Self {
+        self.segments.push(Segment::Code(text.into()));
+        self
+    }
+
+    /// Append a spoiler, which is hidden until the user taps on it. Useful for
+    /// content warnings.
+    pub fn spoiler(mut self, text: impl Into<TextBuilder>) -> Self {
+        self.segments.push(Segment::Spoiler(text.into()));
+        self
+    }
+
+    /// Append a custom emoji. The `placeholder` must be a regular emoji, and is shown
+    /// by clients that can't display the custom emoji.
+    pub fn custom_emoji(
+        mut self,
+        placeholder: impl Into<String>,
+        custom_emoji_id: impl Into<String>,
+    ) -> Self {
+        self.segments.push(Segment::CustomEmoji {
+            placeholder: placeholder.into(),
+            custom_emoji_id: custom_emoji_id.into(),
+        });
+        self
+    }
+
+    /// Render the text as MarkdownV2.
+    pub fn to_markdown(&self) -> Text {
+        Text::Markdown(self.render_markdown())
+    }
+
+    /// Render the text as HTML.
+    pub fn to_html(&self) -> Text {
+        Text::Html(self.render_html())
+    }
+
+    fn render_markdown(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Plain(text) => api::escape_md(text),
+                Segment::Bold(inner) => format!("*{}*", inner.render_markdown()),
+                Segment::Italic(inner) => format!("_{}_", inner.render_markdown()),
+                Segment::Code(text) => format!("`{}`", api::escape_code(text)),
+                Segment::Spoiler(inner) => format!("||{}||", inner.render_markdown()),
+                Segment::CustomEmoji {
+                    placeholder,
+                    custom_emoji_id,
+                } => format!(
+                    "![{}](tg://emoji?id={})",
+                    api::escape_md(placeholder),
+                    custom_emoji_id
+                ),
+            })
+            .collect()
+    }
+
+    fn render_html(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Plain(text) => api::escape_html(text),
+                Segment::Bold(inner) => format!("<b>{}</b>", inner.render_html()),
+                Segment::Italic(inner) => format!("<i>{}</i>", inner.render_html()),
+                Segment::Code(text) => format!("<code>{}</code>", api::escape_html(text)),
+                Segment::Spoiler(inner) => {
+                    format!(r#"<span class="tg-spoiler">{}</span>"#, inner.render_html())
+                }
+                Segment::CustomEmoji {
+                    placeholder,
+                    custom_emoji_id,
+                } => format!(
+                    r#"<tg-emoji emoji-id="{}">{}</tg-emoji>"#,
+                    api::escape_html(custom_emoji_id),
+                    api::escape_html(placeholder)
+                ),
+            })
+            .collect()
+    }
+}
+
+impl From<&str> for TextBuilder {
+    fn from(text: &str) -> Self {
+        TextBuilder::new().text(text)
+    }
+}
+
+impl From<String> for TextBuilder {
+    fn from(text: String) -> Self {
+        TextBuilder::new().text(text)
+    }
+}
+
+/// Split the arguments of a bot command into words, treating double-quoted strings as a
+/// single argument. For example, `/add "hello world" foo` yields `["hello world", "foo"]`.
+///
+/// The command itself (the first word, if it starts with `/`) is skipped. Within quotes, `\"`
+/// is an escaped quote. An unterminated quote runs to the end of the text.
+pub fn command_args(text: &str) -> Vec<String> {
+    let text = text.trim_start();
+    let args = if text.starts_with('/') {
+        text.split_once(char::is_whitespace)
+            .map_or("", |(_, args)| args)
+    } else {
+        text
+    };
+
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut in_quotes = false;
+    let mut chars = args.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_arg = true;
+            }
+            '\\' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push(chars.next().unwrap());
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_arg {
+                    result.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if in_arg {
+        result.push(current);
+    }
+
+    result
+}
@@ -11,6 +11,11 @@ pub enum Update {
     EditedChannelPost(api::Message),
     CallbackQuery(api::CallbackQuery),
     InlineQuery(api::InlineQuery),
+    BusinessMessage(api::Message),
+    ChatJoinRequest(api::ChatJoinRequest),
+    MyChatMember(api::ChatMemberUpdated),
+    ChatMember(api::ChatMemberUpdated),
+    PreCheckoutQuery(api::PreCheckoutQuery),
     Unknown,
 }
 
@@ -28,25 +33,41 @@ impl From<api::Update> for Update {
             Self::CallbackQuery(c.clone())
         } else if let Some(ref c) = update.inline_query {
             Self::InlineQuery(c.clone())
+        } else if let Some(ref m) = update.business_message {
+            Self::BusinessMessage(m.clone())
+        } else if let Some(ref r) = update.chat_join_request {
+            Self::ChatJoinRequest(r.clone())
+        } else if let Some(ref m) = update.my_chat_member {
+            Self::MyChatMember(m.clone())
+        } else if let Some(ref m) = update.chat_member {
+            Self::ChatMember(m.clone())
+        } else if let Some(ref q) = update.pre_checkout_query {
+            Self::PreCheckoutQuery(q.clone())
         } else {
             Self::Unknown
         }
     }
 }
 
-impl From<Update> for api::Message {
-    fn from(event: Update) -> Self {
+/// Returns the message of the update. Fails for callback queries from inline messages,
+/// which have no message attached, and for updates that aren't about a message.
+impl TryFrom<Update> for api::Message {
+    type Error = anyhow::Error;
+
+    fn try_from(event: Update) -> anyhow::Result<Self> {
         use Update::*;
 
         match event {
-            Message(msg) => msg,
-            EditedMessage(msg) => msg,
-            ChannelPost(msg) => msg,
-            EditedChannelPost(msg) => msg,
-            CallbackQuery(query) => query.message.unwrap(),
-            InlineQuery(_) | Unknown => {
-                panic!("Bad Message::Unknown")
-            }
+            Message(msg) => Ok(msg),
+            EditedMessage(msg) => Ok(msg),
+            ChannelPost(msg) => Ok(msg),
+            EditedChannelPost(msg) => Ok(msg),
+            BusinessMessage(msg) => Ok(msg),
+            CallbackQuery(query) => query.message.ok_or(anyhow!(
+                "CallbackQuery has no message (was it sent from an inline message?)"
+            )),
+            InlineQuery(_) | ChatJoinRequest(_) | MyChatMember(_) | ChatMember(_)
+            | PreCheckoutQuery(_) | Unknown => Err(anyhow!("update has no message: {}", event)),
         }
     }
 }
@@ -61,19 +82,29 @@ impl From<Update> for api::CallbackQuery {
         }
     }
 }
+/// Renders the text of the update. Updates without text (e.g., photos, stickers, or
+/// service messages) are rendered as a placeholder, so this is always safe to log.
 impl fmt::Display for Update {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use Update::*;
         match self {
-            Message(msg) => write!(f, "{}", msg.text.clone().unwrap()),
-            EditedMessage(msg) => write!(f, "{}", msg.text.clone().unwrap()),
-            ChannelPost(msg) => write!(f, "{}", msg.text.clone().unwrap()),
-            EditedChannelPost(msg) => write!(f, "{}", msg.text.clone().unwrap()),
-            CallbackQuery(query) => write!(f, "{}", query.data.clone().unwrap()),
-            InlineQuery(query) => write!(f, "{}", query.query.clone()),
-            Unknown => {
-                panic!("Bad Message::Unknown")
+            Message(msg)
+            | EditedMessage(msg)
+            | ChannelPost(msg)
+            | EditedChannelPost(msg)
+            | BusinessMessage(msg) => {
+                write!(f, "{}", msg.text.as_deref().unwrap_or("<non-text message>"))
             }
+            CallbackQuery(query) => write!(f, "{}", query.data.as_deref().unwrap_or("<no data>")),
+            InlineQuery(query) => write!(f, "{}", query.query),
+            ChatJoinRequest(req) => write!(f, "<join request from {}>", req.from.first_name),
+            MyChatMember(update) | ChatMember(update) => write!(
+                f,
+                "<{} is now {}>",
+                update.new_chat_member.user.first_name, update.new_chat_member.status
+            ),
+            PreCheckoutQuery(query) => write!(f, "{}", query.invoice_payload),
+            Unknown => write!(f, "<unknown update>"),
         }
     }
 }
@@ -119,6 +150,39 @@ impl Update {
         .ok_or(anyhow!("message is not a CallbackQuery"))
     }
 
+    pub fn get_inline_query(&self) -> anyhow::Result<&api::InlineQuery> {
+        match self {
+            Update::InlineQuery(query) => Some(query),
+            _ => None,
+        }
+        .ok_or(anyhow!("message is not an InlineQuery"))
+    }
+
+    pub fn get_chat_join_request(&self) -> anyhow::Result<&api::ChatJoinRequest> {
+        match self {
+            Update::ChatJoinRequest(req) => Some(req),
+            _ => None,
+        }
+        .ok_or(anyhow!("message is not a ChatJoinRequest"))
+    }
+
+    /// Returns the chat member update, for both `MyChatMember` and `ChatMember` updates.
+    pub fn get_chat_member_updated(&self) -> anyhow::Result<&api::ChatMemberUpdated> {
+        match self {
+            Update::MyChatMember(update) | Update::ChatMember(update) => Some(update),
+            _ => None,
+        }
+        .ok_or(anyhow!("message is not a ChatMemberUpdated"))
+    }
+
+    pub fn get_pre_checkout_query(&self) -> anyhow::Result<&api::PreCheckoutQuery> {
+        match self {
+            Update::PreCheckoutQuery(query) => Some(query),
+            _ => None,
+        }
+        .ok_or(anyhow!("message is not a PreCheckoutQuery"))
+    }
+
     pub fn get_message_or_post(&self) -> anyhow::Result<&api::Message> {
         match self {
             Update::Message(msg) => Some(msg),
@@ -154,20 +218,60 @@ impl Update {
             Update::EditedMessage(msg) => Some(msg),
             Update::ChannelPost(msg) => Some(msg),
             Update::EditedChannelPost(msg) => Some(msg),
-            Update::CallbackQuery(query) => Some(query.message.as_ref().unwrap()),
-            Update::InlineQuery(_) | Update::Unknown => None,
+            Update::BusinessMessage(msg) => Some(msg),
+            // Callback queries from inline messages have no message attached, only an
+            // inline_message_id.
+            Update::CallbackQuery(query) => query.message.as_ref(),
+            Update::InlineQuery(_)
+            | Update::ChatJoinRequest(_)
+            | Update::MyChatMember(_)
+            | Update::ChatMember(_)
+            | Update::PreCheckoutQuery(_)
+            | Update::Unknown => None,
         }
         .ok_or(anyhow!("message is not a api::Message"))
     }
 
     pub fn chat_id(&self) -> anyhow::Result<i64> {
-        self.message().map(|msg| msg.chat.id)
+        match self {
+            Update::ChatJoinRequest(req) => Ok(req.chat.id),
+            Update::MyChatMember(update) | Update::ChatMember(update) => Ok(update.chat.id),
+            _ => self.message().map(|msg| msg.chat.id),
+        }
     }
 
     pub fn message_id(&self) -> anyhow::Result<i64> {
         self.message().map(|msg| msg.message_id)
     }
 
+    /// Returns the ID of the inline message that originated a callback query. This is only
+    /// set for callback queries from buttons on messages sent via the bot in inline mode.
+    pub fn inline_message_id(&self) -> anyhow::Result<&str> {
+        self.get_callback_query().and_then(|query| {
+            query
+                .inline_message_id
+                .as_deref()
+                .ok_or(anyhow!("callback query has no inline_message_id"))
+        })
+    }
+
+    /// Returns the ID of the business connection the message was received on, if any.
+    /// Replies to the message must be sent on the same connection.
+    pub fn business_connection_id(&self) -> Option<&str> {
+        self.message()
+            .ok()
+            .and_then(|msg| msg.business_connection_id.as_deref())
+    }
+
+    /// Returns the forum topic the message was posted in, if any. Replies to the message
+    /// should be sent to the same topic, otherwise they land in the "General" topic.
+    pub fn message_thread_id(&self) -> Option<i64> {
+        self.message()
+            .ok()
+            .filter(|msg| msg.is_topic_message == Some(true))
+            .and_then(|msg| msg.message_thread_id)
+    }
+
     pub fn query_id(&self) -> anyhow::Result<&str> {
         self.get_callback_query().map(|query| query.id.as_str())
     }
@@ -181,11 +285,26 @@ impl Update {
         })
     }
 
+    /// Returns the arguments of a bot command, respecting double quotes. See
+    /// [`text::command_args`](crate::text::command_args).
+    pub fn command_args(&self) -> anyhow::Result<Vec<String>> {
+        self.text().map(crate::text::command_args)
+    }
+
     pub fn photo(&self) -> anyhow::Result<&Vec<PhotoSize>> {
         self.message()
             .and_then(|msg| msg.photo.as_ref().ok_or(anyhow!("message has no photo")))
     }
 
+    /// Returns the biggest size of the photo in the message. Telegram sends several sizes
+    /// of each photo, the smallest being a thumbnail.
+    pub fn largest_photo(&self) -> anyhow::Result<&PhotoSize> {
+        self.photo()?
+            .iter()
+            .max_by_key(|size| size.width * size.height)
+            .ok_or(anyhow!("message has no photo"))
+    }
+
     pub fn document(&self) -> anyhow::Result<&Document> {
         self.message().and_then(|msg| {
             msg.document
@@ -194,6 +313,59 @@ impl Update {
         })
     }
 
+    pub fn animation(&self) -> anyhow::Result<&api::Animation> {
+        self.message().and_then(|msg| {
+            msg.animation
+                .as_ref()
+                .ok_or(anyhow!("message has no animation"))
+        })
+    }
+
+    pub fn location(&self) -> anyhow::Result<&api::Location> {
+        self.message().and_then(|msg| {
+            msg.location
+                .as_ref()
+                .ok_or(anyhow!("message has no location"))
+        })
+    }
+
+    pub fn venue(&self) -> anyhow::Result<&api::Venue> {
+        self.message()
+            .and_then(|msg| msg.venue.as_ref().ok_or(anyhow!("message has no venue")))
+    }
+
+    pub fn users_shared(&self) -> anyhow::Result<&api::UsersShared> {
+        self.message().and_then(|msg| {
+            msg.users_shared
+                .as_ref()
+                .ok_or(anyhow!("message has no shared users"))
+        })
+    }
+
+    pub fn chat_shared(&self) -> anyhow::Result<&api::ChatShared> {
+        self.message().and_then(|msg| {
+            msg.chat_shared
+                .as_ref()
+                .ok_or(anyhow!("message has no shared chat"))
+        })
+    }
+
+    pub fn successful_payment(&self) -> anyhow::Result<&api::SuccessfulPayment> {
+        self.message().and_then(|msg| {
+            msg.successful_payment
+                .as_ref()
+                .ok_or(anyhow!("message has no successful payment"))
+        })
+    }
+
+    pub fn refunded_payment(&self) -> anyhow::Result<&api::RefundedPayment> {
+        self.message().and_then(|msg| {
+            msg.refunded_payment
+                .as_ref()
+                .ok_or(anyhow!("message has no refunded payment"))
+        })
+    }
+
     pub fn data(&self) -> anyhow::Result<&str> {
         self.get_callback_query()
             .map(|query| query.data.as_ref().unwrap().as_str())
@@ -202,12 +374,33 @@ impl Update {
     pub fn from_user(&self) -> anyhow::Result<&api::User> {
         use Update::*;
         match self {
-            Message(msg) | EditedMessage(msg) | ChannelPost(msg) | EditedChannelPost(msg) => {
-                msg.from.as_ref()
-            }
+            Message(msg)
+            | EditedMessage(msg)
+            | ChannelPost(msg)
+            | EditedChannelPost(msg)
+            | BusinessMessage(msg) => msg.from.as_ref(),
             CallbackQuery(query) => Some(&query.from),
+            ChatJoinRequest(req) => Some(&req.from),
+            MyChatMember(update) | ChatMember(update) => Some(&update.from),
+            PreCheckoutQuery(query) => Some(&query.from),
             _ => None,
         }
         .ok_or(anyhow!("message has no user"))
     }
+
+    /// Returns the origin of a forwarded message, see [`api::Message::origin`].
+    pub fn forward_origin(&self) -> anyhow::Result<api::MessageOrigin> {
+        self.get_message_or_post()
+            .and_then(|msg| msg.origin().ok_or(anyhow!("message is not forwarded")))
+    }
+
+    /// Returns the chat on whose behalf the message was sent (e.g., for anonymous group
+    /// admins and channel posts), in which case `from_user` may not be set.
+    pub fn sender_chat(&self) -> anyhow::Result<&api::Chat> {
+        self.get_message_or_post().and_then(|msg| {
+            msg.sender_chat
+                .as_ref()
+                .ok_or(anyhow!("message has no sender_chat"))
+        })
+    }
 }
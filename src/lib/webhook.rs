@@ -0,0 +1,46 @@
+//! Helpers for bots that receive updates via webhooks on their own HTTP server.
+//!
+//! These don't depend on any particular HTTP framework, so they can be used with
+//! axum, actix, warp, hyper, etc.
+
+/// The header Telegram uses to send the `secret_token` configured with `setWebhook`.
+pub const WEBHOOK_SECRET_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+/// Returns true if `headers` contain the `X-Telegram-Bot-Api-Secret-Token` header, and its
+/// value matches `expected`. Header names are compared case-insensitively, and the secret is
+/// compared in constant time.
+///
+/// `headers` can be any iterator over `(name, value)` pairs, e.g., an `http::HeaderMap`.
+///
+/// # Example
+///
+/// ```
+/// # use mobot::webhook::verify_webhook_secret;
+/// let headers = vec![("x-telegram-bot-api-secret-token", "s3cr3t")];
+/// assert!(verify_webhook_secret(headers, "s3cr3t"));
+/// ```
+pub fn verify_webhook_secret<K, V>(
+    headers: impl IntoIterator<Item = (K, V)>,
+    expected: impl AsRef<str>,
+) -> bool
+where
+    K: AsRef<str>,
+    V: AsRef<[u8]>,
+{
+    let expected = expected.as_ref().as_bytes();
+
+    headers
+        .into_iter()
+        .find(|(name, _)| name.as_ref().eq_ignore_ascii_case(WEBHOOK_SECRET_HEADER))
+        .is_some_and(|(_, value)| constant_time_eq(value.as_ref(), expected))
+}
+
+/// Compare two byte strings without short-circuiting on the first mismatch, so the
+/// comparison doesn't leak how much of the secret matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
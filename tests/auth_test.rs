@@ -26,7 +26,7 @@ async fn error_handler<S: handler::BotState>(
     error!("Error: {}", err);
     let result = api
         .send_message(&SendMessageRequest {
-            chat_id,
+            chat_id: chat_id.into(),
             text: format!("Sorry! {}.", err),
             ..Default::default()
         })
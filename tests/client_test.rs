@@ -31,3 +31,790 @@ async fn it_works() {
             .await
     );
 }
+
+#[test]
+fn reply_parameters() {
+    let req = api::SendMessageRequest::new(1, "hi").with_reply_to_message_id(42);
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(
+        json["reply_parameters"],
+        serde_json::json!({"message_id": 42})
+    );
+
+    let req = api::SendStickerRequest::new(1, "sticker".to_string()).with_reply_parameters(
+        api::ReplyParameters::new(42)
+            .with_chat_id(-100)
+            .with_quote("hello", 6),
+    );
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(
+        json["reply_parameters"],
+        serde_json::json!({"message_id": 42, "chat_id": -100, "quote": "hello", "quote_position": 6})
+    );
+}
+
+#[test]
+fn chat_id() {
+    let req = api::SendMessageRequest::new(-1001234567890, "hi");
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["chat_id"], serde_json::json!(-1001234567890i64));
+
+    let req = api::SendMessageRequest::new("@mobotnews", "hi");
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["chat_id"], serde_json::json!("@mobotnews"));
+
+    let chat_id: api::ChatId = serde_json::from_str("\"@mobotnews\"").unwrap();
+    assert_eq!(chat_id, api::ChatId::Username("@mobotnews".into()));
+    assert_eq!(chat_id.id(), None);
+}
+
+#[test]
+fn input_file() {
+    let req = api::SendAnimationRequest::new(1, "gif123");
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["animation"], "gif123");
+
+    let req = api::SendAnimationRequest::new(1, api::InputFile::upload("cat.gif", vec![1, 2, 3]));
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["animation"], "cat.gif");
+}
+
+#[test]
+fn request_builder() {
+    let req = api::SendAnimationRequest::new(1, "gif123")
+        .with_caption("so cute")
+        .with_duration(3);
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["caption"], "so cute");
+    assert_eq!(json["duration"], 3);
+    assert!(json.get("width").is_none());
+
+    // Setters on flattened base structs end up at the top level of the request.
+    let req = api::EditMessageTextRequest {
+        base: api::EditMessageBase::new()
+            .with_chat_id("@mobotnews")
+            .with_message_id(42)
+            .with_parse_mode(api::ParseMode::HTML),
+        text: "edited".into(),
+    };
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"chat_id": "@mobotnews", "message_id": 42, "parse_mode": "HTML", "text": "edited"})
+    );
+}
+
+#[test]
+fn inline_query_button() {
+    let req = api::AnswerInlineQuery::new("q1".into())
+        .with_button(api::InlineQueryResultsButton::start("Set up", "setup"))
+        .with_article_text("title", "text");
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(
+        json["button"],
+        serde_json::json!({"text": "Set up", "start_parameter": "setup"})
+    );
+    assert_eq!(json["results"][0]["title"], "title");
+
+    let req = api::AnswerInlineQuery::new("q2".into())
+        .with_cache_time(10)
+        .with_is_personal(true)
+        .with_next_offset("20");
+    let json = serde_json::to_value(&req).unwrap();
+    assert_eq!(json["cache_time"], 10);
+    assert_eq!(json["is_personal"], true);
+    assert_eq!(json["next_offset"], "20");
+
+    let button = api::InlineQueryResultsButton::web_app("Open", "https://example.com");
+    assert_eq!(
+        serde_json::to_value(&button).unwrap(),
+        serde_json::json!({"text": "Open", "web_app": {"url": "https://example.com"}})
+    );
+}
+
+#[tokio::test]
+async fn non_json_response() {
+    let body = "<html><body><h1>502 Bad Gateway</h1></body></html>\n";
+    let err = api::ApiResponse::<bool>::from_http(502, body).unwrap_err();
+    match err.downcast_ref::<api::ApiError>() {
+        Some(api::ApiError::Http {
+            status,
+            body_snippet,
+        }) => {
+            assert_eq!(*status, 502);
+            assert_eq!(body_snippet, body.trim());
+        }
+        _ => panic!("unexpected error: {}", err),
+    }
+
+    // Non-JSON responses from post handlers are reported too.
+    let client = Client::new("token".to_string())
+        .with_post_handler_fn(|_: String, _: String| Ok("Service Unavailable".to_string()));
+    let api = API::new(client);
+    let err = api
+        .send_message(&api::SendMessageRequest::new(1, "hi"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "HTTP error 200: Service Unavailable");
+
+    // API responses with a result of the wrong shape aren't HTTP errors.
+    let body = r#"{"ok": true, "result": {"unexpected": "shape"}}"#;
+    let err = api::ApiResponse::<bool>::from_http(200, body).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<api::ApiError>(),
+        Some(api::ApiError::Malformed(_))
+    ));
+    assert_eq!(err.downcast_ref::<api::ApiError>().unwrap().status(), None);
+}
+
+#[test]
+fn error_status() {
+    let body =
+        r#"{"ok": false, "error_code": 429, "description": "Too Many Requests: retry after 5"}"#;
+    let err = api::ApiResponse::<bool>::from_http(429, body).unwrap_err();
+    let err = err.downcast_ref::<api::ApiError>().unwrap();
+    assert_eq!(err.status(), Some(429));
+    assert_eq!(
+        err.to_string(),
+        "Telegram error (429): Too Many Requests: retry after 5"
+    );
+
+    // Conflicts (e.g., another instance polling the same token) have their own variant.
+    let conflict = r#"{"ok": false, "error_code": 409, "description": "Conflict: terminated by other getUpdates request"}"#;
+    let err = api::ApiResponse::<bool>::from_http(409, conflict).unwrap_err();
+    let err = err.downcast_ref::<api::ApiError>().unwrap();
+    assert!(matches!(err, api::ApiError::Conflict(_)));
+    assert_eq!(err.status(), Some(409));
+
+    // Errors without an HTTP status (e.g., from post handlers) have no status.
+    let err = api::ApiResponse::<bool>::from_http(200, body)
+        .unwrap()
+        .result()
+        .unwrap_err();
+    assert_eq!(err.downcast_ref::<api::ApiError>().unwrap().status(), None);
+}
+
+#[tokio::test]
+async fn profile() {
+    let fakeserver = fake::FakeAPI::new();
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver));
+
+    api.set_my_name(&api::SetMyNameRequest::new("Mobot"))
+        .await
+        .unwrap();
+    api.set_my_name(&api::SetMyNameRequest::new("Mobote").with_language_code("es"))
+        .await
+        .unwrap();
+    api.set_my_short_description(&api::SetMyShortDescriptionRequest::new("A test bot"))
+        .await
+        .unwrap();
+
+    let name = api
+        .get_my_name(&api::GetMyNameRequest::new())
+        .await
+        .unwrap();
+    assert_eq!(name.name, "Mobot");
+    let name = api
+        .get_my_name(&api::GetMyNameRequest::new().with_language_code("es"))
+        .await
+        .unwrap();
+    assert_eq!(name.name, "Mobote");
+
+    let req = api::GetMyShortDescriptionRequest::new();
+    assert_eq!(
+        api.get_my_short_description(&req)
+            .await
+            .unwrap()
+            .short_description,
+        "A test bot"
+    );
+    assert_eq!(api.get_my_description(&req).await.unwrap().description, "");
+
+    api.set_chat_menu_button(&api::SetChatMenuButtonRequest::new(
+        api::MenuButton::WebApp {
+            text: "Open".into(),
+            web_app: api::WebAppInfo {
+                url: "https://example.com".into(),
+            },
+        },
+    ))
+    .await
+    .unwrap();
+    let button = api
+        .get_chat_menu_button(&api::GetChatMenuButtonRequest::new().with_chat_id(42))
+        .await
+        .unwrap();
+    assert!(matches!(button, api::MenuButton::WebApp { text, .. } if text == "Open"));
+    assert_eq!(
+        serde_json::to_value(api::MenuButton::Commands).unwrap(),
+        serde_json::json!({"type": "commands"})
+    );
+}
+
+#[test]
+fn game_and_pay_buttons() {
+    let button = api::InlineKeyboardButton::from("Play").with_callback_game();
+    assert_eq!(
+        serde_json::to_value(&button).unwrap(),
+        serde_json::json!({"text": "Play", "callback_game": {}})
+    );
+
+    let button = api::InlineKeyboardButton::from("Pay 5 XTR").with_pay();
+    assert_eq!(
+        serde_json::to_value(&button).unwrap(),
+        serde_json::json!({"text": "Pay 5 XTR", "pay": true})
+    );
+}
+
+#[test]
+fn callback_data_length() {
+    let button = api::InlineKeyboardButton::from("ok")
+        .try_with_callback_data("a".repeat(api::MAX_CALLBACK_DATA_LEN))
+        .unwrap();
+    assert_eq!(button.callback_data.unwrap().len(), 64);
+
+    assert!(api::InlineKeyboardButton::from("too long")
+        .try_with_callback_data("a".repeat(65))
+        .is_err());
+
+    #[derive(Serialize)]
+    struct Vote {
+        poll: u32,
+        choice: &'static str,
+    }
+
+    let button = api::InlineKeyboardButton::from("yes")
+        .try_with_callback(&Vote {
+            poll: 7,
+            choice: "yes",
+        })
+        .unwrap();
+    assert_eq!(
+        button.callback_data.unwrap(),
+        r#"{"poll":7,"choice":"yes"}"#
+    );
+
+    assert!(api::InlineKeyboardButton::from("long")
+        .try_with_callback(&vec!["long"; 20])
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "callback_data is longer than 64 bytes")]
+#[cfg(debug_assertions)]
+fn callback_data_length_debug_assert() {
+    let _ = api::InlineKeyboardButton::from("too long").with_callback_data("a".repeat(65));
+}
+
+#[tokio::test]
+async fn rate_limiter() {
+    use std::time::{Duration, Instant};
+
+    let limiter = RateLimiter::new()
+        .with_global_limit(100, Duration::from_secs(1))
+        .with_chat_limit(1, Duration::from_millis(200))
+        .with_group_limit(2, Duration::from_secs(2));
+    let client = Client::new("token".to_string())
+        .with_post_handler(fake::FakeAPI::new())
+        .with_rate_limiter(limiter);
+    let api = API::new(client);
+
+    // Messages to the same chat are spaced out.
+    let start = Instant::now();
+    for _ in 0..3 {
+        api.send_message(&api::SendMessageRequest::new(1, "hi"))
+            .await
+            .unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(400));
+
+    // Other chats aren't held up.
+    let start = Instant::now();
+    api.send_message(&api::SendMessageRequest::new(2, "hi"))
+        .await
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    // Groups have an additional limit: the third message waits for the group bucket to
+    // refill (1s per message), not just the chat bucket (200ms).
+    let start = Instant::now();
+    for _ in 0..3 {
+        api.send_message(&api::SendMessageRequest::new(-100, "hi"))
+            .await
+            .unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(900));
+
+    // Limits that would never let a message through are rejected up front.
+    let rejected = |set: fn(RateLimiter) -> RateLimiter| {
+        std::panic::catch_unwind(|| set(RateLimiter::new())).is_err()
+    };
+    assert!(rejected(|l| l.with_global_limit(0, Duration::from_secs(1))));
+    assert!(rejected(|l| l.with_chat_limit(0, Duration::from_secs(1))));
+    assert!(rejected(|l| l.with_group_limit(0, Duration::from_secs(1))));
+    assert!(rejected(|l| l.with_chat_limit(1, Duration::ZERO)));
+}
+
+#[tokio::test]
+async fn call_raw() {
+    let fakeserver = fake::FakeAPI::new();
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver));
+
+    let result = api
+        .call_raw("setMyName", serde_json::json!({"name": "Mobot"}))
+        .await
+        .unwrap();
+    assert_eq!(result, serde_json::json!(true));
+
+    let result = api
+        .call_raw("getMyName", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(result, serde_json::json!({"name": "Mobot"}));
+
+    assert!(api
+        .call_raw("notARealMethod", serde_json::json!({}))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn user_profile_photos_stream() {
+    use futures::TryStreamExt;
+
+    let fakeserver = fake::FakeAPI::new();
+    let photos: Vec<Vec<api::PhotoSize>> = (0..250)
+        .map(|i| {
+            vec![api::PhotoSize {
+                file_id: format!("photo{}", i),
+                ..Default::default()
+            }]
+        })
+        .collect();
+    fakeserver.set_user_profile_photos(42, photos).await;
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver));
+
+    // A single page is capped at 100 photos.
+    let page = api
+        .get_user_profile_photos(&api::GetUserProfilePhotosRequest::new(42))
+        .await
+        .unwrap();
+    assert_eq!(page.total_count, 250);
+    assert_eq!(page.photos.len(), 100);
+
+    // The stream yields all of them, across pages.
+    let all: Vec<_> = api
+        .user_profile_photos_stream(42)
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 250);
+    assert_eq!(all[249][0].file_id, "photo249");
+
+    let none: Vec<_> = api
+        .user_profile_photos_stream(7)
+        .try_collect()
+        .await
+        .unwrap();
+    assert!(none.is_empty());
+}
+
+#[tokio::test]
+async fn set_commands_multi() {
+    let fakeserver = fake::FakeAPI::new();
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+    let command = |command: &str, description: &str| api::BotCommand {
+        command: command.into(),
+        description: description.into(),
+    };
+
+    api.set_commands_multi(vec![
+        (
+            api::BotCommandScope::new(api::BotCommnandScopeType::Default),
+            None,
+            vec![command("start", "Start the bot")],
+        ),
+        (
+            api::BotCommandScope::new(api::BotCommnandScopeType::Default),
+            Some("de".into()),
+            vec![command("start", "Bot starten")],
+        ),
+        (
+            api::BotCommandScope::chat_administrators(-100),
+            None,
+            vec![command("ban", "Ban a user")],
+        ),
+    ])
+    .await
+    .unwrap();
+
+    assert_eq!(
+        fakeserver.commands().await,
+        [command("start", "Start the bot")]
+    );
+    assert_eq!(
+        api.get_commands(
+            api::BotCommandScope::new(api::BotCommnandScopeType::Default),
+            Some("de")
+        )
+        .await
+        .unwrap(),
+        [command("start", "Bot starten")]
+    );
+    assert_eq!(
+        api.get_commands(api::BotCommandScope::chat_administrators(-100), None)
+            .await
+            .unwrap(),
+        [command("ban", "Ban a user")]
+    );
+    assert!(api
+        .get_commands(api::BotCommandScope::chat(-100), None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Failures are aggregated, and don't stop the other calls.
+    fakeserver
+        .fail_next(
+            "setMyCommands",
+            api::ApiResponse::Err("Bad Request: BOT_COMMAND_INVALID"),
+        )
+        .await;
+    let err = api
+        .set_commands_multi(vec![
+            (
+                api::BotCommandScope::chat(1),
+                Some("fr".into()),
+                vec![command("BAD", "Invalid")],
+            ),
+            (
+                api::BotCommandScope::chat(2),
+                None,
+                vec![command("help", "Get help")],
+            ),
+        ])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("BOT_COMMAND_INVALID"));
+    assert!(err.to_string().contains("fr"));
+    assert_eq!(
+        api.get_commands(api::BotCommandScope::chat(2), None)
+            .await
+            .unwrap(),
+        [command("help", "Get help")]
+    );
+}
+
+#[tokio::test]
+async fn channel_usernames() {
+    let fakeserver = fake::FakeAPI::new();
+    let channel = fakeserver.create_chat("news").await;
+    fakeserver.set_chat_username(channel.chat_id, "@news").await;
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+
+    let sent = api
+        .send_message(&api::SendMessageRequest::new("@news", "breaking"))
+        .await
+        .unwrap();
+    assert_eq!(sent.chat.id, channel.chat_id);
+    channel.expect_text("breaking").await;
+
+    api.edit_message_text(
+        &api::EditMessageTextRequest::new("update".into())
+            .with_chat_id("@news")
+            .with_message_id(sent.message_id),
+    )
+    .await
+    .unwrap();
+    channel.expect_text("update").await;
+
+    api.send_chat_action(&api::SendChatActionRequest::new(
+        "@news",
+        api::ChatAction::Typing,
+    ))
+    .await
+    .unwrap();
+    assert_eq!(
+        fakeserver.chat_actions(channel.chat_id).await,
+        [api::ChatAction::Typing]
+    );
+
+    api.delete_message(&api::DeleteMessageRequest::new("@news", sent.message_id))
+        .await
+        .unwrap();
+    assert!(fakeserver
+        .get_message(channel.chat_id, sent.message_id)
+        .await
+        .is_none());
+
+    // Unknown usernames are rejected.
+    assert!(api
+        .send_chat_action(&api::SendChatActionRequest::new(
+            "@unknown",
+            api::ChatAction::Typing
+        ))
+        .await
+        .is_err());
+    assert!(api
+        .send_message(&api::SendMessageRequest::new("@unknown", "hello"))
+        .await
+        .is_err());
+    assert!(api
+        .send_location(&api::SendLocationRequest::new("@unknown", 51.5, -0.1))
+        .await
+        .is_err());
+    assert!(api
+        .edit_message_text(
+            &api::EditMessageTextRequest::new("update".into())
+                .with_chat_id("@unknown")
+                .with_message_id(sent.message_id),
+        )
+        .await
+        .is_err());
+    assert!(api
+        .edit_message_text(&api::EditMessageTextRequest::new("update".into()).with_chat_id("@news"))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn sticker_sets() {
+    let fakeserver = fake::FakeAPI::new();
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+
+    let stickers = vec![
+        api::InputSticker::new(
+            api::InputFile::upload("cat.png", vec![1, 2, 3]),
+            api::StickerFormat::Static,
+            "🐱",
+        ),
+        api::InputSticker::new("sticker123", api::StickerFormat::Video, "🐶")
+            .with_keywords(vec!["dog".into()]),
+    ];
+    api.create_new_sticker_set(&api::CreateNewStickerSetRequest::new(
+        1,
+        "pets_by_mobot",
+        "Pets",
+        stickers,
+    ))
+    .await
+    .unwrap();
+
+    // Set names must be unique.
+    assert!(api
+        .create_new_sticker_set(&api::CreateNewStickerSetRequest::new(
+            1,
+            "pets_by_mobot",
+            "Pets",
+            vec![api::InputSticker::new(
+                "sticker456",
+                api::StickerFormat::Static,
+                "🐭"
+            )],
+        ))
+        .await
+        .is_err());
+
+    api.add_sticker_to_set(&api::AddStickerToSetRequest::new(
+        1,
+        "pets_by_mobot",
+        api::InputSticker::new(
+            api::InputFile::upload("mouse.tgs", vec![4, 5, 6]),
+            api::StickerFormat::Animated,
+            "🐭",
+        ),
+    ))
+    .await
+    .unwrap();
+
+    // Uploads are sent as attachments, and referenced from the request.
+    let set = api
+        .get_sticker_set(&api::GetStickerSetRequest::new("pets_by_mobot"))
+        .await
+        .unwrap();
+    assert_eq!(set.title, "Pets");
+    assert_eq!(set.sticker_type, api::StickerType::Regular);
+    assert_eq!(
+        set.stickers
+            .iter()
+            .map(|s| (s.file_id.as_str(), s.emoji.as_deref().unwrap()))
+            .collect::<Vec<_>>(),
+        [
+            ("attach://sticker0", "🐱"),
+            ("sticker123", "🐶"),
+            ("attach://sticker0", "🐭")
+        ]
+    );
+    assert!(set.stickers[1].is_video);
+    assert!(set.stickers[2].is_animated);
+
+    assert!(api
+        .add_sticker_to_set(&api::AddStickerToSetRequest::new(
+            1,
+            "missing_by_mobot",
+            api::InputSticker::new("sticker456", api::StickerFormat::Static, "🐭"),
+        ))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn reply_parameters_on_sends() {
+    let fakeserver = fake::FakeAPI::new();
+    let chat = fakeserver.create_chat("qubyte").await;
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+
+    let sent = api
+        .send_message(&api::SendMessageRequest::new(chat.chat_id, "where?"))
+        .await
+        .unwrap();
+
+    let location = api
+        .send_location(
+            &api::SendLocationRequest::new(chat.chat_id, 51.5, -0.1)
+                .with_reply_to_message_id(sent.message_id),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        location.reply_to_message.unwrap().message_id,
+        sent.message_id
+    );
+
+    let venue = api
+        .send_venue(
+            &api::SendVenueRequest::new(chat.chat_id, 51.5, -0.1, "Cafe", "1 Main St")
+                .with_reply_to_message_id(sent.message_id),
+        )
+        .await
+        .unwrap();
+    assert_eq!(venue.reply_to_message.unwrap().message_id, sent.message_id);
+
+    // Replies to messages that don't exist are rejected, unless explicitly allowed.
+    let invoice = |reply: api::ReplyParameters| {
+        api::SendInvoiceRequest::new(
+            chat.chat_id,
+            "Coffee",
+            "A cup of coffee",
+            "coffee-1",
+            "",
+            "XTR",
+            vec![api::LabeledPrice::new("Coffee", 250)],
+        )
+        .with_reply_parameters(reply)
+    };
+    assert!(api
+        .send_invoice(&invoice(api::ReplyParameters::new(12345)))
+        .await
+        .is_err());
+    let sent = api
+        .send_invoice(&invoice(
+            api::ReplyParameters::new(12345).with_allow_sending_without_reply(true),
+        ))
+        .await
+        .unwrap();
+    assert!(sent.reply_to_message.is_none());
+}
+
+/// Serve a single HTTP request on a local port with `body` as the JSON response, and
+/// return the server's URL, and the raw request headers it received.
+async fn serve_once(body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![];
+        let mut buf = [0; 4096];
+        while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+        }
+        socket
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let request = String::from_utf8_lossy(&request).to_lowercase();
+        request.split("\r\n\r\n").next().unwrap().to_string()
+    });
+    (url, server)
+}
+
+#[tokio::test]
+async fn request_headers() {
+    let (url, server) = serve_once(r#"{"ok": true, "result": true}"#).await;
+    let api = API::new(
+        Client::new("token".to_string())
+            .with_api_url(url)
+            .with_user_agent("testbot/1.0")
+            .with_request_ids(true),
+    );
+    assert!(api.delete_webhook(false).await.unwrap());
+
+    let headers = server.await.unwrap();
+    assert!(headers.starts_with("post /bottoken/deletewebhook "));
+    assert!(headers.contains("\r\nuser-agent: testbot/1.0\r\n"));
+    let request_id = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("x-request-id: "))
+        .expect("no X-Request-Id header");
+    assert_eq!(request_id.len(), 16);
+}
+
+#[tokio::test]
+async fn pool_options() {
+    let (url, server) = serve_once(r#"{"ok": true, "result": true}"#).await;
+    let api = API::new(
+        Client::new("token".to_string())
+            .with_api_url(url)
+            .with_pool_max_idle_per_host(2)
+            .with_pool_idle_timeout(std::time::Duration::from_secs(30))
+            .with_tcp_keepalive(std::time::Duration::from_secs(60)),
+    );
+
+    // The HTTP client is built with the pool settings on the first request.
+    assert!(api.delete_webhook(false).await.unwrap());
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn message_entities() {
+    let fakeserver = fake::FakeAPI::new().with_call_log();
+    let chat = fakeserver.create_chat("qubyte").await;
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+
+    api.send_message(
+        &api::SendMessageRequest::new(chat.chat_id, "Hello, world!").with_entities(vec![
+            api::MessageEntity::new(api::MessageEntityType::Bold, 0, 5),
+            api::MessageEntity::new(api::MessageEntityType::TextLink, 7, 5)
+                .with_url("https://example.com"),
+        ]),
+    )
+    .await
+    .unwrap();
+
+    // Entities are sent with their type, and without unset fields.
+    let (method, req) = fakeserver.call_log().await.pop().unwrap();
+    assert_eq!(method, "sendMessage");
+    assert_eq!(
+        req["entities"],
+        serde_json::json!([
+            {"type": "bold", "offset": 0, "length": 5},
+            {"type": "text_link", "offset": 7, "length": 5, "url": "https://example.com"},
+        ])
+    );
+
+    // Entity types added to the Bot API later are parsed as `Unknown`.
+    let entity: api::MessageEntity =
+        serde_json::from_str(r#"{"type": "date_time", "offset": 2, "length": 3}"#).unwrap();
+    assert_eq!(entity.entity_type, api::MessageEntityType::Unknown);
+    assert_eq!((entity.offset, entity.length), (2, 3));
+}
@@ -1,5 +1,4 @@
 /// Test that markdown text is properly escaped.
-
 #[test]
 fn escape_code() {
     let code = "hello `world` \\foo";
@@ -16,3 +15,8 @@ fn escape_markdown() {
         "hello \\*world\\* \\[foo\\]\\(bar\\) \\_baz\\_"
     );
 }
+
+#[test]
+fn escape_markdown_backslash() {
+    assert_eq!(mobot::api::escape_md(r"C:\path \."), r"C:\\path \\\.");
+}
@@ -1,4 +1,10 @@
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use anyhow::{bail, Result};
 use log::*;
@@ -147,6 +153,44 @@ async fn multiple_chats() {
     shutdown_notifier.notified().await;
 }
 
+#[tokio::test]
+async fn fake_chat_expectations() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+    router.add_route(Route::Message(Matcher::Any), handle_chat_event);
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat1 = fakeserver.create_chat("qubyte").await;
+    let chat2 = fakeserver.create_chat("qubyte").await;
+
+    chat1.send_text("ping1").await.unwrap();
+    chat1.expect_text("pong(1): ping1").await;
+
+    // Replies only go to the chat that sent the message.
+    chat2.expect_none(Duration::from_millis(100)).await;
+
+    chat1.send_text("ping2").await.unwrap();
+    chat1.send_text("ping3").await.unwrap();
+    let replies: Vec<String> = chat1
+        .collect(2)
+        .await
+        .iter()
+        .map(|u| u.to_string())
+        .collect();
+    assert_eq!(replies, ["pong(2): ping2", "pong(3): ping3"]);
+    chat1.expect_none(Duration::from_millis(100)).await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
 #[tokio::test]
 async fn multiple_chats_new_state() {
     mobot::init_logger();
@@ -193,6 +237,75 @@ async fn multiple_chats_new_state() {
     shutdown_notifier.notified().await;
 }
 
+#[tokio::test]
+async fn multiple_chats_state_fn() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let created = Arc::new(AtomicUsize::new(0));
+    let mut router = Router::new(client).with_poll_timeout_s(1).with_state_fn({
+        let created = Arc::clone(&created);
+        move || TestApp {
+            counter: 100 * (created.fetch_add(1, Ordering::SeqCst) as i32 + 1),
+        }
+    });
+    router.add_route(Route::Default, handle_chat_event);
+    let handle = router.spawn();
+
+    let chat1 = fakeserver.create_chat("qubyte").await;
+    let chat2 = fakeserver.create_chat("qubyte").await;
+
+    chat1.send_text("ping1").await.unwrap();
+    chat1.expect_text("pong(101): ping1").await;
+    chat1.send_text("ping2").await.unwrap();
+    chat1.expect_text("pong(102): ping2").await;
+    chat2.send_text("ping1").await.unwrap();
+    chat2.expect_text("pong(201): ping1").await;
+
+    // The state function is only called for new chats.
+    assert_eq!(created.load(Ordering::SeqCst), 2);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn multiple_accounts() {
+    let fakeserver1 = fake::FakeAPI::new();
+    let fakeserver2 = fake::FakeAPI::new();
+    let client1 = Client::new("token1".to_string()).with_post_handler(fakeserver1.clone());
+    let client2 = Client::new("token2".to_string()).with_post_handler(fakeserver2.clone());
+
+    let mut router = Router::new(client1)
+        .with_account(client2)
+        .with_poll_timeout_s(1);
+    router.add_route(
+        Route::Default,
+        |e: Event, state: State<TestApp>| async move {
+            let mut state = state.get().write().await;
+            state.counter += 1;
+            Ok(Action::ReplyText(format!(
+                "account {}: {}",
+                e.account(),
+                state.counter
+            )))
+        },
+    );
+    let handle = router.spawn();
+
+    // Each account replies via its own client, and keeps its own chat states.
+    let chat1 = fakeserver1.create_chat("qubyte").await;
+    let chat2 = fakeserver2.create_chat("qubyte").await;
+
+    chat1.send_text("ping").await.unwrap();
+    chat1.expect_text("account 0: 1").await;
+    chat1.send_text("ping").await.unwrap();
+    chat1.expect_text("account 0: 2").await;
+    chat2.send_text("ping").await.unwrap();
+    chat2.expect_text("account 1: 1").await;
+
+    handle.shutdown().await;
+}
+
 #[tokio::test]
 async fn add_route() {
     mobot::init_logger();
@@ -269,7 +382,7 @@ async fn edit_message_text() {
     let chat1 = fakeserver.create_chat("qubyte").await;
 
     chat1.send_text("ping1").await.unwrap();
-    let message: api::Message = chat1.recv_update().await.unwrap().into();
+    let message = api::Message::try_from(chat1.recv_update().await.unwrap()).unwrap();
 
     assert_eq!(message.text.unwrap(), "pong(1): ping1");
 
@@ -284,6 +397,103 @@ async fn edit_message_text() {
     shutdown_notifier.notified().await;
 }
 
+#[tokio::test]
+async fn log_handler_continues() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client);
+    router
+        .add_route(Route::Default, handlers::log_handler)
+        .add_route(Route::Default, |_: Event, _: State<()>| async move {
+            Ok(Action::Done)
+        });
+
+    // The log handler passes stickers and inline queries on to the next handler.
+    let mut sticker = api::Message::new("qubyte", "");
+    sticker.text = None;
+    sticker.sticker = Some(api::Sticker {
+        file_id: "sticker1".into(),
+        ..Default::default()
+    });
+    let inline_query = api::InlineQuery {
+        id: "q1".into(),
+        query: "cats".into(),
+        ..Default::default()
+    };
+
+    for update in [
+        api::Update {
+            message: Some(sticker),
+            ..Default::default()
+        },
+        api::Update {
+            inline_query: Some(inline_query),
+            ..Default::default()
+        },
+    ] {
+        let actions = router.dispatch(update).await.unwrap();
+        assert!(matches!(&actions[..], [Action::Next, Action::Done]));
+    }
+}
+
+#[tokio::test]
+async fn edit_inline_message() {
+    let fakeserver = fake::FakeAPI::new().with_call_log();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client);
+    router.add_route(
+        Route::CallbackQuery(Matcher::Any),
+        |e: Event, _: State<()>| async move {
+            assert!(e.edit_inline_message("You picked yes").await?);
+            assert!(e.remove_inline_message_keyboard().await?);
+            assert!(
+                e.api
+                    .edit_inline_message_caption(
+                        &api::EditMessageCaptionRequest::new("yes".into())
+                            .with_inline_message_id(e.update.inline_message_id()?)
+                    )
+                    .await?
+            );
+            Ok(Action::Done)
+        },
+    );
+
+    // Callback queries from messages sent in inline mode have no message.
+    router
+        .dispatch(api::Update {
+            callback_query: Some(api::CallbackQuery {
+                id: "q1".into(),
+                from: api::User::default(),
+                message: None,
+                inline_message_id: Some("inline1".into()),
+                data: Some("yes".into()),
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // The edits are addressed by `inline_message_id` alone.
+    let log = fakeserver.call_log().await;
+    let methods: Vec<_> = log.iter().map(|(method, _)| method.as_str()).collect();
+    assert_eq!(
+        methods,
+        [
+            "editMessageText",
+            "editMessageReplyMarkup",
+            "editMessageCaption"
+        ]
+    );
+    for (_, req) in &log {
+        assert_eq!(req["inline_message_id"], "inline1");
+        assert!(req.get("chat_id").is_none());
+        assert!(req.get("message_id").is_none());
+    }
+    assert_eq!(log[0].1["text"], "You picked yes");
+}
+
 /// This handler displays a message with two inline keyboard buttons: "yes" and "no".
 async fn ask_message(e: Event, _: State<()>) -> Result<Action, anyhow::Error> {
     e.api
@@ -332,7 +542,7 @@ async fn push_buttons() {
     chat1.send_text("what?").await.unwrap();
 
     // Expect some buttons
-    let message: api::Message = chat1.recv_update().await.unwrap().into();
+    let message = api::Message::try_from(chat1.recv_update().await.unwrap()).unwrap();
     assert_eq!(message.text.unwrap(), "Push the button!");
 
     // Push "yes"
@@ -354,3 +564,2500 @@ async fn push_buttons() {
     shutdown_tx.send(()).await.unwrap();
     shutdown_notifier.notified().await;
 }
+
+#[tokio::test]
+async fn ack_callback() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::CallbackQuery(Matcher::Exact("vote".into())),
+            |_, _| async { Ok(Action::AckCallback(Some("Thanks for voting".into()))) },
+        )
+        .add_route(
+            Route::CallbackQuery(Matcher::Any),
+            |e: Event, _| async move {
+                let acked = matches!(e.prev_action(), Some(Action::AckCallback(_)));
+                Ok(Action::ReplyText(format!(
+                    "pressed {} (acked: {})",
+                    e.update.data()?,
+                    acked
+                )))
+            },
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_update(Update::CallbackQuery(api::CallbackQuery {
+        id: "q1".into(),
+        from: chat.from.clone().into(),
+        message: Some(fake::FakeMessage::text(chat.chat_id, "mobot", "Vote?").into()),
+        inline_message_id: None,
+        data: Some("vote".into()),
+    }))
+    .await
+    .unwrap();
+    chat.expect_text("pressed vote (acked: true)").await;
+
+    let answer = fakeserver.callback_answer("q1").await.unwrap();
+    assert_eq!(answer.text.as_deref(), Some("Thanks for voting"));
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn allowed_updates() {
+    let client = Client::new("token".to_string());
+    let mut router = Router::<()>::new(client);
+
+    router
+        .add_route(Route::InlineQuery(Matcher::Any), handlers::done_handler)
+        .add_route(
+            Route::Message(Matcher::Exact("foo".into())),
+            handlers::done_handler,
+        )
+        .add_route(Route::Message(Matcher::Any), handlers::done_handler);
+    assert_eq!(router.allowed_updates(), vec!["inline_query", "message"]);
+
+    // A default route needs all update types.
+    router.add_route(Route::Default, handlers::done_handler);
+    assert!(router.allowed_updates().is_empty());
+}
+
+#[test]
+fn match_topic() {
+    let mut message = api::Message::new("qubyte", "hello");
+    message.message_thread_id = Some(42);
+    let update = api::Update {
+        message: Some(message),
+        ..Default::default()
+    };
+
+    assert!(Route::Message(Matcher::Topic(42)).match_update(&update));
+    assert!(!Route::Message(Matcher::Topic(7)).match_update(&update));
+}
+
+#[derive(Debug, Clone, Default, BotState)]
+struct OtherState {
+    counter: i32,
+}
+
+/// This handler keeps its own state type, independent of the router's state.
+async fn handle_other_state(e: Event, _: State<TestApp>) -> Result<Action, anyhow::Error> {
+    let state = e.state::<OtherState>();
+    let mut state = state.get().write().await;
+    state.counter += 10;
+    Ok(Action::ReplyText(format!("other({})", state.counter)))
+}
+
+#[tokio::test]
+async fn multiple_state_types() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router
+        .add_route(
+            Route::Message(Matcher::Exact("other".into())),
+            handle_other_state,
+        )
+        .add_route(Route::Message(Matcher::Any), handle_chat_event);
+
+    tokio::spawn(async move {
+        info!("Starting router...");
+        router.start().await;
+    });
+
+    let chat1 = fakeserver.create_chat("qubyte").await;
+    let chat2 = fakeserver.create_chat("qubyte").await;
+
+    chat1.send_text("other").await.unwrap();
+    assert_eq!(chat1.recv_update().await.unwrap().to_string(), "other(10)");
+
+    chat1.send_text("ping1").await.unwrap();
+    assert_eq!(
+        chat1.recv_update().await.unwrap().to_string(),
+        "pong(1): ping1"
+    );
+
+    chat1.send_text("other").await.unwrap();
+    assert_eq!(chat1.recv_update().await.unwrap().to_string(), "other(20)");
+
+    chat2.send_text("other").await.unwrap();
+    assert_eq!(chat2.recv_update().await.unwrap().to_string(), "other(10)");
+
+    info!("Shutting down...");
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn chat_states_created_on_use() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client);
+    router
+        .add_route(
+            Route::Message(Matcher::Exact("other".into())),
+            |e: Event, _: State<()>| async move {
+                e.state::<OtherState>().get().write().await.counter += 1;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(Route::Default, |_: Event, _: State<()>| async move {
+            Ok(Action::Done)
+        });
+
+    let update = |chat_id: i64, text: &str| {
+        let mut message = api::Message::new("qubyte", text);
+        message.chat.id = chat_id;
+        api::Update {
+            message: Some(message),
+            ..Default::default()
+        }
+    };
+
+    // Handlers that don't use chat states don't leave any behind.
+    for chat_id in 1..=100 {
+        router.dispatch(update(chat_id, "hello")).await.unwrap();
+    }
+    assert_eq!(router.stateful_chats(), 0);
+
+    router.dispatch(update(1, "other")).await.unwrap();
+    router.dispatch(update(1, "other")).await.unwrap();
+    assert_eq!(router.stateful_chats(), 1);
+}
+
+/// A post handler that returns a single out-of-order batch of updates, and records
+/// the replies sent by the bot.
+#[derive(Clone, Default)]
+struct OutOfOrderAPI {
+    polled: Arc<std::sync::atomic::AtomicBool>,
+    replies: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl client::Post for OutOfOrderAPI {
+    async fn post(&self, method: String, req: String) -> Result<String> {
+        match method.as_str() {
+            "getUpdates" => {
+                let updates = if self.polled.swap(true, std::sync::atomic::Ordering::SeqCst) {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    vec![]
+                } else {
+                    [3, 1, 2]
+                        .into_iter()
+                        .map(|id| api::Update {
+                            update_id: id,
+                            message: Some(api::Message::new("qubyte", id.to_string())),
+                            ..Default::default()
+                        })
+                        .collect()
+                };
+                Ok(serde_json::to_string(&api::ApiResponse::Ok(updates))?)
+            }
+            "sendMessage" => {
+                let req: api::SendMessageRequest = serde_json::from_str(&req)?;
+                self.replies.lock().unwrap().push(req.text.clone());
+                Ok(serde_json::to_string(&api::ApiResponse::Ok(
+                    api::Message::new("mobot", req.text),
+                ))?)
+            }
+            _ => bail!("Unknown method: {}", method),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn sorted_updates() {
+    mobot::init_logger();
+    let fakeserver = OutOfOrderAPI::default();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+
+    // Earlier updates take longer to handle, so they'd finish last if handled concurrently.
+    router.add_route(Route::Default, |e: Event, _| async move {
+        let id: u64 = e.update.to_string().parse()?;
+        tokio::time::sleep(Duration::from_millis((4 - id) * 20)).await;
+        Ok(Action::ReplyText(e.update.to_string()))
+    });
+    let handle = router.spawn();
+
+    while fakeserver.replies.lock().unwrap().len() < 3 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Updates of the same chat are handled one at a time, in update_id order, not the
+    // order they were returned.
+    assert_eq!(*fakeserver.replies.lock().unwrap(), vec!["1", "2", "3"]);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn send_and_pin() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(Route::Default, |e: Event, _| async move {
+        let message = e.send_and_pin("Announcement!").await?;
+        Ok(Action::ReplyText(format!("pinned {}", message.message_id)))
+    });
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/announce").await.unwrap();
+
+    let updates = chat.collect(2).await;
+    assert_eq!(updates[0].to_string(), "Announcement!");
+    assert_eq!(
+        updates[1].to_string(),
+        format!("pinned {}", updates[0].message_id().unwrap())
+    );
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn injected_errors() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_error_handler(|_, _, _, err| async move {
+            ErrorAction::Reply(format!("failed: {}", err))
+        });
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(Route::Default, |e: Event, _| async move {
+        e.send_message("pong").await?;
+        Ok(Action::Done)
+    });
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    // The first sendMessage fails, so the error handler replies instead.
+    fakeserver
+        .fail_next(
+            "sendMessage",
+            api::ApiResponse::Err("Forbidden: bot was blocked by the user"),
+        )
+        .await;
+    chat.send_text("ping").await.unwrap();
+    let reply = chat.collect(1).await[0].to_string();
+    assert!(
+        reply.starts_with("failed: ") && reply.contains("blocked"),
+        "{}",
+        reply
+    );
+
+    // Failures are one-shot.
+    chat.send_text("ping").await.unwrap();
+    chat.expect_text("pong").await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn retry_handler() {
+    use mobot::handlers::WithRetry;
+
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_error_handler(|_, _, _, err| async move {
+            ErrorAction::Reply(format!("failed: {}", err))
+        });
+
+    // Fails on every attempt but the third.
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let flaky = {
+        let attempts = Arc::clone(&attempts);
+        move |_: Event, _: State<()>| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            async move {
+                if !attempt.is_multiple_of(3) {
+                    bail!("attempt {} failed", attempt);
+                }
+                Ok(Action::ReplyText(format!("ok after {}", attempt)))
+            }
+        }
+    };
+
+    router
+        .add_route(
+            Route::Message(Matcher::Exact("twice".into())),
+            flaky.clone().with_retry(2, Duration::from_millis(10)),
+        )
+        .add_route(
+            Route::Message(Matcher::Exact("once".into())),
+            flaky.with_retry(1, Duration::from_millis(10)),
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("twice").await.unwrap();
+    chat.expect_text("ok after 3").await;
+
+    // With a single retry, the second failure goes to the error handler.
+    chat.send_text("once").await.unwrap();
+    chat.expect_text("failed: attempt 5 failed").await;
+    assert_eq!(attempts.load(Ordering::SeqCst), 5);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn business_messages() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(
+        Route::BusinessMessage(Matcher::Any),
+        |e: Event, _| async move { Ok(Action::ReplyText(format!("on behalf: {}", e.update))) },
+    );
+    assert_eq!(router.allowed_updates(), vec!["business_message"]);
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mut message = api::Message::new("qubyte", "hello");
+    message.chat.id = chat.chat_id;
+    message.business_connection_id = Some("biz1".into());
+    chat.send_update(Update::BusinessMessage(message))
+        .await
+        .unwrap();
+
+    // Replies are sent on the same business connection.
+    let reply = chat.expect_text("on behalf: hello").await;
+    assert_eq!(reply.business_connection_id(), Some("biz1"));
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn forward_and_delete() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mods = fakeserver.create_chat("mods").await;
+    let mods_chat_id = mods.chat_id;
+
+    router
+        .add_route(
+            Route::Message(Matcher::Prefix("report".into())),
+            move |_, _| async move {
+                Ok(Action::Forward {
+                    to_chat_id: mods_chat_id.into(),
+                })
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::Prefix("spam".into())),
+            |_, _| async { Ok(Action::Delete) },
+        );
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    // Reported messages are forwarded to the mods chat.
+    chat.send_text("report: bad user").await.unwrap();
+    let forwarded = mods.expect_text("report: bad user").await;
+    let forwarded = api::Message::try_from(forwarded).unwrap();
+    assert_eq!(forwarded.forward_from.unwrap().first_name, "qubyte");
+    match forwarded.forward_origin {
+        Some(api::MessageOrigin::User { sender_user, .. }) => {
+            assert_eq!(sender_user.first_name, "qubyte")
+        }
+        origin => panic!("unexpected origin: {:?}", origin),
+    }
+
+    // Spam is deleted.
+    let mut spam = api::Message::new("qubyte", "spam spam spam");
+    spam.chat.id = chat.chat_id;
+    let spam_id = spam.message_id;
+    chat.send_update(Update::Message(spam)).await.unwrap();
+
+    // Once this is forwarded, the spam message has been received by the fake server.
+    chat.send_text("report: spammer").await.unwrap();
+    mods.expect_text("report: spammer").await;
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while fakeserver
+            .get_message(chat.chat_id, spam_id)
+            .await
+            .is_some()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("spam message wasn't deleted");
+    chat.expect_none(Duration::from_millis(100)).await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn forward_and_copy_messages() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mods = fakeserver.create_chat("mods").await;
+    let mods_chat_id = mods.chat_id;
+
+    router.add_route(
+        Route::Message(Matcher::Prefix("archive".into())),
+        move |e: Event, _| async move {
+            let chat_id = e.update.chat_id()?;
+            let message_ids = vec![e.update.message_id()?, -1];
+
+            let forwarded = e
+                .api
+                .forward_messages(&api::ForwardMessagesRequest::new(
+                    mods_chat_id,
+                    chat_id,
+                    message_ids.clone(),
+                ))
+                .await?;
+            let copied = e
+                .api
+                .copy_messages(&api::CopyMessagesRequest::new(
+                    mods_chat_id,
+                    chat_id,
+                    message_ids,
+                ))
+                .await?;
+
+            Ok(Action::ReplyText(format!(
+                "forwarded {}, copied {}",
+                forwarded.len(),
+                copied.len()
+            )))
+        },
+    );
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    // The missing message is skipped, so only one of each arrives.
+    chat.send_text("archive this").await.unwrap();
+    chat.expect_text("forwarded 1, copied 1").await;
+
+    let forwarded = api::Message::try_from(mods.expect_text("archive this").await).unwrap();
+    assert_eq!(forwarded.forward_from.unwrap().first_name, "qubyte");
+
+    let copied = api::Message::try_from(mods.expect_text("archive this").await).unwrap();
+    assert!(copied.forward_from.is_none());
+    assert!(copied.forward_origin.is_none());
+    assert!(fakeserver
+        .get_message(mods_chat_id, copied.message_id)
+        .await
+        .is_some());
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn leave_chat() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mods = fakeserver.create_chat("mods").await;
+    let mods_chat_id = mods.chat_id;
+
+    router
+        .add_route(
+            Route::Message(Matcher::Exact("/members".into())),
+            |e: Event, _| async move {
+                let count = e.get_chat_member_count().await?;
+                Ok(Action::ReplyText(format!("members: {}", count)))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::Exact("/leave".into())),
+            move |e: Event, _| async move {
+                assert!(e.leave_chat().await?);
+
+                // The bot is no longer in the chat, so lookups on it fail.
+                let after = e.get_chat_member_count().await;
+                e.api
+                    .send_message(&api::SendMessageRequest::new(
+                        mods_chat_id,
+                        format!("left, count failed: {}", after.is_err()),
+                    ))
+                    .await?;
+                Ok(Action::Done)
+            },
+        );
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    chat.send_text("/members").await.unwrap();
+    chat.expect_text("members: 2").await;
+
+    chat.send_text("/leave").await.unwrap();
+    mods.expect_text("left, count failed: true").await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn background_jobs() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_error_handler(|_, _, _, err| async move {
+            ErrorAction::Reply(format!("failed: {}", err))
+        });
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(Route::Default, |e: Event, _| async move {
+        let event = e.clone();
+        e.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            if event.update.to_string() == "fail" {
+                bail!("boom");
+            }
+            event.send_message("done").await?;
+            Ok(())
+        });
+
+        Ok(Action::ReplyText("working".into()))
+    });
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    chat.send_text("job").await.unwrap();
+    chat.expect_text("working").await;
+    chat.expect_text("done").await;
+
+    // Errors in background jobs go to the error handler.
+    chat.send_text("fail").await.unwrap();
+    chat.expect_text("working").await;
+    chat.expect_text("failed: boom").await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn poll_health() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let fakeserver2 = fake::FakeAPI::new();
+    let client2 = Client::new("token2".to_string()).with_post_handler(fakeserver2.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_account(client2)
+        .with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+    let mut health = router.health();
+    let mut health2 = router.account_health(1).unwrap();
+    assert!(router.account_health(2).is_none());
+    assert_eq!(*health.borrow(), PollHealth::default());
+
+    router.add_route(Route::Default, |_, _| async { Ok(Action::Done) });
+
+    // The first poll fails, then polling recovers.
+    fakeserver
+        .fail_next("getUpdates", api::ApiResponse::Err("Bad Gateway"))
+        .await;
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    health.changed().await.unwrap();
+    assert_eq!(health.borrow_and_update().consecutive_failures, 1);
+    assert!(health.borrow().last_success.is_none());
+
+    health.changed().await.unwrap();
+    assert_eq!(health.borrow().consecutive_failures, 0);
+    assert!(health.borrow().last_success.is_some());
+
+    // The other account's health is tracked separately.
+    health2.changed().await.unwrap();
+    assert_eq!(health2.borrow().consecutive_failures, 0);
+    assert!(health2.borrow().last_success.is_some());
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+/// A post handler that records the `limit` of every `getUpdates` call, and returns no
+/// updates.
+#[derive(Clone, Default)]
+struct PollLimitAPI {
+    limits: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+}
+
+#[async_trait::async_trait]
+impl client::Post for PollLimitAPI {
+    async fn post(&self, method: String, req: String) -> Result<String> {
+        if method != "getUpdates" {
+            bail!("unexpected method {}", method);
+        }
+        let req: serde_json::Value = serde_json::from_str(&req)?;
+        self.limits.lock().unwrap().push(req["limit"].clone());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        Ok(r#"{"ok": true, "result": []}"#.to_string())
+    }
+}
+
+#[tokio::test]
+async fn poll_limit() {
+    let fakeserver = PollLimitAPI::default();
+    let limits = Arc::clone(&fakeserver.limits);
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver);
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_poll_limit(5);
+    router.add_route(Route::Default, |_, _| async { Ok(Action::Done) });
+    let handle = router.spawn();
+
+    while limits.lock().unwrap().len() < 2 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    handle.shutdown().await;
+
+    assert!(limits.lock().unwrap().iter().all(|limit| *limit == 5));
+}
+
+#[tokio::test]
+async fn shutdown_mid_poll() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    // Use a long poll timeout, so the poll is still pending when we shut down.
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(60);
+    router.add_route(Route::Default, |_, _| async { Ok(Action::Done) });
+    let handle = router.spawn();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The handle reports the health of the router's only account.
+    assert_eq!(handle.health().borrow().consecutive_failures, 0);
+    assert!(handle.account_health(0).is_some());
+    assert!(handle.account_health(1).is_none());
+
+    tokio::time::timeout(Duration::from_secs(5), handle.shutdown())
+        .await
+        .expect("shutdown waited for the pending poll");
+}
+
+#[tokio::test]
+async fn animations() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    // Reply to GIFs with the same GIF.
+    router.add_route(
+        Route::Message(Matcher::Animation),
+        |e: Event, _| async move {
+            let file_id = e.update.animation()?.file_id.clone();
+            e.api
+                .send_animation(&api::SendAnimationRequest::new(
+                    e.update.chat_id()?,
+                    file_id,
+                ))
+                .await?;
+            Ok(Action::Done)
+        },
+    );
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mut message = api::Message::fake("qubyte");
+    message.chat.id = chat.chat_id;
+    message.animation = Some(api::Animation {
+        file_id: "gif123".into(),
+        ..Default::default()
+    });
+    chat.send_update(Update::Message(message)).await.unwrap();
+
+    let reply = chat.collect(1).await.remove(0);
+    assert_eq!(reply.animation().unwrap().file_id, "gif123");
+
+    // Text messages don't match.
+    chat.send_text("hello").await.unwrap();
+    chat.expect_none(Duration::from_millis(200)).await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn match_caption() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_match_captions(true);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(
+        Route::Message(Matcher::BotCommand("save".into())),
+        |e: Event, _| async move {
+            let message = api::Message::try_from(e.update)?;
+            Ok(Action::ReplyText(format!(
+                "saved: {}",
+                message.text_or_caption().unwrap()
+            )))
+        },
+    );
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    // With caption matching, text matchers fall back to the caption of media messages.
+    let mut message = api::Message::fake("qubyte");
+    message.chat.id = chat.chat_id;
+    message.photo = Some(vec![]);
+    message.caption = Some("/save holiday".into());
+    chat.send_update(Update::Message(message)).await.unwrap();
+    chat.expect_text("saved: /save holiday").await;
+
+    chat.send_text("/save note").await.unwrap();
+    chat.expect_text("saved: /save note").await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[test]
+fn match_caption_modes() {
+    let mut message = api::Message::fake("qubyte");
+    message.photo = Some(vec![]);
+    message.caption = Some("/report spam".into());
+    let update = api::Update {
+        message: Some(message),
+        ..Default::default()
+    };
+
+    // By default, only the message text is matched.
+    let route = Route::Message(Matcher::BotCommand("report".into()));
+    assert!(!route.match_update(&update));
+    assert!(route.match_update_with_captions(&update));
+    assert!(Route::Any(Matcher::Prefix("/report".into())).match_update_with_captions(&update));
+}
+
+#[tokio::test]
+async fn commands_macro() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    commands!(router,
+        "start": "Start the bot" => |_: Event, _: State<()>| async move {
+            Ok(Action::ReplyText("welcome".into()))
+        },
+        "help": "Show help" => |_: Event, _: State<()>| async move {
+            Ok(Action::ReplyText("help".into()))
+        },
+    );
+
+    let commands: Vec<(&str, &str)> = router
+        .commands()
+        .iter()
+        .map(|c| (c.command.as_str(), c.description.as_str()))
+        .collect();
+    assert_eq!(
+        commands,
+        vec![("start", "Start the bot"), ("help", "Show help")]
+    );
+
+    // The command menu is published to Telegram.
+    assert!(router.set_my_commands().await.unwrap());
+    assert_eq!(fakeserver.commands().await.len(), 2);
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/help").await.unwrap();
+    chat.expect_text("help").await;
+    chat.send_text("/start").await.unwrap();
+    chat.expect_text("welcome").await;
+    chat.send_text("hello").await.unwrap();
+    chat.expect_none(Duration::from_millis(500)).await;
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[test]
+fn match_length() {
+    let message_update = |message: api::Message| api::Update {
+        message: Some(message),
+        ..Default::default()
+    };
+
+    let empty = Route::Message(Matcher::Empty);
+    let short = Route::Message(Matcher::Length(1..=10));
+    let long = Route::Message(Matcher::Length(11..=usize::MAX));
+
+    let update = message_update(api::Message::new("qubyte", ""));
+    assert!(empty.match_update(&update));
+    assert!(!short.match_update(&update));
+
+    // Messages without text (e.g., photos) are empty too.
+    let mut photo = api::Message::fake("qubyte");
+    photo.photo = Some(vec![]);
+    assert!(empty.match_update(&message_update(photo)));
+
+    // Length is measured in characters, not bytes.
+    let update = message_update(api::Message::new("qubyte", "héllo wörld"));
+    assert!(!empty.match_update(&update));
+    assert!(!short.match_update(&update));
+    assert!(long.match_update(&update));
+
+    let update = message_update(api::Message::new("qubyte", "hello"));
+    assert!(short.match_update(&update));
+    assert!(!long.match_update(&update));
+
+    // Updates of other types don't match.
+    assert!(!empty.match_update(&api::Update::default()));
+}
+
+#[tokio::test]
+async fn reply_with() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+
+    router.add_route(Route::Default, |_: Event, _: State<()>| async move {
+        // The chat ID is filled in by the router.
+        Ok(Action::ReplyWith(
+            api::SendMessageRequest::new(0, "Pick one")
+                .with_reply_markup(api::ReplyMarkup::reply_keyboard_markup(vec![vec![
+                    "yes".into(),
+                    "no".into(),
+                ]]))
+                .into(),
+        ))
+    });
+
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hi").await.unwrap();
+    chat.expect_text("Pick one").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn dispatch() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    let mut router = Router::<()>::new(client);
+    router.add_route(
+        Route::Message(Matcher::Exact("ping".into())),
+        |_: Event, _: State<()>| async move { Ok(Action::ReplyText("pong".into())) },
+    );
+
+    let update = |text: &str| {
+        let mut message = api::Message::new("qubyte", text);
+        message.chat.id = chat.chat_id;
+        api::Update {
+            message: Some(message),
+            ..Default::default()
+        }
+    };
+
+    let actions = router.dispatch(update("ping")).await.unwrap();
+    assert!(matches!(&actions[..], [Action::ReplyText(text)] if text == "pong"));
+    chat.expect_text("pong").await;
+
+    // No handlers match, so there are no actions.
+    let actions = router.dispatch(update("hello")).await.unwrap();
+    assert!(actions.is_empty());
+    chat.expect_none(Duration::from_millis(100)).await;
+
+    assert_eq!(router.allowed_updates(), vec!["message"]);
+}
+
+#[tokio::test]
+async fn reply_in_topic() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |_: Event, _: State<()>| async move {
+        Ok(Action::ReplyText("pong".into()))
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "ping").into();
+    message.message_thread_id = Some(42);
+    message.is_topic_message = Some(true);
+    chat.send_update(Update::Message(message)).await.unwrap();
+
+    let reply = api::Message::try_from(chat.expect_text("pong").await).unwrap();
+    assert_eq!(reply.message_thread_id, Some(42));
+
+    // Replies to messages outside topics aren't sent to a topic.
+    chat.send_text("ping").await.unwrap();
+    let reply = api::Message::try_from(chat.expect_text("pong").await).unwrap();
+    assert_eq!(reply.message_thread_id, None);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn ask() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    // The echo route comes first, so answers only reach the `ReplyTo` route because the
+    // router checks pending prompts before normal routing.
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::BotCommand("name".into())),
+            |e: Event, _: State<()>| async move {
+                e.ask("name", "What's your name?").await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::Any),
+            |e: Event, _: State<()>| async move {
+                Ok(Action::ReplyText(format!("echo: {}", e.update.text()?)))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::ReplyTo("name".into())),
+            |e: Event, _: State<()>| async move {
+                Ok(Action::ReplyText(format!("Hello, {}!", e.update.text()?)))
+            },
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    let answer = |prompt: &api::Message, text: &str| {
+        let mut answer: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", text).into();
+        answer.reply_to_message = Some(Box::new(prompt.clone()));
+        Update::Message(answer)
+    };
+
+    chat.send_text("/name").await.unwrap();
+    let prompt = api::Message::try_from(chat.expect_text("What's your name?").await).unwrap();
+    chat.send_update(answer(&prompt, "Alice")).await.unwrap();
+    chat.expect_text("Hello, Alice!").await;
+
+    // The prompt was answered, so later replies to it are routed normally.
+    chat.send_update(answer(&prompt, "Bob")).await.unwrap();
+    chat.expect_text("echo: Bob").await;
+
+    // Asking again replaces the pending prompt.
+    chat.send_text("/name").await.unwrap();
+    let old = api::Message::try_from(chat.expect_text("What's your name?").await).unwrap();
+    chat.send_text("/name").await.unwrap();
+    let new = api::Message::try_from(chat.expect_text("What's your name?").await).unwrap();
+    chat.send_update(answer(&old, "Carol")).await.unwrap();
+    chat.expect_text("echo: Carol").await;
+    chat.send_update(answer(&new, "Dave")).await.unwrap();
+    chat.expect_text("Hello, Dave!").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn ask_expires() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_prompt_ttl(Duration::from_millis(50));
+    router
+        .add_route(
+            Route::Message(Matcher::BotCommand("name".into())),
+            |e: Event, _: State<()>| async move {
+                e.ask("name", "What's your name?").await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::ReplyTo("name".into())),
+            |e: Event, _: State<()>| async move {
+                Ok(Action::ReplyText(format!("Hello, {}!", e.update.text()?)))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::Any),
+            |e: Event, _: State<()>| async move {
+                Ok(Action::ReplyText(format!("echo: {}", e.update.text()?)))
+            },
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/name").await.unwrap();
+    let prompt = api::Message::try_from(chat.expect_text("What's your name?").await).unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // The prompt expired, so the answer is routed like any other message.
+    let mut answer: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "Alice").into();
+    answer.reply_to_message = Some(Box::new(prompt));
+    chat.send_update(Update::Message(answer)).await.unwrap();
+    chat.expect_text("echo: Alice").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn send_ephemeral() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        e.send_ephemeral("your code is 1234", Duration::from_millis(200))
+            .await?;
+        Ok(Action::Done)
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("code please").await.unwrap();
+    let message = api::Message::try_from(chat.expect_text("your code is 1234").await).unwrap();
+    assert!(fakeserver
+        .get_message(chat.chat_id, message.message_id)
+        .await
+        .is_some());
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while fakeserver
+            .get_message(chat.chat_id, message.message_id)
+            .await
+            .is_some()
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("ephemeral message wasn't deleted");
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn reply_markdown_escaped() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        e.reply_markdown_escaped(e.update.text()?).await?;
+        Ok(Action::Done)
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("*not bold* (v1.0)!").await.unwrap();
+    chat.expect_text(r"\*not bold\* \(v1\.0\)\!").await;
+
+    chat.send_text(r"C:\path \.").await.unwrap();
+    chat.expect_text(r"C:\\path \\\.").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn matched_route() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    async fn handle(e: Event, _: State<()>) -> Result<Action> {
+        Ok(Action::ReplyText(match e.matched_route() {
+            Some(Route::Message(Matcher::Prefix(_))) => "prefix".into(),
+            Some(Route::Message(_)) => "message".into(),
+            Some(Route::EditedMessage(_)) => "edited".into(),
+            route => format!("{:?}", route),
+        }))
+    }
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(Route::Message(Matcher::Prefix("/".into())), handle)
+        .add_route(Route::Message(Matcher::Any), handle)
+        .add_route(Route::EditedMessage(Matcher::Any), handle)
+        .add_route(Route::Default, handle);
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/start").await.unwrap();
+    chat.expect_text("prefix").await;
+    chat.send_text("hello").await.unwrap();
+    chat.expect_text("message").await;
+
+    let message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "hello!").into();
+    chat.send_update(Update::EditedMessage(message))
+        .await
+        .unwrap();
+    chat.expect_text("edited").await;
+    chat.send_callback_query("yes").await.unwrap();
+    chat.expect_text("Some(Any(Any))").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn handler_pipeline() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::BotCommand("add".into())),
+            |e: Event, _| async move {
+                assert!(e.prev_action().is_none());
+                let numbers = e
+                    .update
+                    .command_args()?
+                    .iter()
+                    .map(|arg| arg.parse::<i64>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Action::NextWith(serde_json::json!(numbers)))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::BotCommand("add".into())),
+            |e: Event, _| async move {
+                let numbers: Vec<i64> = serde_json::from_value(e.prev_value().unwrap().clone())?;
+                Ok(Action::NextWith(numbers.iter().sum::<i64>().into()))
+            },
+        )
+        .add_route(Route::Message(Matcher::Any), |e: Event, _| async move {
+            Ok(Action::ReplyText(match e.prev_value() {
+                Some(sum) => format!("sum: {}", sum),
+                None => format!("prev: {:?}", e.prev_action()),
+            }))
+        });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/add 1 2 3").await.unwrap();
+    chat.expect_text("sum: 6").await;
+    chat.send_text("hello").await.unwrap();
+    chat.expect_text("prev: None").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn reply_and_continue() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(Route::Message(Matcher::Any), |e: Event, _| async move {
+            assert!(e.last_sent_message_id().is_none());
+            Ok(Action::ReplyAndContinue("working...".into()))
+        })
+        .add_route(Route::Message(Matcher::Any), |e: Event, _| async move {
+            let message_id = e.last_sent_message_id().unwrap();
+            e.edit_message(message_id, format!("done: {}", e.update.text()?))
+                .await?;
+            Ok(Action::Done)
+        });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hello").await.unwrap();
+    let sent = api::Message::try_from(chat.expect_text("working...").await).unwrap();
+    let edited = chat.expect_text("done: hello").await;
+    assert!(matches!(edited, Update::EditedMessage(_)));
+    assert_eq!(edited.message_id().unwrap(), sent.message_id);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn post_hook() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let log = Arc::new(tokio::sync::Mutex::new(vec![]));
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_error_handler(
+            |_, _, _, err| async move { ErrorAction::Reply(format!("failed: {}", err)) },
+        )
+        .with_post_hook({
+            let log = Arc::clone(&log);
+            move |e: Event, action: Option<Action>| {
+                let log = Arc::clone(&log);
+                async move {
+                    log.lock().await.push(format!(
+                        "{}: {:?} => {:?}",
+                        e.update.text().unwrap_or_default(),
+                        e.matched_route(),
+                        action
+                    ));
+                }
+            }
+        });
+    router
+        .add_route(Route::Message(Matcher::Any), |_, _| async {
+            Ok(Action::Next)
+        })
+        .add_route(
+            Route::Message(Matcher::Exact("fail".into())),
+            |_, _| async { bail!("oops") },
+        )
+        .add_route(Route::Message(Matcher::Any), |_, _| async {
+            Ok(Action::ReplyText("pong".into()))
+        });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("ping").await.unwrap();
+    chat.expect_text("pong").await;
+    chat.send_text("fail").await.unwrap();
+    chat.expect_text("failed: oops").await;
+
+    // The hook runs after the action is carried out, so wait for it.
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while log.lock().await.len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("post hook wasn't called");
+
+    assert_eq!(
+        *log.lock().await,
+        vec![
+            r#"ping: Some(Message(Any)) => Some(ReplyText("pong"))"#.to_string(),
+            r#"fail: Some(Message(Exact("fail"))) => None"#.to_string(),
+        ]
+    );
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn chat_join_request() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::ChatJoinRequest(Matcher::Regex("(?i)crypto".into())),
+            |e: Event, _| async move {
+                let req = e.update.get_chat_join_request()?;
+                e.api
+                    .decline_chat_join_request(&api::DeclineChatJoinRequestRequest::new(
+                        req.chat.id,
+                        req.from.id,
+                    ))
+                    .await?;
+                Ok(Action::ReplyText(format!(
+                    "declined {}",
+                    req.from.first_name
+                )))
+            },
+        )
+        .add_route(
+            Route::ChatJoinRequest(Matcher::Any),
+            |e: Event, _| async move {
+                let req = e.update.get_chat_join_request()?;
+                e.api
+                    .approve_chat_join_request(&api::ApproveChatJoinRequestRequest::new(
+                        e.update.chat_id()?,
+                        e.update.from_user()?.id,
+                    ))
+                    .await?;
+                Ok(Action::ReplyText(format!(
+                    "approved {}",
+                    req.from.first_name
+                )))
+            },
+        );
+    assert_eq!(router.allowed_updates(), vec!["chat_join_request"]);
+    let handle = router.spawn();
+
+    let group = fakeserver.create_chat("group").await;
+    let join_request = |name: &str, bio: &str| api::ChatJoinRequest {
+        chat: api::Chat {
+            id: group.chat_id,
+            chat_type: "supergroup".into(),
+            ..Default::default()
+        },
+        from: api::User {
+            id: 42,
+            first_name: name.into(),
+            ..Default::default()
+        },
+        bio: Some(bio.into()),
+        ..Default::default()
+    };
+
+    group
+        .send_update(Update::ChatJoinRequest(join_request(
+            "alice",
+            "I like cats",
+        )))
+        .await
+        .unwrap();
+    group.expect_text("approved alice").await;
+
+    group
+        .send_update(Update::ChatJoinRequest(join_request("bob", "CRYPTO gains")))
+        .await
+        .unwrap();
+    group.expect_text("declined bob").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn payments() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::BotCommand("buy".into())),
+            |e: Event, _| async move {
+                e.api
+                    .send_invoice(
+                        &api::SendInvoiceRequest::new(
+                            e.update.chat_id()?,
+                            "Coffee",
+                            "A cup of coffee",
+                            "coffee",
+                            "provider-token",
+                            "USD",
+                            vec![
+                                api::LabeledPrice::new("Coffee", 250),
+                                api::LabeledPrice::new("Tip", 50),
+                            ],
+                        )
+                        .with_need_name(true),
+                    )
+                    .await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::PreCheckoutQuery(Matcher::Exact("tea".into())),
+            |e: Event, _| async move {
+                let query = e.update.get_pre_checkout_query()?;
+                e.api
+                    .answer_pre_checkout_query(&api::AnswerPreCheckoutQueryRequest::error(
+                        &query.id,
+                        "Out of tea",
+                    ))
+                    .await?;
+                e.api
+                    .send_message(&api::SendMessageRequest::new(query.from.id, "no tea"))
+                    .await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::PreCheckoutQuery(Matcher::Any),
+            |e: Event, _| async move {
+                let query = e.update.get_pre_checkout_query()?;
+                e.api
+                    .answer_pre_checkout_query(&api::AnswerPreCheckoutQueryRequest::ok(&query.id))
+                    .await?;
+                e.api
+                    .send_message(&api::SendMessageRequest::new(query.from.id, "confirmed"))
+                    .await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::SuccessfulPayment),
+            |e: Event, _| async move {
+                let payment = e.successful_payment()?;
+                Ok(Action::ReplyText(format!(
+                    "paid {} {} for {} ({})",
+                    payment.total_amount,
+                    payment.currency,
+                    payment.invoice_payload,
+                    payment.telegram_payment_charge_id
+                )))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::RefundedPayment),
+            |e: Event, _| async move {
+                let payment = e.refunded_payment()?;
+                Ok(Action::ReplyText(format!(
+                    "refunded {} ({})",
+                    payment.invoice_payload, payment.telegram_payment_charge_id
+                )))
+            },
+        );
+    assert_eq!(
+        router.allowed_updates(),
+        vec!["message", "pre_checkout_query"]
+    );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("alice").await;
+    chat.send_text("/buy").await.unwrap();
+    let update = chat.recv_update().await.unwrap();
+    let invoice = update.get_new().unwrap().invoice.as_ref().unwrap();
+    assert_eq!(invoice.title, "Coffee");
+    assert_eq!(invoice.total_amount, 300);
+
+    let pre_checkout_query = |id: &str, payload: &str| api::PreCheckoutQuery {
+        id: id.into(),
+        from: api::User {
+            id: chat.chat_id,
+            first_name: chat.from.clone(),
+            ..Default::default()
+        },
+        currency: "USD".into(),
+        total_amount: 300,
+        invoice_payload: payload.into(),
+        ..Default::default()
+    };
+
+    chat.send_update(Update::PreCheckoutQuery(pre_checkout_query("q1", "tea")))
+        .await
+        .unwrap();
+    chat.expect_text("no tea").await;
+    let answer = fakeserver.pre_checkout_answer("q1").await.unwrap();
+    assert!(!answer.ok);
+    assert_eq!(answer.error_message.as_deref(), Some("Out of tea"));
+
+    chat.send_update(Update::PreCheckoutQuery(pre_checkout_query("q2", "coffee")))
+        .await
+        .unwrap();
+    chat.expect_text("confirmed").await;
+    assert!(fakeserver.pre_checkout_answer("q2").await.unwrap().ok);
+
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "alice", "").into();
+    message.text = None;
+    message.successful_payment = Some(api::SuccessfulPayment {
+        currency: "USD".into(),
+        total_amount: 300,
+        invoice_payload: "coffee".into(),
+        telegram_payment_charge_id: "charge1".into(),
+        ..Default::default()
+    });
+    chat.send_update(Update::Message(message.clone()))
+        .await
+        .unwrap();
+    chat.expect_text("paid 300 USD for coffee (charge1)").await;
+
+    message.successful_payment = None;
+    message.refunded_payment = Some(api::RefundedPayment {
+        currency: "USD".into(),
+        total_amount: 300,
+        invoice_payload: "coffee".into(),
+        telegram_payment_charge_id: "charge1".into(),
+        ..Default::default()
+    });
+    chat.send_update(Update::Message(message)).await.unwrap();
+    chat.expect_text("refunded coffee (charge1)").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn my_permissions() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_permissions_ttl(Duration::from_secs(1));
+    router
+        .add_route(Route::Message(Matcher::Any), |e: Event, _| async move {
+            Ok(Action::ReplyText(format!(
+                "can delete: {}",
+                e.my_permissions().await?.can_delete_messages()
+            )))
+        })
+        .add_route(Route::MyChatMember(Matcher::Any), |_, _| async {
+            Ok(Action::ReplyText("status changed".into()))
+        });
+    let handle = router.spawn();
+
+    let group = fakeserver.create_chat("group").await;
+    let admin = api::ChatMember {
+        status: "administrator".into(),
+        user: fakeserver.me(),
+        can_delete_messages: Some(true),
+        ..Default::default()
+    };
+
+    group.send_text("spam").await.unwrap();
+    group.expect_text("can delete: false").await;
+
+    // The bot's membership is cached...
+    fakeserver
+        .set_chat_member(group.chat_id, admin.clone())
+        .await;
+    group.send_text("spam").await.unwrap();
+    group.expect_text("can delete: false").await;
+
+    // ...but refreshed when the bot's status changes.
+    group
+        .send_update(Update::MyChatMember(api::ChatMemberUpdated {
+            chat: api::Chat {
+                id: group.chat_id,
+                ..Default::default()
+            },
+            new_chat_member: admin,
+            ..Default::default()
+        }))
+        .await
+        .unwrap();
+    group.expect_text("status changed").await;
+    group.send_text("spam").await.unwrap();
+    group.expect_text("can delete: true").await;
+
+    // ...or when the cached entry expires.
+    let mut member = api::ChatMember {
+        status: "member".into(),
+        user: fakeserver.me(),
+        ..Default::default()
+    };
+    fakeserver
+        .set_chat_member(group.chat_id, member.clone())
+        .await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    group.send_text("spam").await.unwrap();
+    group.expect_text("can delete: false").await;
+
+    // Owners can always delete messages.
+    member.status = "creator".into();
+    assert!(member.can_delete_messages());
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn chat_member_updates() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::MyChatMember(Matcher::Any),
+            |e: Event, _| async move {
+                let update = e.update.get_chat_member_updated()?;
+                Ok(Action::ReplyText(format!(
+                    "bot joined: {}, left: {}",
+                    update.joined(),
+                    update.left()
+                )))
+            },
+        )
+        .add_route(
+            Route::ChatMember(Matcher::Exact("kicked".into())),
+            |e: Event, _| async move {
+                let update = e.update.get_chat_member_updated()?;
+                Ok(Action::ReplyText(format!(
+                    "{} was banned",
+                    update.new_chat_member.user.first_name
+                )))
+            },
+        )
+        .add_route(Route::Default, |_, _| async { Ok(Action::Done) });
+
+    // `chat_member` updates must be requested explicitly.
+    assert!(router
+        .allowed_updates()
+        .contains(&"chat_member".to_string()));
+    assert!(router.allowed_updates().contains(&"message".to_string()));
+    let handle = router.spawn();
+
+    let group = fakeserver.create_chat("group").await;
+    let member_update = |name: &str, old_status: &str, new_status: &str| api::ChatMemberUpdated {
+        chat: api::Chat {
+            id: group.chat_id,
+            chat_type: "group".into(),
+            ..Default::default()
+        },
+        old_chat_member: api::ChatMember {
+            status: old_status.into(),
+            ..Default::default()
+        },
+        new_chat_member: api::ChatMember {
+            status: new_status.into(),
+            user: api::User {
+                first_name: name.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    group
+        .send_update(Update::MyChatMember(member_update("bot", "left", "member")))
+        .await
+        .unwrap();
+    group.expect_text("bot joined: true, left: false").await;
+
+    group
+        .send_update(Update::ChatMember(member_update(
+            "mallory", "member", "kicked",
+        )))
+        .await
+        .unwrap();
+    group.expect_text("mallory was banned").await;
+
+    // Other member updates go to the default handler.
+    group
+        .send_update(Update::ChatMember(member_update("alice", "left", "member")))
+        .await
+        .unwrap();
+    group.expect_none(Duration::from_millis(200)).await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn venues() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(Route::Message(Matcher::Venue), |e: Event, _| async move {
+            let venue = e.update.venue()?;
+            Ok(Action::ReplyText(format!("see you at {}", venue.title)))
+        })
+        .add_route(Route::Message(Matcher::Any), |e: Event, _| async move {
+            e.api
+                .send_venue(
+                    &api::SendVenueRequest::new(
+                        e.update.chat_id()?,
+                        40.7484,
+                        -73.9857,
+                        "Empire State Building",
+                        "20 W 34th St, New York",
+                    )
+                    .with_google_place_id("ChIJaXQRs6lZwokRY6EFpJnhNNE"),
+                )
+                .await?;
+            Ok(Action::Done)
+        });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("where?").await.unwrap();
+    let message = api::Message::try_from(chat.recv_update().await.unwrap()).unwrap();
+    let venue = message.venue.unwrap();
+    assert_eq!(venue.title, "Empire State Building");
+    assert_eq!(venue.location.latitude, 40.7484);
+    assert_eq!(message.location.unwrap().longitude, -73.9857);
+
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "").into();
+    message.text = None;
+    message.venue = Some(api::Venue {
+        title: "Joe's Pizza".into(),
+        ..Default::default()
+    });
+    chat.send_update(Update::Message(message)).await.unwrap();
+    chat.expect_text("see you at Joe's Pizza").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn live_location() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::BotCommand("track".into())),
+            |e: Event, _| async move {
+                let chat_id = e.update.chat_id()?;
+                let message = e
+                    .api
+                    .send_location(
+                        &api::SendLocationRequest::new(chat_id, 51.5, -0.12)
+                            .with_live_period(3600)
+                            .with_proximity_alert_radius(100),
+                    )
+                    .await?;
+                e.api
+                    .edit_message_live_location(
+                        &api::EditMessageLiveLocationRequest::new(
+                            api::EditMessageBase::new()
+                                .with_chat_id(chat_id)
+                                .with_message_id(message.message_id),
+                            51.6,
+                            -0.13,
+                        )
+                        .with_heading(90),
+                    )
+                    .await?;
+                e.api
+                    .stop_message_live_location(&api::StopMessageLiveLocationRequest::new(
+                        api::EditMessageBase::new()
+                            .with_chat_id(chat_id)
+                            .with_message_id(message.message_id),
+                    ))
+                    .await?;
+                Ok(Action::Done)
+            },
+        )
+        .add_route(
+            Route::EditedMessage(Matcher::Location),
+            |e: Event, _| async move {
+                let location = e.update.location()?;
+                Ok(Action::ReplyText(format!(
+                    "you're at {}, {}",
+                    location.latitude, location.longitude
+                )))
+            },
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("/track").await.unwrap();
+
+    let Update::Message(sent) = chat.recv_update().await.unwrap() else {
+        panic!("expected a location message");
+    };
+    let location = sent.location.unwrap();
+    assert_eq!(
+        (location.latitude, location.live_period),
+        (51.5, Some(3600))
+    );
+    assert_eq!(location.proximity_alert_radius, Some(100));
+
+    let Update::EditedMessage(edited) = chat.recv_update().await.unwrap() else {
+        panic!("expected an edited location");
+    };
+    let location = edited.location.unwrap();
+    assert_eq!((location.latitude, location.heading), (51.6, Some(90)));
+
+    let Update::EditedMessage(stopped) = chat.recv_update().await.unwrap() else {
+        panic!("expected a stopped location");
+    };
+    assert_eq!(stopped.location.unwrap().live_period, None);
+
+    // Users' live location updates arrive as edited messages.
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "").into();
+    message.text = None;
+    message.location = Some(api::Location {
+        latitude: 1.5,
+        longitude: 2.5,
+        live_period: Some(60),
+        ..Default::default()
+    });
+    chat.send_update(Update::EditedMessage(message))
+        .await
+        .unwrap();
+    chat.expect_text("you're at 1.5, 2.5").await;
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn shared_users_and_chats() {
+    let button = api::KeyboardButton::from("Pick a user to promote")
+        .with_request_users(api::KeyboardButtonRequestUsers::new(1).with_user_is_bot(false));
+    assert_eq!(
+        serde_json::to_value(&button).unwrap(),
+        serde_json::json!({
+            "text": "Pick a user to promote",
+            "request_users": {"request_id": 1, "user_is_bot": false}
+        })
+    );
+
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router
+        .add_route(
+            Route::Message(Matcher::UsersShared),
+            |e: Event, _| async move {
+                let shared = e.update.users_shared()?;
+                Ok(Action::ReplyText(format!(
+                    "promoting {} (request {})",
+                    shared.users[0].user_id, shared.request_id
+                )))
+            },
+        )
+        .add_route(
+            Route::Message(Matcher::ChatShared),
+            |e: Event, _| async move {
+                let shared = e.update.chat_shared()?;
+                Ok(Action::ReplyText(format!("joining {}", shared.chat_id)))
+            },
+        );
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "").into();
+    message.text = None;
+    message.users_shared = Some(api::UsersShared {
+        request_id: 1,
+        users: vec![api::SharedUser {
+            user_id: 42,
+            ..Default::default()
+        }],
+    });
+    chat.send_update(Update::Message(message)).await.unwrap();
+    chat.expect_text("promoting 42 (request 1)").await;
+
+    let mut message: api::Message = fake::FakeMessage::text(chat.chat_id, "qubyte", "").into();
+    message.text = None;
+    message.chat_shared = Some(api::ChatShared {
+        request_id: 2,
+        chat_id: -100,
+        ..Default::default()
+    });
+    chat.send_update(Update::Message(message)).await.unwrap();
+    chat.expect_text("joining -100").await;
+
+    handle.shutdown().await;
+}
+
+/// `PoisonAPI` serves a fixed list of raw updates, some of which can't be parsed, and
+/// records the texts of the messages sent by the bot.
+struct PoisonAPI {
+    updates: Vec<serde_json::Value>,
+    sent: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait::async_trait]
+impl client::Post for PoisonAPI {
+    async fn post(&self, method: String, req: String) -> Result<String> {
+        let req: serde_json::Value = serde_json::from_str(&req)?;
+        let result = match method.as_str() {
+            "getUpdates" => {
+                let offset = req["offset"].as_i64().unwrap_or_default();
+                let limit = req["limit"].as_u64().unwrap_or(100) as usize;
+                let updates: Vec<_> = self
+                    .updates
+                    .iter()
+                    .filter(|u| u["update_id"].as_i64().unwrap() >= offset)
+                    .take(limit)
+                    .cloned()
+                    .collect();
+                if updates.is_empty() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+                serde_json::json!(updates)
+            }
+            "sendMessage" => {
+                self.sent
+                    .lock()
+                    .unwrap()
+                    .push(req["text"].as_str().unwrap().to_string());
+                serde_json::to_value(api::Message::default())?
+            }
+            _ => bail!("unexpected method {}", method),
+        };
+        Ok(serde_json::json!({"ok": true, "result": result}).to_string())
+    }
+}
+
+#[tokio::test]
+async fn skip_malformed_update() {
+    let message = |update_id: i64, text: &str| {
+        let message: api::Message = fake::FakeMessage::text(1, "qubyte", text).into();
+        serde_json::json!({"update_id": update_id, "message": message})
+    };
+    let poison = serde_json::json!({"update_id": 11, "message": {"message_id": "oops"}});
+
+    let sent = Arc::new(std::sync::Mutex::new(vec![]));
+    let client = Client::new("token".to_string()).with_post_handler(PoisonAPI {
+        updates: vec![message(10, "one"), poison, message(12, "two")],
+        sent: Arc::clone(&sent),
+    });
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |e: Event, _| async move {
+        Ok(Action::ReplyText(e.update.text()?.to_string()))
+    });
+    let handle = router.spawn();
+
+    // The update before the bad one is handled, and so is the one after it.
+    for _ in 0..100 {
+        if sent.lock().unwrap().len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(*sent.lock().unwrap(), vec!["one", "two"]);
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn keep_typing() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |e: Event, _| async move {
+        let _typing = e.keep_chat_action(api::ChatAction::Typing, Duration::from_millis(50));
+        tokio::time::sleep(Duration::from_millis(180)).await;
+        Ok(Action::ReplyText("done".into()))
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("slow").await.unwrap();
+    chat.expect_text("done").await;
+
+    // The action is refreshed while the handler runs, and stops once it returns.
+    let actions = fakeserver.chat_actions(chat.chat_id).await;
+    assert!(actions.len() >= 3, "too few chat actions: {:?}", actions);
+    assert!(actions.iter().all(|a| *a == api::ChatAction::Typing));
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(
+        fakeserver.chat_actions(chat.chat_id).await.len(),
+        actions.len()
+    );
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn delete_webhook() {
+    let fakeserver = fake::FakeAPI::new();
+    fakeserver.set_webhook("https://example.com/bot").await;
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+    assert_eq!(
+        api.get_webhook_info().await.unwrap().url,
+        "https://example.com/bot"
+    );
+    let err = api
+        .get_updates(&api::GetUpdatesRequest::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<api::ApiError>(),
+        Some(api::ApiError::Conflict(_))
+    ));
+
+    // The router deletes the webhook before polling.
+    let mut router = Router::<()>::new(client)
+        .with_poll_timeout_s(1)
+        .with_delete_webhook(true);
+    router.add_route(Route::Default, |_, _| async {
+        Ok(Action::ReplyText("polling".into()))
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hi").await.unwrap();
+    chat.expect_text("polling").await;
+    assert_eq!(api.get_webhook_info().await.unwrap().url, "");
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn answer_inline_query() {
+    let answers = Arc::new(std::sync::Mutex::new(vec![]));
+    let client = Client::new("token".to_string()).with_post_handler_fn({
+        let answers = Arc::clone(&answers);
+        move |method: String, req: String| {
+            assert_eq!(method, "answerInlineQuery");
+            answers
+                .lock()
+                .unwrap()
+                .push(serde_json::from_str::<serde_json::Value>(&req)?);
+            Ok(r#"{"ok": true, "result": true}"#.to_string())
+        }
+    });
+
+    let mut router = Router::<()>::new(client);
+    router.add_route(
+        Route::InlineQuery(Matcher::Any),
+        |e: Event, _: State<()>| async move {
+            let query = e.inline_query()?.query.clone();
+            if query.is_empty() {
+                e.answer_inline_empty(api::InlineQueryResultsButton::start(
+                    "Type something to echo",
+                    "help",
+                ))
+                .await?;
+                return Ok(Action::Done);
+            }
+            e.answer(vec![api::InlineQueryResultArticle::new(
+                "1",
+                "Echo",
+                query.to_uppercase(),
+            )])
+            .await?;
+            Ok(Action::Done)
+        },
+    );
+
+    let update = |id: &str, query: &str| api::Update {
+        inline_query: Some(api::InlineQuery {
+            id: id.into(),
+            from: "qubyte".into(),
+            query: query.into(),
+            offset: "".into(),
+        }),
+        ..Default::default()
+    };
+    router.dispatch(update("q1", "hello")).await.unwrap();
+    router.dispatch(update("q2", "")).await.unwrap();
+
+    let answers = answers.lock().unwrap();
+    assert_eq!(answers.len(), 2);
+    assert_eq!(answers[0]["inline_query_id"], "q1");
+    assert_eq!(
+        answers[0]["results"][0]["input_message_content"]["message_text"],
+        "HELLO"
+    );
+
+    // Empty answers still send the (empty) results.
+    assert_eq!(answers[1]["inline_query_id"], "q2");
+    assert_eq!(answers[1]["results"], serde_json::json!([]));
+    assert_eq!(answers[1]["button"]["start_parameter"], "help");
+}
+
+#[tokio::test]
+async fn progress_elapsed() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        ProgressBar::new()
+            .with_update_interval(Duration::from_millis(20))
+            .with_min_edit_interval(Duration::ZERO)
+            .with_elapsed_after(Duration::from_millis(100))
+            .start(&e, async {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                Ok(())
+            })
+            .await?;
+        Ok(Action::Done)
+    });
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("go").await.unwrap();
+    chat.expect_text("...").await;
+
+    // The first updates only show the bar, and once the task runs past the threshold,
+    // the elapsed time is shown too.
+    chat.expect_text("\u{258E}").await;
+    loop {
+        let text = chat.recv_update().await.unwrap().to_string();
+        if text.ends_with(" still working\u{2026} 0s") {
+            break;
+        }
+        assert!(!text.contains(' '), "unexpected progress: {}", text);
+    }
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn unknown_updates() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+    let reactions = Arc::new(std::sync::Mutex::new(vec![]));
+
+    let mut router = Router::<()>::new(client).with_extra_update_types(&["message_reaction"]);
+    router.add_route(Route::Default, |_: Event, _: State<()>| async move {
+        bail!("unknown updates shouldn't reach the default route")
+    });
+    router.add_route(Route::Unknown(Matcher::Exact("message_reaction".into())), {
+        let reactions = Arc::clone(&reactions);
+        move |e: Event, _: State<()>| {
+            let reactions = Arc::clone(&reactions);
+            async move {
+                let reaction = &e.raw_update().unwrap().other["message_reaction"];
+                reactions
+                    .lock()
+                    .unwrap()
+                    .push(reaction["new_reaction"][0]["emoji"].to_string());
+                Ok(Action::Done)
+            }
+        }
+    });
+
+    // Reactions aren't sent by default, so all the routed update types are requested
+    // explicitly, along with the extra one.
+    let allowed_updates = router.allowed_updates();
+    assert!(allowed_updates.contains(&"message_reaction".to_string()));
+    assert!(allowed_updates.contains(&"message".to_string()));
+    assert!(allowed_updates.contains(&"callback_query".to_string()));
+
+    let update = |json: &str| serde_json::from_str::<api::Update>(json).unwrap();
+    let actions = router
+        .dispatch(update(
+            r#"{
+                "update_id": 1,
+                "message_reaction": {
+                    "chat": {"id": -1001, "type": "supergroup"},
+                    "message_id": 10,
+                    "date": 1700000000,
+                    "old_reaction": [],
+                    "new_reaction": [{"type": "emoji", "emoji": "👍"}]
+                }
+            }"#,
+        ))
+        .await
+        .unwrap();
+    assert!(matches!(&actions[..], [Action::Done]));
+    assert_eq!(*reactions.lock().unwrap(), [r#""👍""#]);
+
+    // Other unknown update types don't match, and aren't passed to the default route.
+    let actions = router
+        .dispatch(update(
+            r#"{"update_id": 2, "chat_boost": {"chat": {"id": -1001}}}"#,
+        ))
+        .await
+        .unwrap();
+    assert!(actions.is_empty());
+    assert_eq!(reactions.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn chatless_updates() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+    let errors = Arc::new(std::sync::Mutex::new(vec![]));
+
+    let mut router = Router::<TestApp>::new(client).with_error_handler({
+        let errors = Arc::clone(&errors);
+        move |_, chat_id, _, _| {
+            errors.lock().unwrap().push(chat_id);
+            async {}
+        }
+    });
+    router.add_route(
+        Route::Unknown(Matcher::Any),
+        |e: Event, state: State<TestApp>| async move {
+            let mut state = state.get().write().await;
+            state.counter += 1;
+
+            let update = &e.raw_update().unwrap().other;
+            match update.get("poll") {
+                Some(poll) if poll["is_closed"] == true => bail!("poll is closed"),
+                Some(_) => Ok(Action::NextWith(state.counter.into())),
+                None => Ok(Action::ReplyText(format!("answers: {}", state.counter))),
+            }
+        },
+    );
+
+    // Poll answers are keyed by the user who answered.
+    let chat = fakeserver.create_chat("qubyte").await;
+    let update = |json: String| serde_json::from_str::<api::Update>(&json).unwrap();
+    for update_id in 1..=2 {
+        router
+            .dispatch(update(format!(
+                r#"{{
+                    "update_id": {},
+                    "poll_answer": {{
+                        "poll_id": "p1",
+                        "user": {{"id": {}, "is_bot": false, "first_name": "qubyte"}},
+                        "option_ids": [0]
+                    }}
+                }}"#,
+                update_id, chat.chat_id
+            )))
+            .await
+            .unwrap();
+    }
+    chat.expect_text("answers: 1").await;
+    chat.expect_text("answers: 2").await;
+
+    // Polls have no chat, so they don't share a per-chat state...
+    let poll = |update_id, is_closed| {
+        update(format!(
+            r#"{{
+                "update_id": {},
+                "poll": {{"id": "p1", "question": "?", "options": [], "is_closed": {}}}
+            }}"#,
+            update_id, is_closed
+        ))
+    };
+    for update_id in 3..=4 {
+        let actions = router.dispatch(poll(update_id, false)).await.unwrap();
+        assert!(matches!(&actions[..], [Action::NextWith(n)] if n == 1));
+    }
+
+    // ...and errors aren't passed to the error handler, which has no chat to reply to.
+    let actions = router.dispatch(poll(5, true)).await.unwrap();
+    assert!(actions.is_empty());
+    assert!(errors.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn progress_throttled() {
+    mobot::init_logger();
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    let (shutdown_notifier, shutdown_tx) = router.shutdown();
+
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        ProgressBar::new()
+            .with_update_interval(Duration::from_millis(10))
+            .with_min_edit_interval(Duration::from_millis(100))
+            .start(&e, async {
+                tokio::time::sleep(Duration::from_millis(350)).await;
+                Ok(())
+            })
+            .await?;
+        Ok(Action::Done)
+    });
+
+    tokio::spawn(async move {
+        router.start().await;
+    });
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("go").await.unwrap();
+    chat.expect_text("...").await;
+
+    // The bar is updated ~35 times, but only edited every 100ms, until it's done.
+    let mut edits = 0;
+    let mut last_edit = tokio::time::Instant::now();
+    loop {
+        let text = chat.recv_update().await.unwrap().to_string();
+        if text.ends_with('\u{2714}') {
+            break;
+        }
+        edits += 1;
+        last_edit = tokio::time::Instant::now();
+    }
+    assert!(
+        (2..=5).contains(&edits),
+        "unexpected number of edits: {}",
+        edits
+    );
+
+    // The final edit waits for the interval too (with some slack for delivery delays).
+    assert!(last_edit.elapsed() >= Duration::from_millis(80));
+
+    shutdown_tx.send(()).await.unwrap();
+    shutdown_notifier.notified().await;
+}
+
+#[tokio::test]
+async fn reply_to_deleted_message() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+
+    let mut router = Router::<()>::new(client).with_poll_timeout_s(1);
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        let message_id = e.update.message_id()?;
+        if e.update.text()? == "delete me" {
+            e.delete_message(message_id).await?;
+        }
+
+        // Replies to deleted messages fail, unless sending without a reply is allowed.
+        if e.reply("reply").await.is_err() {
+            let reply_parameters =
+                api::ReplyParameters::new(message_id).with_allow_sending_without_reply(true);
+            e.reply_with("sent anyway", reply_parameters).await?;
+        }
+        Ok(Action::Done)
+    });
+    let handle = router.spawn();
+
+    let chat = fakeserver.create_chat("qubyte").await;
+    chat.send_text("hello").await.unwrap();
+    let message = api::Message::try_from(chat.expect_text("reply").await).unwrap();
+    assert_eq!(message.reply_to_message.unwrap().text.unwrap(), "hello");
+
+    chat.send_text("delete me").await.unwrap();
+    let message = api::Message::try_from(chat.expect_text("sent anyway").await).unwrap();
+    assert!(message.reply_to_message.is_none());
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn call_log() {
+    let fakeserver = fake::FakeAPI::new().with_call_log();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    let mut router = Router::<()>::new(client);
+    router.add_route(Route::Default, |e: Event, _: State<()>| async move {
+        e.send_chat_action(api::ChatAction::Typing).await?;
+        let message = e.send_message("working...").await?;
+        e.delete_message(message.message_id).await?;
+        Ok(Action::ReplyText("done".into()))
+    });
+
+    let mut message = api::Message::new("qubyte", "go");
+    message.chat.id = chat.chat_id;
+    router
+        .dispatch(api::Update {
+            message: Some(message),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let log = fakeserver.call_log().await;
+    assert_eq!(
+        log.iter()
+            .map(|(method, _)| method.as_str())
+            .collect::<Vec<_>>(),
+        [
+            "sendChatAction",
+            "sendMessage",
+            "deleteMessage",
+            "sendMessage"
+        ]
+    );
+    assert_eq!(log[0].1["action"], "typing");
+    assert_eq!(log[1].1["text"], "working...");
+    assert_eq!(log[3].1["text"], "done");
+    assert_eq!(log[3].1["chat_id"], chat.chat_id);
+}
+
+#[tokio::test]
+async fn dedup_updates() {
+    let fakeserver = fake::FakeAPI::new();
+    let client = Client::new("token".to_string()).with_post_handler(fakeserver.clone());
+    let chat = fakeserver.create_chat("qubyte").await;
+    let handled = Arc::new(AtomicUsize::new(0));
+
+    let mut router = Router::<()>::new(client).with_dedup(2);
+    router.add_route(Route::Default, {
+        let handled = Arc::clone(&handled);
+        move |_: Event, _: State<()>| {
+            let handled = Arc::clone(&handled);
+            async move {
+                handled.fetch_add(1, Ordering::SeqCst);
+                Ok(Action::Done)
+            }
+        }
+    });
+
+    let update = |update_id| {
+        let mut message = api::Message::new("qubyte", "hello");
+        message.chat.id = chat.chat_id;
+        api::Update {
+            update_id,
+            message: Some(message),
+            ..Default::default()
+        }
+    };
+
+    router.dispatch(update(1)).await.unwrap();
+    router.dispatch(update(2)).await.unwrap();
+    assert!(router.dispatch(update(1)).await.unwrap().is_empty());
+    assert_eq!(handled.load(Ordering::SeqCst), 2);
+
+    // Only the last two updates are remembered.
+    router.dispatch(update(3)).await.unwrap();
+    router.dispatch(update(1)).await.unwrap();
+    assert!(router.dispatch(update(3)).await.unwrap().is_empty());
+    assert_eq!(handled.load(Ordering::SeqCst), 4);
+}
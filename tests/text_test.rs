@@ -0,0 +1,59 @@
+/// Test the text helpers.
+use mobot::{text::command_args, Text};
+
+#[test]
+fn command_args_quoted() {
+    assert_eq!(
+        command_args(r#"/add "hello world" foo"#),
+        vec!["hello world", "foo"]
+    );
+    assert_eq!(command_args("/add  foo   bar "), vec!["foo", "bar"]);
+    assert_eq!(command_args("/start@mobot"), Vec::<String>::new());
+    assert_eq!(command_args(r#"/say "" x"#), vec!["", "x"]);
+    assert_eq!(
+        command_args(r#"/say "she said \"hi\"""#),
+        vec![r#"she said "hi""#]
+    );
+}
+
+#[test]
+fn command_args_unterminated_quote() {
+    assert_eq!(
+        command_args(r#"/add foo "hello world"#),
+        vec!["foo", "hello world"]
+    );
+}
+
+#[test]
+fn builder_spoiler() {
+    let text = Text::builder()
+        .text("Warning: ")
+        .spoiler(Text::builder().text("a < b ").bold("and").italic(" c_d."))
+        .text(r" C:\.");
+
+    assert_eq!(
+        text.to_markdown().to_string(),
+        r"Warning: ||a < b *and*_ c\_d\._|| C:\\\."
+    );
+    assert_eq!(
+        text.to_html().to_string(),
+        r#"Warning: <span class="tg-spoiler">a &lt; b <b>and</b><i> c_d.</i></span> C:\."#
+    );
+}
+
+#[test]
+fn builder_custom_emoji() {
+    let text = Text::builder()
+        .custom_emoji("👍", "5368324170671202286")
+        .spoiler(Text::builder().code("x`y"));
+
+    assert!(matches!(text.to_markdown(), Text::Markdown(_)));
+    assert_eq!(
+        text.to_markdown().to_string(),
+        "![👍](tg://emoji?id=5368324170671202286)||`x\\`y`||"
+    );
+    assert_eq!(
+        text.to_html().to_string(),
+        r#"<tg-emoji emoji-id="5368324170671202286">👍</tg-emoji><span class="tg-spoiler"><code>x`y</code></span>"#
+    );
+}
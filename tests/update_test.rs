@@ -0,0 +1,294 @@
+/// Test that updates from Telegram are deserialized robustly.
+use mobot::{api::ApiResponse, *};
+
+#[test]
+fn unknown_update_types() {
+    let body = r#"{
+        "ok": true,
+        "result": [
+            {
+                "update_id": 1,
+                "chat_boost": {
+                    "chat": {"id": -1001, "type": "channel", "title": "news"},
+                    "boost": {
+                        "boost_id": "abc",
+                        "add_date": 1700000000,
+                        "expiration_date": 1800000000,
+                        "source": {"source": "premium", "user": {"id": 7, "is_bot": false, "first_name": "bob"}}
+                    }
+                }
+            },
+            {
+                "update_id": 2,
+                "message": {
+                    "message_id": 10,
+                    "date": 1700000000,
+                    "chat": {"id": -1002},
+                    "new_chat_members": [{"id": 8, "is_bot": false, "first_name": "alice"}]
+                }
+            }
+        ]
+    }"#;
+
+    let updates = ApiResponse::<Vec<api::Update>>::from_str(body)
+        .unwrap()
+        .result()
+        .unwrap()
+        .clone();
+
+    assert_eq!(updates.len(), 2);
+
+    // Unmodeled update types are kept as JSON, and show up as Update::Unknown.
+    assert_eq!(updates[0].update_id, 1);
+    assert!(updates[0].message.is_none());
+    assert_eq!(updates[0].other["chat_boost"]["boost"]["boost_id"], "abc");
+    assert!(matches!(Update::from(updates[0].clone()), Update::Unknown));
+
+    // Service messages with missing fields still deserialize.
+    let message = updates[1].message.as_ref().unwrap();
+    assert_eq!(message.chat.id, -1002);
+    assert!(message.text.is_none());
+}
+
+#[test]
+fn callback_query_without_message() {
+    let update = Update::CallbackQuery(api::CallbackQuery {
+        id: "1".into(),
+        from: "qubyte".into(),
+        inline_message_id: Some("inline1".into()),
+        data: Some("yes".into()),
+        ..Default::default()
+    });
+
+    // Callback queries from inline messages have no chat, but shouldn't panic.
+    assert!(update.chat_id().is_err());
+    assert!(update.message_id().is_err());
+    assert_eq!(update.inline_message_id().unwrap(), "inline1");
+}
+
+#[test]
+fn display_non_text_updates() {
+    let mut message = api::Message::fake("qubyte");
+    message.photo = Some(vec![]);
+
+    assert_eq!(Update::Message(message).to_string(), "<non-text message>");
+    assert_eq!(Update::Unknown.to_string(), "<unknown update>");
+    assert_eq!(
+        Update::Message(api::Message::new("qubyte", "hello")).to_string(),
+        "hello"
+    );
+}
+
+#[tokio::test]
+async fn updates_stream() {
+    use futures::StreamExt;
+
+    let fakeserver = fake::FakeAPI::new();
+    let api = API::new(Client::new("token".to_string()).with_post_handler(fakeserver.clone()));
+    let chat = fakeserver.create_chat("qubyte").await;
+
+    chat.send_text("ping1").await.unwrap();
+    chat.send_text("ping2").await.unwrap();
+
+    let updates = api.updates_stream(api::GetUpdatesRequest::new().with_timeout(1));
+    futures::pin_mut!(updates);
+
+    let update = updates.next().await.unwrap().unwrap();
+    assert_eq!(update.message.unwrap().text.unwrap(), "ping1");
+
+    let update = updates.next().await.unwrap().unwrap();
+    assert_eq!(update.message.unwrap().text.unwrap(), "ping2");
+}
+
+#[test]
+fn localize() {
+    let api = std::sync::Arc::new(API::new(Client::new("token".to_string())));
+    let localizer: event::Localizer = std::sync::Arc::new(|lang, key| format!("{}:{}", lang, key));
+
+    let mut message = api::Message::new("qubyte", "hola");
+    message.from.as_mut().unwrap().language_code = Some("es".into());
+    let e = Event::new(api.clone(), Update::Message(message)).with_localizer(localizer.clone());
+    assert_eq!(e.tr("greeting"), "es:greeting");
+
+    // Fall back to the default language if the sender's language is unknown.
+    let e = Event::new(
+        api.clone(),
+        Update::Message(api::Message::new("qubyte", "hi")),
+    )
+    .with_localizer(localizer);
+    assert_eq!(e.tr("greeting"), "en:greeting");
+
+    // Without a localizer, the key is returned as is.
+    let e = Event::new(api, Update::Message(api::Message::new("qubyte", "hi")));
+    assert_eq!(e.tr("greeting"), "greeting");
+}
+
+#[test]
+fn reply_to_message() {
+    let body = r#"{
+        "ok": true,
+        "result": [
+            {
+                "update_id": 1,
+                "message": {
+                    "message_id": 11,
+                    "date": 1700000000,
+                    "chat": {"id": 7, "type": "private"},
+                    "text": "42",
+                    "reply_to_message": {
+                        "message_id": 10,
+                        "date": 1699999999,
+                        "chat": {"id": 7, "type": "private"},
+                        "from": {"id": 1, "is_bot": true, "first_name": "mobot"},
+                        "text": "How old are you?"
+                    }
+                }
+            }
+        ]
+    }"#;
+
+    let updates = ApiResponse::<Vec<api::Update>>::from_str(body)
+        .unwrap()
+        .result()
+        .unwrap()
+        .clone();
+
+    let message = updates[0].message.as_ref().unwrap();
+    assert!(message.is_reply());
+    assert_eq!(message.replied_text(), Some("How old are you?"));
+    assert_eq!(message.reply_to_message.as_ref().unwrap().message_id, 10);
+
+    let message = api::Message::new("qubyte", "hello");
+    assert!(!message.is_reply());
+    assert_eq!(message.replied_text(), None);
+}
+
+#[test]
+fn custom_emoji_only() {
+    let message = |text: &str, entities: &str| -> api::Message {
+        serde_json::from_str(&format!(
+            r#"{{
+                "message_id": 1,
+                "date": 1700000000,
+                "chat": {{"id": 7, "type": "private"}},
+                "text": "{}",
+                "entities": [{}]
+            }}"#,
+            text, entities
+        ))
+        .unwrap()
+    };
+
+    // "👍" is 2 UTF-16 code units.
+    let thumbs = r#"{"type": "custom_emoji", "offset": 0, "length": 2, "custom_emoji_id": "1"}"#;
+    let m = message("👍", thumbs);
+    assert_eq!(m.text.as_deref(), Some("👍"));
+    assert!(m.has_only_custom_emoji());
+
+    let m = message(
+        "👍 🔥",
+        &format!(
+            r#"{}, {{"type": "custom_emoji", "offset": 3, "length": 2, "custom_emoji_id": "2"}}"#,
+            thumbs
+        ),
+    );
+    assert!(m.has_only_custom_emoji());
+
+    // Regular emoji, or custom emoji mixed with text, don't count.
+    assert!(!message("👍", "").has_only_custom_emoji());
+    assert!(!message("👍 nice", thumbs).has_only_custom_emoji());
+    assert!(!api::Message::new("qubyte", "").has_only_custom_emoji());
+}
+
+#[test]
+fn forward_origin() {
+    let message: api::Message = serde_json::from_str(
+        r#"{
+            "message_id": 1,
+            "date": 1700000100,
+            "chat": {"id": 7, "type": "private"},
+            "text": "news",
+            "is_automatic_forward": true,
+            "forward_origin": {
+                "type": "channel",
+                "date": 1700000000,
+                "chat": {"id": -1001, "type": "channel", "title": "News"},
+                "message_id": 42
+            }
+        }"#,
+    )
+    .unwrap();
+    match message.origin().unwrap() {
+        api::MessageOrigin::Channel {
+            chat, message_id, ..
+        } => {
+            assert_eq!(chat.id, -1001);
+            assert_eq!(message_id, 42);
+        }
+        origin => panic!("unexpected origin: {:?}", origin),
+    }
+    assert!(message.is_automatic_forward);
+    assert!(!message.has_protected_content);
+
+    // Messages with only the legacy fields are mapped to an origin.
+    let message: api::Message = serde_json::from_str(
+        r#"{
+            "message_id": 2,
+            "date": 1700000100,
+            "chat": {"id": 7, "type": "private"},
+            "text": "psst",
+            "forward_sender_name": "Anonymous",
+            "forward_date": 1700000000
+        }"#,
+    )
+    .unwrap();
+    assert!(!message.is_automatic_forward);
+    let origin = message.origin().unwrap();
+    assert_eq!(origin.date(), Some(1700000000));
+    assert!(matches!(
+        origin,
+        api::MessageOrigin::HiddenUser { sender_user_name, .. } if sender_user_name == "Anonymous"
+    ));
+
+    // Origin types added to the Bot API later don't break parsing the message.
+    let message: api::Message = serde_json::from_str(
+        r#"{
+            "message_id": 3,
+            "date": 1700000100,
+            "chat": {"id": 7, "type": "private"},
+            "text": "from the future",
+            "forward_origin": {"type": "hologram", "date": 1700000000, "sender": "R2"}
+        }"#,
+    )
+    .unwrap();
+    let origin = message.origin().unwrap();
+    assert!(matches!(origin, api::MessageOrigin::Unknown));
+    assert_eq!(origin.date(), None);
+
+    let update = Update::Message(api::Message::new("qubyte", "hello"));
+    assert!(update.forward_origin().is_err());
+}
+
+#[test]
+fn largest_photo() {
+    let size = |file_id: &str, width, height| api::PhotoSize {
+        file_id: file_id.into(),
+        width,
+        height,
+        ..Default::default()
+    };
+    let mut message = api::Message::fake("qubyte");
+    message.photo = Some(vec![
+        size("medium", 320, 240),
+        size("large", 1280, 960),
+        size("thumb", 90, 67),
+    ]);
+
+    assert_eq!(
+        Update::Message(message).largest_photo().unwrap().file_id,
+        "large"
+    );
+    assert!(Update::Message(api::Message::new("qubyte", "hello"))
+        .largest_photo()
+        .is_err());
+}
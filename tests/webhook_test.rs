@@ -0,0 +1,26 @@
+/// Test webhook secret verification.
+use mobot::webhook::verify_webhook_secret;
+
+#[test]
+fn verify_secret() {
+    let headers = vec![
+        ("Content-Type", "application/json"),
+        ("X-Telegram-Bot-Api-Secret-Token", "s3cr3t"),
+    ];
+
+    assert!(verify_webhook_secret(headers.clone(), "s3cr3t"));
+    assert!(!verify_webhook_secret(headers.clone(), "s3cr3"));
+    assert!(!verify_webhook_secret(headers, "wrong!"));
+
+    // Header names are case-insensitive.
+    assert!(verify_webhook_secret(
+        vec![("x-telegram-bot-api-secret-token", "s3cr3t")],
+        "s3cr3t"
+    ));
+
+    // Missing header.
+    assert!(!verify_webhook_secret(
+        vec![("Content-Type", "application/json")],
+        "s3cr3t"
+    ));
+}